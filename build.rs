@@ -0,0 +1,62 @@
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// Writes a golden test for every known answer in answers.txt whose input file is present in
+/// inputs/, for `src/golden.rs` to include. Malformed lines are skipped here, since the tests for
+/// `answers.rs` report them.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=answers.txt");
+    println!("cargo:rerun-if-changed=inputs");
+
+    let answers = fs::read_to_string("answers.txt").unwrap_or_default();
+
+    let mut tests = String::new();
+    for line in answers.lines() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (slow, line) = match line.strip_prefix("slow ") {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        let fields: Vec<&str> = line.splitn(4, ' ').collect();
+        if fields.len() != 4 {
+            continue;
+        }
+
+        let (day, input) = (fields[0], fields[2]);
+        let part = match fields[1] {
+            "one" | "1" => "One",
+            "two" | "2" => "Two",
+            _ => continue,
+        };
+
+        if day.parse::<u32>().is_err()
+            || !input.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            || !Path::new("inputs").join(format!("{}.txt", input)).exists()
+        {
+            continue;
+        }
+
+        if slow {
+            tests.push_str("#[ignore = \"slow\"]\n");
+        }
+        writeln!(
+            tests,
+            "#[test]\nfn day_{day}_part_{part_name}_{input}() {{\n    check({day}, Part::{part}, \"{input}\");\n}}\n",
+            day = day,
+            part_name = part.to_lowercase(),
+            part = part,
+            input = input
+        )
+        .unwrap();
+    }
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("golden_tests.rs"), tests).unwrap();
+}
@@ -0,0 +1,170 @@
+use std::fmt;
+
+use num_bigint::BigInt;
+use serde_json::{json, Value};
+
+use crate::search::Best;
+
+/// The result of solving one part of a day.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Answer {
+    Integer(i64),
+    BigInteger(BigInt),
+    Text(String),
+    /// Rows of a rendered picture that has to be read by eye, such as the CRT output of day 10.
+    Image(Vec<String>),
+    /// The best answer found before a search ran out of time or was cancelled, which might not be
+    /// the right one.
+    Incomplete(Box<Answer>),
+}
+
+impl Answer {
+    pub fn image(s: &str) -> Answer {
+        Answer::Image(s.lines().map(|l| l.to_string()).collect())
+    }
+
+    /// Marks the answer as the best found before a search was stopped early.
+    pub fn incomplete(self) -> Answer {
+        match self {
+            Answer::Incomplete(_) => self,
+            answer => Answer::Incomplete(Box::new(answer)),
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !matches!(self, Answer::Incomplete(_))
+    }
+
+    /// The name of the kind of answer, as used in JSON output. Incomplete answers are named after
+    /// the kind of answer they hold.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Answer::Integer(_) => "integer",
+            Answer::BigInteger(_) => "big_integer",
+            Answer::Text(_) => "text",
+            Answer::Image(_) => "image",
+            Answer::Incomplete(answer) => answer.type_name(),
+        }
+    }
+
+    /// Converts the answer to JSON. Big integers are written as strings so that they survive
+    /// parsers that read every number as a double, and images as an array of rows.
+    pub fn to_json(&self) -> Value {
+        match self {
+            Answer::Integer(value) => json!(value),
+            Answer::BigInteger(value) => json!(value.to_string()),
+            Answer::Text(value) => json!(value),
+            Answer::Image(rows) => json!(rows),
+            Answer::Incomplete(answer) => answer.to_json(),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::BigInteger(value) => write!(f, "{}", value),
+            Answer::Text(value) => f.write_str(value),
+            Answer::Image(rows) => f.write_str(&rows.join("\n")),
+            Answer::Incomplete(answer) => write!(f, "{} (incomplete)", answer),
+        }
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(value: i32) -> Answer {
+        Answer::Integer(value as i64)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Answer {
+        Answer::Integer(value)
+    }
+}
+
+impl From<BigInt> for Answer {
+    fn from(value: BigInt) -> Answer {
+        Answer::BigInteger(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Answer {
+        Answer::Text(value)
+    }
+}
+
+impl<T: Into<Answer>> From<Best<T>> for Answer {
+    fn from(best: Best<T>) -> Answer {
+        let answer = best.value.into();
+
+        if best.complete {
+            answer
+        } else {
+            answer.incomplete()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!("-12", Answer::from(-12).to_string());
+        assert_eq!("1514285714288", Answer::from(1514285714288i64).to_string());
+        assert_eq!(
+            "123456789012345678901234567890",
+            Answer::from("123456789012345678901234567890".parse::<BigInt>().unwrap()).to_string()
+        );
+        assert_eq!("CMZ", Answer::from("CMZ".to_string()).to_string());
+    }
+
+    #[test]
+    fn test_image_display() {
+        let image = Answer::image("#..#\n.##.\n");
+
+        assert_eq!(
+            Answer::Image(vec!["#..#".to_string(), ".##.".to_string()]),
+            image
+        );
+        assert_eq!("#..#\n.##.", image.to_string());
+    }
+
+    #[test]
+    fn test_incomplete() {
+        let complete = Answer::from(Best {
+            value: 1651,
+            complete: true,
+        });
+        let incomplete = Answer::from(Best {
+            value: 1651,
+            complete: false,
+        });
+
+        assert_eq!(Answer::Integer(1651), complete);
+        assert!(complete.is_complete());
+        assert!(!incomplete.is_complete());
+        assert_eq!("1651 (incomplete)", incomplete.to_string());
+        assert_eq!(json!(1651), incomplete.to_json());
+        assert_eq!("integer", incomplete.type_name());
+        assert_eq!(incomplete, incomplete.clone().incomplete());
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(json!(-12), Answer::from(-12).to_json());
+        assert_eq!(
+            json!("123456789012345678901234567890"),
+            Answer::from("123456789012345678901234567890".parse::<BigInt>().unwrap()).to_json()
+        );
+        assert_eq!(
+            json!(["#..#", ".##."]),
+            Answer::image("#..#\n.##.\n").to_json()
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::answer::Answer;
+use crate::solution::Part;
+
+/// The file of known answers kept at the root of the repository.
+pub const ANSWERS_PATH: &str = "answers.txt";
+
+/// Known correct answers, keyed by day, part and the name of the input file they were found for.
+#[derive(Debug, Default)]
+pub struct ExpectedAnswers {
+    answers: BTreeMap<(u32, Part, String), String>,
+    slow: BTreeSet<(u32, Part, String)>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Check {
+    Pass,
+    Fail(String),
+    Unknown,
+}
+
+impl ExpectedAnswers {
+    pub fn load(path: &Path) -> Result<ExpectedAnswers, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+        contents.parse()
+    }
+
+    pub fn get(&self, day: u32, part: Part, input: &str) -> Option<&str> {
+        self.answers
+            .get(&(day, part, input.to_string()))
+            .map(|a| a.as_str())
+    }
+
+    /// Whether the answer is marked as slow to find, so that its golden test is ignored by default.
+    pub fn is_slow(&self, day: u32, part: Part, input: &str) -> bool {
+        self.slow.contains(&(day, part, input.to_string()))
+    }
+
+    /// All of the known answers as `(day, part, input, answer)`, ordered by day.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, Part, &str, &str)> {
+        self.answers
+            .iter()
+            .map(|((day, part, input), answer)| (*day, *part, input.as_str(), answer.as_str()))
+    }
+
+    pub fn check(&self, day: u32, part: Part, input: &str, answer: &Answer) -> Check {
+        match self.get(day, part, input) {
+            None => Check::Unknown,
+            Some(expected) if expected == answer.to_string() => Check::Pass,
+            Some(expected) => Check::Fail(expected.to_string()),
+        }
+    }
+}
+
+impl FromStr for ExpectedAnswers {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ExpectedAnswers, String> {
+        let mut answers = BTreeMap::new();
+        let mut slow = BTreeSet::new();
+        for (i, line) in s.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (is_slow, line) = match line.strip_prefix("slow ") {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+
+            let parts: Vec<&str> = line.splitn(4, ' ').collect();
+            if parts.len() != 4 {
+                return Err(format!(
+                    "Line {}: expected \"<day> <part> <input> <answer>\", found \"{}\"",
+                    i + 1,
+                    line
+                ));
+            }
+
+            let day: u32 = parts[0]
+                .parse()
+                .map_err(|_| format!("Line {}: invalid day \"{}\"", i + 1, parts[0]))?;
+            let part: Part = parts[1]
+                .parse()
+                .map_err(|e| format!("Line {}: {}", i + 1, e))?;
+
+            let key = (day, part, parts[2].to_string());
+            if is_slow {
+                slow.insert(key.clone());
+            }
+            answers.insert(key, parts[3].replace("\\n", "\n"));
+        }
+
+        Ok(ExpectedAnswers { answers, slow })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let answers =
+            ExpectedAnswers::from_str("# comment\n\n1 one one 74394\n10 two ten #.\\n.#\n")
+                .unwrap();
+
+        assert_eq!(
+            Check::Pass,
+            answers.check(1, Part::One, "one", &Answer::Integer(74394))
+        );
+        assert_eq!(
+            Check::Fail("74394".to_string()),
+            answers.check(1, Part::One, "one", &Answer::Integer(5))
+        );
+        assert_eq!(
+            Check::Unknown,
+            answers.check(1, Part::Two, "one", &Answer::Integer(5))
+        );
+        assert_eq!(
+            Check::Pass,
+            answers.check(10, Part::Two, "ten", &Answer::image("#.\n.#\n"))
+        );
+    }
+
+    #[test]
+    fn test_slow_flag() {
+        let answers =
+            ExpectedAnswers::from_str("slow 19 one nineteen 1\n19 two nineteen 2\n").unwrap();
+
+        assert!(answers.is_slow(19, Part::One, "nineteen"));
+        assert!(!answers.is_slow(19, Part::Two, "nineteen"));
+        assert_eq!(Some("1"), answers.get(19, Part::One, "nineteen"));
+    }
+
+    #[test]
+    fn test_malformed_line() {
+        assert!(ExpectedAnswers::from_str("1 three one 5").is_err());
+        assert!(ExpectedAnswers::from_str("1 one").is_err());
+    }
+
+    #[test]
+    fn test_answers_file_parses() {
+        ExpectedAnswers::load(Path::new(ANSWERS_PATH)).unwrap();
+    }
+}
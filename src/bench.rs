@@ -0,0 +1,236 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::params::Params;
+use crate::solution::{Day, Part};
+use crate::Error;
+
+/// Summary statistics over the timings of repeated runs.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Stats {
+        assert!(!samples.is_empty());
+
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+
+        let seconds: Vec<f64> = samples.iter().map(|s| s.as_secs_f64()).collect();
+        let mean = seconds.iter().sum::<f64>() / seconds.len() as f64;
+        let variance =
+            seconds.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / seconds.len() as f64;
+
+        Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            std_dev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+
+    /// The statistics as a JSON object, in nanoseconds.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "min_ns": self.min.as_nanos() as u64,
+            "median_ns": self.median.as_nanos() as u64,
+            "mean_ns": self.mean.as_nanos() as u64,
+            "std_dev_ns": self.std_dev.as_nanos() as u64,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub day: u32,
+    pub part: Part,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+/// Times parsing and solving separately, running each `iterations` times after `warmup` untimed
+/// runs.
+pub fn bench(
+    day: &Day,
+    part: Part,
+    input: &str,
+    params: &Params,
+    warmup: usize,
+    iterations: usize,
+) -> Result<BenchResult, Error> {
+    for _ in 0..warmup {
+        let parsed = day.parse(&mut input.as_bytes())?;
+        day.solve(part, parsed.as_ref(), params)?;
+    }
+
+    let mut parse_samples = vec![];
+    let mut solve_samples = vec![];
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        let parsed = day.parse(&mut input.as_bytes())?;
+        parse_samples.push(start.elapsed());
+
+        let start = Instant::now();
+        day.solve(part, parsed.as_ref(), params)?;
+        solve_samples.push(start.elapsed());
+    }
+
+    Ok(BenchResult {
+        day: day.number,
+        part,
+        parse: Stats::from_samples(&parse_samples),
+        solve: Stats::from_samples(&solve_samples),
+    })
+}
+
+/// Median timings from an earlier benchmark run, keyed by day, part and input name.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    entries: BTreeMap<(u32, Part, String), (Duration, Duration)>,
+}
+
+impl Baseline {
+    /// Loads a baseline file, treating a missing file as an empty baseline.
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+        if !path.exists() {
+            return Ok(Baseline::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+        let mut entries = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let parts: Vec<&str> = line.split(' ').collect();
+            let invalid = || {
+                format!(
+                    "{} line {}: expected \"<day> <part> <input> <parse ns> <solve ns>\", found \"{}\"",
+                    path.display(),
+                    i + 1,
+                    line
+                )
+            };
+            if parts.len() != 5 {
+                return Err(invalid());
+            }
+
+            let day: u32 = parts[0].parse().map_err(|_| invalid())?;
+            let part: Part = parts[1].parse().map_err(|_| invalid())?;
+            let parse: u64 = parts[3].parse().map_err(|_| invalid())?;
+            let solve: u64 = parts[4].parse().map_err(|_| invalid())?;
+
+            entries.insert(
+                (day, part, parts[2].to_string()),
+                (Duration::from_nanos(parse), Duration::from_nanos(solve)),
+            );
+        }
+
+        Ok(Baseline { entries })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let mut contents =
+            "# Benchmark medians: <day> <part> <input> <parse ns> <solve ns>\n".to_string();
+        for ((day, part, input), (parse, solve)) in self.entries.iter() {
+            contents.push_str(&format!(
+                "{} {} {} {} {}\n",
+                day,
+                part.name(),
+                input,
+                parse.as_nanos(),
+                solve.as_nanos()
+            ));
+        }
+
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// Returns the baseline `(parse, solve)` medians for the given run, if there are any.
+    pub fn get(&self, day: u32, part: Part, input: &str) -> Option<(Duration, Duration)> {
+        self.entries.get(&(day, part, input.to_string())).copied()
+    }
+
+    pub fn record(&mut self, result: &BenchResult, input: &str) {
+        self.entries.insert(
+            (result.day, result.part, input.to_string()),
+            (result.parse.median, result.solve.median),
+        );
+    }
+}
+
+/// Whether `current` is slower than `baseline` by more than `threshold` (e.g. 0.1 for 10%).
+pub fn is_regression(baseline: Duration, current: Duration, threshold: f64) -> bool {
+    current.as_secs_f64() > baseline.as_secs_f64() * (1.0 + threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Read;
+
+    use crate::get_days;
+
+    #[test]
+    fn test_stats() {
+        let samples: Vec<Duration> = [4, 1, 3, 2]
+            .iter()
+            .map(|ms| Duration::from_millis(*ms))
+            .collect();
+
+        let stats = Stats::from_samples(&samples);
+
+        assert_eq!(Duration::from_millis(1), stats.min);
+        assert_eq!(Duration::from_micros(2500), stats.median);
+        assert_eq!(Duration::from_micros(2500), stats.mean);
+        assert_eq!(1118, stats.std_dev.as_micros());
+    }
+
+    #[test]
+    fn test_bench() {
+        let days = get_days();
+        let day = days.iter().find(|d| d.number == 1).unwrap();
+
+        let mut input = String::new();
+        File::open(day.example_path())
+            .unwrap()
+            .read_to_string(&mut input)
+            .unwrap();
+
+        let result = bench(day, Part::Two, &input, &day.default_params(true), 1, 3).unwrap();
+
+        assert_eq!(1, result.day);
+        assert!(result.parse.min <= result.parse.median);
+        assert!(result.solve.min <= result.solve.median);
+    }
+
+    #[test]
+    fn test_regression() {
+        let baseline = Duration::from_millis(100);
+
+        assert!(!is_regression(baseline, Duration::from_millis(105), 0.1));
+        assert!(is_regression(baseline, Duration::from_millis(115), 0.1));
+    }
+}
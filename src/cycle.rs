@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use log::debug;
+
+/// Where a simulation starts repeating itself: the state after `start + length` steps is the same
+/// as the state after `start` steps.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Steps a simulation until it reaches a state that it has been in before, as identified by `key`.
+///
+/// `step` is given the state and the number of steps taken so far. Only returns once a state
+/// repeats, so the simulation must eventually repeat.
+pub fn find_cycle<S, K>(
+    initial: S,
+    step: impl FnMut(S, usize) -> S,
+    key: impl FnMut(&S) -> K,
+) -> Cycle
+where
+    K: Eq + Hash,
+{
+    let (_, cycle) = run_until_cycle(initial, None, step, key, |_| 0);
+
+    cycle.expect("the simulation ran without a step limit")
+}
+
+/// Finds the value of `metric` after `num_steps` steps of a simulation, skipping ahead once the
+/// simulation starts to repeat.
+///
+/// Within a cycle the metric has to change by the same amount each time around, like the height of
+/// a tower that is built up in a repeating pattern.
+pub fn extrapolate<S, K>(
+    initial: S,
+    num_steps: u64,
+    step: impl FnMut(S, usize) -> S,
+    key: impl FnMut(&S) -> K,
+    metric: impl FnMut(&S) -> i64,
+) -> i64
+where
+    K: Eq + Hash,
+{
+    let (metrics, cycle) = run_until_cycle(initial, Some(num_steps), step, key, metric);
+
+    let cycle = match cycle {
+        Some(cycle) => cycle,
+        None => return *metrics.last().unwrap(),
+    };
+
+    let cycle_end = cycle.start + cycle.length;
+    let gain_per_cycle = metrics[cycle_end] - metrics[cycle.start];
+
+    // The target is some number of whole cycles after a step within the first cycle
+    let remaining = num_steps - cycle.start as u64;
+    let num_cycles = remaining / cycle.length as u64;
+    let offset = (remaining % cycle.length as u64) as usize;
+
+    metrics[cycle.start + offset] + gain_per_cycle * num_cycles as i64
+}
+
+/// Records the metric of each state until either a state repeats or `max_steps` have been taken.
+fn run_until_cycle<S, K>(
+    mut state: S,
+    max_steps: Option<u64>,
+    mut step: impl FnMut(S, usize) -> S,
+    mut key: impl FnMut(&S) -> K,
+    mut metric: impl FnMut(&S) -> i64,
+) -> (Vec<i64>, Option<Cycle>)
+where
+    K: Eq + Hash,
+{
+    let mut seen: HashMap<K, usize> = HashMap::new();
+    let mut metrics = vec![];
+
+    loop {
+        let steps = metrics.len();
+        metrics.push(metric(&state));
+
+        if Some(steps as u64) == max_steps {
+            return (metrics, None);
+        }
+
+        if let Some(start) = seen.insert(key(&state), steps) {
+            let cycle = Cycle {
+                start,
+                length: steps - start,
+            };
+            debug!("Found {:?}", cycle);
+
+            return (metrics, Some(cycle));
+        }
+
+        state = step(state, steps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    /// A counter that runs 0, 1, 2, 3, 4, 2, 3, 4, ... and a running total of its values.
+    fn step((value, total): (i64, i64), _: usize) -> (i64, i64) {
+        let value = if value == 4 { 2 } else { value + 1 };
+
+        (value, total + value)
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let cycle = find_cycle((0, 0), step, |(value, _)| *value);
+
+        assert_eq!(
+            Cycle {
+                start: 2,
+                length: 3
+            },
+            cycle
+        );
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        for num_steps in 0..20 {
+            let mut state = (0, 0);
+            for i in 0..num_steps {
+                state = step(state, i);
+            }
+
+            let actual = extrapolate((0, 0), num_steps as u64, step, |s| s.0, |s| s.1);
+
+            assert_eq!(state.1, actual, "after {} steps", num_steps);
+        }
+
+        assert_eq!(
+            2999999999998,
+            extrapolate((0, 0), 1000000000000, step, |s| s.0, |s| s.1)
+        );
+    }
+}
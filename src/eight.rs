@@ -1,304 +1,144 @@
-use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = count_visible_trees(&values);
+use crate::answer::Answer;
+use crate::grid::{Direction, Grid, Position};
+use crate::image::{Color, Image};
+use crate::params::Params;
+use crate::parse::{read_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Part, Solution, SolveError};
 
-    println!("{}", answer);
-}
-
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_best_senic_score(&values);
-
-    println!("{}", answer);
-}
-
-#[derive(Eq, PartialEq, Hash, Debug, Clone)]
-struct Position {
-    row: usize,
-    column: usize,
-}
-
-impl Position {
-    fn new(r: usize, c: usize) -> Position {
-        Position { row: r, column: c }
-    }
+pub struct DayEight;
 
-    fn moved(&self, row_change: i32, column_change: i32) -> Option<Position> {
-        let new_row = row_change + self.row as i32;
-        let new_column = column_change + self.column as i32;
+impl Solution for DayEight {
+    type Input = Grid<i32>;
 
-        if new_row < 0 || new_column < 0 {
-            return None;
-        }
-
-        Some(Position::new(new_row as usize, new_column as usize))
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
     }
-}
 
-struct Grid {
-    trees: Vec<Vec<i32>>,
-}
-
-impl Grid {
-    fn from_lines(lines: &[String]) -> Grid {
-        let mut trees = vec![vec![0; lines.get(0).unwrap().len()]; lines.len()];
-
-        for (i, line) in lines.iter().enumerate() {
-            for (j, c) in line.chars().enumerate() {
-                trees[i][j] = c.to_string().parse().unwrap();
-            }
-        }
-
-        Grid { trees }
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(count_visible_trees(input).into())
     }
 
-    fn get_height(&self) -> usize {
-        self.trees.len()
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_best_senic_score(input).into())
     }
 
-    fn get_width(&self) -> usize {
-        self.trees.get(1).unwrap().len()
+    fn image(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        Ok(Some(match part {
+            Part::One => visibility_image(input),
+            Part::Two => senic_score_image(input),
+        }))
     }
 
-    fn contains(&self, pos: &Position) -> bool {
-        // Note: these first two are implied by usize
-        // pos.row >= 0
-        // && pos.column >= 0
-        pos.row < self.get_height() && pos.column < self.get_width()
-    }
-
-    fn get_visible_trees(&self) -> Vec<Position> {
-        let mut visibile = VisibilityMap::new();
-
-        for r in 0..self.get_height() {
-            for c in 0..self.get_width() {
-                let pos = Position::new(r, c);
-
-                // Mark edges
-                if r == 0 || c == 0 || r == self.get_height() - 1 || c == self.get_width() - 1 {
-                    visibile.mark_visible(&pos);
-                    continue;
-                }
-
-                if visibile.is_known_visible(&pos) {
-                    continue;
-                }
-
-                for direction in vec![
-                    Direction::Up,
-                    Direction::Down,
-                    Direction::Left,
-                    Direction::Right,
-                ] {
-                    let start = match direction {
-                        Direction::Up => Position::new(self.get_height() - 1, c),
-                        Direction::Down => Position::new(0, c),
-                        Direction::Left => Position::new(r, self.get_width() - 1),
-                        Direction::Right => Position::new(r, 0),
-                    };
-
-                    let mut p2 = start;
-                    let mut prev_height = -9999;
-                    loop {
-                        let height = self.get(&p2);
-                        if height > prev_height {
-                            if p2 == pos {
-                                visibile.mark_visible(&pos);
-                                break;
-                            } else {
-                                prev_height = height;
-                            }
-                        }
-
-                        if p2 == pos {
-                            break;
-                        }
-
-                        let new_p2 = direction.advance(&p2);
-                        if let Some(new_p2) = new_p2 {
-                            if self.contains(&new_p2) {
-                                p2 = new_p2;
-                            } else {
-                                break;
-                            }
-                        } else {
-                            break;
-                        }
-                    }
-                }
-            }
-        }
-
-        visibile.known_visibile.iter().cloned().collect()
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
     }
+}
 
-    fn get(&self, pos: &Position) -> i32 {
-        self.trees[pos.row][pos.column]
-    }
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Grid<i32>, ParseError> {
+    Grid::parse(&read_lines(reader)?, "a row of tree heights (0-9)", |c| {
+        c.to_digit(10).map(|d| d as i32)
+    })
+}
 
-    fn calc_senic_score(&self, pos: &Position) -> i32 {
-        let mut score = 1;
-        let source_height = self.get(pos);
+/// The heights of the trees from a position to the edge of the grid in the given direction, not
+/// including the tree at the position itself.
+fn trees_towards_edge<'a>(
+    grid: &'a Grid<i32>,
+    position: &Position,
+    direction: Direction,
+) -> impl Iterator<Item = i32> + 'a {
+    let mut current = *position;
+
+    std::iter::from_fn(move || {
+        current = current.step(direction, 1);
+        grid.get(&current).copied()
+    })
+}
 
-        for direction in vec![
-            Direction::Up,
-            Direction::Down,
-            Direction::Left,
-            Direction::Right,
-        ] {
-            let mut p2 = pos.clone();
-            let mut count = 1;
-            loop {
-                let new_p2 = direction.advance(&p2);
+fn is_visible(grid: &Grid<i32>, position: &Position) -> bool {
+    let height = grid.get(position).unwrap();
 
-                if new_p2.is_none() {
-                    count -= 1;
-                    break;
-                }
+    Direction::all().iter().any(|direction| {
+        trees_towards_edge(grid, position, *direction).all(|other| other < *height)
+    })
+}
 
-                let new_p2 = new_p2.unwrap();
-                if !self.contains(&new_p2) {
-                    count -= 1;
-                    break;
-                }
-
-                let new_height = self.get(&new_p2);
-                if new_height >= source_height {
-                    break;
-                }
+fn calc_senic_score(grid: &Grid<i32>, position: &Position) -> i32 {
+    let height = grid.get(position).unwrap();
 
+    Direction::all()
+        .iter()
+        .map(|direction| {
+            let mut count = 0;
+            for other in trees_towards_edge(grid, position, *direction) {
                 count += 1;
-                p2 = new_p2;
-            }
-            score *= count;
-        }
-
-        score
-    }
-
-    fn find_best_senic_score(&self) -> i32 {
-        let mut max_senic_score = 0;
-
-        for r in 0..self.get_height() {
-            for c in 0..self.get_width() {
-                let pos = Position::new(r, c);
-
-                let score = self.calc_senic_score(&pos);
-                if score > max_senic_score {
-                    max_senic_score = score;
+                if other >= *height {
+                    break;
                 }
             }
-        }
 
-        max_senic_score
-    }
+            count
+        })
+        .product()
 }
 
-#[derive(Eq, PartialEq, Debug, Clone)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+/// How many trees can be seen from outside the grid.
+pub fn count_visible_trees(grid: &Grid<i32>) -> i32 {
+    grid.positions().filter(|p| is_visible(grid, p)).count() as i32
 }
 
-impl Direction {
-    fn advance(&self, pos: &Position) -> Option<Position> {
-        match self {
-            Direction::Up => pos.moved(-1, 0),
-            Direction::Down => pos.moved(1, 0),
-            Direction::Left => pos.moved(0, -1),
-            Direction::Right => pos.moved(0, 1),
-        }
-    }
-}
-
-struct VisibilityMap {
-    known_visibile: HashSet<Position>,
-}
-
-impl VisibilityMap {
-    fn new() -> VisibilityMap {
-        VisibilityMap {
-            known_visibile: HashSet::new(),
-        }
-    }
-
-    fn mark_visible(&mut self, position: &Position) {
-        self.known_visibile.insert(position.clone());
-    }
-
-    fn is_known_visible(&self, position: &Position) -> bool {
-        self.known_visibile.contains(position)
-    }
+/// The highest scenic score of any tree.
+pub fn find_best_senic_score(grid: &Grid<i32>) -> i32 {
+    grid.positions()
+        .map(|p| calc_senic_score(grid, &p))
+        .max()
+        .unwrap()
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Grid {
-    Grid::from_lines(&reader.lines().map(|l| l.unwrap()).collect::<Vec<String>>())
+/// Draws the visible trees in green and the hidden ones in brown, with taller trees brighter.
+fn visibility_image(grid: &Grid<i32>) -> Image {
+    Image::from_bounds(&grid.bounds(), |p| {
+        let brightness = (grid.get(p).unwrap() + 1) as f64 / 10.0;
+        let color = if is_visible(grid, p) {
+            Color::new(80, 230, 60)
+        } else {
+            Color::new(120, 70, 30)
+        };
+
+        Color::BLACK.mix(&color, brightness)
+    })
 }
 
-fn count_visible_trees(grid: &Grid) -> i32 {
-    grid.get_visible_trees().len() as i32
-}
+/// A heatmap of the senic score of every tree, relative to the best one.
+fn senic_score_image(grid: &Grid<i32>) -> Image {
+    let best = find_best_senic_score(grid).max(1) as f64;
 
-fn find_best_senic_score(grid: &Grid) -> i32 {
-    grid.find_best_senic_score()
+    // Most scores are much smaller than the best, so they are spread out to tell them apart
+    Image::from_bounds(&grid.bounds(), |p| {
+        Color::heat((calc_senic_score(grid, p) as f64 / best).sqrt())
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/eight_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 21;
-        let actual = count_visible_trees(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/eight.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 1870;
-        let actual = count_visible_trees(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/eight_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 8;
-        let actual = find_best_senic_score(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/eight.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 517440;
-        let actual = find_best_senic_score(&values);
-
-        assert_eq!(expected, actual)
-    }
+/// Generates a `size` by `size` grid of tree heights.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+
+    (0..size)
+        .map(|_| {
+            let mut row: String = (0..size)
+                .map(|_| (b'0' + rng.below(10) as u8) as char)
+                .collect();
+            row.push('\n');
+            row
+        })
+        .collect()
 }
@@ -1,26 +1,46 @@
 use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = calc_num_sides(&values);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayEighteen;
+
+impl Solution for DayEighteen {
+    type Input = Droplet;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(calc_num_sides(input).into())
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = calc_num_sides_no_bubbles(&values);
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(calc_num_sides_no_bubbles(input).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Droplet {
-    Droplet::from_lines(&reader.lines().map(|l| l.unwrap()).collect::<Vec<String>>())
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Droplet, ParseError> {
+    let cubes = parse_lines(reader, Cube::from_str)?;
+    if cubes.is_empty() {
+        return Err(ParseError::new("", "at least one cube"));
+    }
+
+    Ok(Droplet::new(&cubes))
 }
 
-fn calc_num_sides(droplet: &Droplet) -> i32 {
+/// The surface area of the droplet, including the sides of any air pockets trapped inside it.
+pub fn calc_num_sides(droplet: &Droplet) -> i32 {
     let mut num_sides = 0;
     for cube in droplet.cubes.iter() {
         for neighbor in cube.get_neighbors() {
@@ -33,7 +53,8 @@ fn calc_num_sides(droplet: &Droplet) -> i32 {
     num_sides
 }
 
-fn calc_num_sides_no_bubbles(droplet: &Droplet) -> i32 {
+/// The exterior surface area of the droplet, leaving out air pockets trapped inside it.
+pub fn calc_num_sides_no_bubbles(droplet: &Droplet) -> i32 {
     let mut num_sides_with_bubbles = 0;
     let mut maybe_bubbles = vec![];
     for cube in droplet.cubes.iter() {
@@ -92,10 +113,10 @@ fn calc_num_sides_no_bubbles(droplet: &Droplet) -> i32 {
 }
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Ord, PartialOrd)]
-struct Cube {
-    x: i32,
-    y: i32,
-    z: i32,
+pub struct Cube {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
 }
 
 impl Cube {
@@ -103,14 +124,17 @@ impl Cube {
         Cube { x, y, z }
     }
 
-    fn from_str(s: &str) -> Cube {
+    fn from_str(s: &str) -> Result<Cube, ParseError> {
         let parts: Vec<&str> = s.split(',').collect();
-
-        Cube {
-            x: parts[0].parse().unwrap(),
-            y: parts[1].parse().unwrap(),
-            z: parts[2].parse().unwrap(),
+        if parts.len() != 3 {
+            return Err(ParseError::new(s, "a cube, such as \"2,2,2\""));
         }
+
+        Ok(Cube {
+            x: parse_number(parts[0], "an x coordinate")?,
+            y: parse_number(parts[1], "a y coordinate")?,
+            z: parse_number(parts[2], "a z coordinate")?,
+        })
     }
 
     fn moved(&self, x_diff: i32, y_diff: i32, z_diff: i32) -> Cube {
@@ -136,9 +160,9 @@ impl Cube {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct Bounds3D {
-    top_left_shallow: Cube,
-    bottom_right_deep: Cube,
+pub struct Bounds3D {
+    pub top_left_shallow: Cube,
+    pub bottom_right_deep: Cube,
 }
 
 impl Bounds3D {
@@ -153,9 +177,9 @@ impl Bounds3D {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct Droplet {
-    cubes: Vec<Cube>,
-    bounds: Bounds3D,
+pub struct Droplet {
+    pub cubes: Vec<Cube>,
+    pub bounds: Bounds3D,
 }
 
 impl Droplet {
@@ -167,12 +191,6 @@ impl Droplet {
         }
     }
 
-    fn from_lines(lines: &[String]) -> Droplet {
-        let cubes: Vec<Cube> = lines.iter().map(|l| Cube::from_str(l)).collect();
-
-        Droplet::new(&cubes)
-    }
-
     fn calculate_bounds(cubes: &[Cube]) -> Bounds3D {
         let xs: Vec<i32> = cubes.iter().map(|c| c.x).collect();
         let ys: Vec<i32> = cubes.iter().map(|c| c.y).collect();
@@ -196,9 +214,7 @@ impl Droplet {
         let mut cubes_to_check = vec![candidate_cube.clone()];
         let mut bubble_cubes: HashSet<Cube> = cubes_to_check.iter().cloned().collect();
         let mut already_checked: HashSet<Cube> = HashSet::new();
-        while !cubes_to_check.is_empty() {
-            let cube = cubes_to_check.pop().unwrap();
-
+        while let Some(cube) = cubes_to_check.pop() {
             already_checked.insert(cube.clone());
 
             for neighbor in cube.get_neighbors() {
@@ -225,58 +241,79 @@ impl Droplet {
     }
 }
 
-/*
+/// Generates a droplet of `size` cubes, grown outwards from a single cube so that it is lumpy
+/// enough to trap some air pockets.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let start = (size as f64).cbrt() as i64 + 1;
+    let mut cubes = vec![(start, start, start)];
+    let mut seen: HashSet<(i64, i64, i64)> = cubes.iter().cloned().collect();
+
+    while cubes.len() < size.max(1) {
+        let (x, y, z) = *rng.choose(&cubes);
+        let cube = match rng.below(6) {
+            0 => (x - 1, y, z),
+            1 => (x + 1, y, z),
+            2 => (x, y - 1, z),
+            3 => (x, y + 1, z),
+            4 => (x, y, z - 1),
+            _ => (x, y, z + 1),
+        };
+
+        if cube.0 >= 0 && cube.1 >= 0 && cube.2 >= 0 && seen.insert(cube) {
+            cubes.push(cube);
+        }
+    }
+
+    cubes
+        .iter()
+        .map(|(x, y, z)| format!("{},{},{}\n", x, y, z))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
+    /// Counts the sides of the droplet that steam can reach, by flooding the box around the
+    /// droplet from one of its corners.
+    fn flood_exterior_sides(droplet: &Droplet) -> i32 {
+        let cubes: HashSet<Cube> = droplet.cubes.iter().cloned().collect();
+        let corner = droplet.bounds.top_left_shallow.moved(-1, -1, -1);
+        let outside = Bounds3D {
+            top_left_shallow: corner.clone(),
+            bottom_right_deep: droplet.bounds.bottom_right_deep.moved(1, 1, 1),
+        };
+
+        let mut num_sides = 0;
+        let mut steam: HashSet<Cube> = HashSet::new();
+        steam.insert(corner.clone());
+        let mut to_visit = vec![corner];
+        while let Some(cube) = to_visit.pop() {
+            for neighbor in cube.get_neighbors() {
+                if cubes.contains(&neighbor) {
+                    num_sides += 1;
+                } else if outside.contains(&neighbor) && steam.insert(neighbor.clone()) {
+                    to_visit.push(neighbor);
+                }
+            }
+        }
 
-        assert_eq!(expected, actual)
+        num_sides
     }
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    fn test_num_sides_no_bubbles_matches_flood_fill() {
+        for seed in 0..30 {
+            let mut rng = Rng::new(seed);
+            let size = rng.between(1, 300) as usize;
+            let input = generate(&mut rng, size);
+            let droplet = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
 
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
+            let expected = flood_exterior_sides(&droplet);
+            let actual = calc_num_sides_no_bubbles(&droplet);
 
-        assert_eq!(expected, actual)
+            assert_eq!(expected, actual, "seed {}: {}", seed, input);
+        }
     }
 }
-*/
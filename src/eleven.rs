@@ -1,22 +1,53 @@
 use std::collections::HashMap;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+
+use crate::answer::Answer;
+use crate::params::{Param, Params};
+use crate::parse::{parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
+
+pub struct DayEleven;
+
+impl Solution for DayEleven {
+    type Input = Vec<Monkey>;
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::integer(
+                "part_one_rounds",
+                20,
+                "Rounds of monkey business in part one",
+            )
+            .with_range(0..=i64::MAX),
+            Param::integer(
+                "part_two_rounds",
+                10000,
+                "Rounds of monkey business in part two",
+            )
+            .with_range(0..=i64::MAX),
+        ]
+    }
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = calc_monkey_business(&values);
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-    println!("{}", answer);
-}
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(calc_monkey_business(input, params.integer("part_one_rounds")).into())
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = calc_monkey_business_2(&values);
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(calc_monkey_business_2(input, params.integer("part_two_rounds")).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-type Item = i64;
+pub type Item = i64;
 
 #[derive(Debug)]
 struct State {
@@ -51,29 +82,44 @@ impl State {
 }
 
 #[derive(Debug)]
-struct Monkey {
-    starting_items: Vec<Item>,
-    operation: Operation,
-    rule: MonkeyRule,
+pub struct Monkey {
+    pub starting_items: Vec<Item>,
+    pub operation: Operation,
+    pub rule: MonkeyRule,
 }
 
 impl Monkey {
-    fn from_lines(lines: &[String]) -> Monkey {
-        let starting_items: Vec<Item> = lines[1]
-            .split(": ")
-            .last()
-            .unwrap()
+    /// Parses the description of a monkey, which starts on line `first_line` of the input.
+    fn from_lines(lines: &[String], first_line: usize) -> Result<Monkey, ParseError> {
+        let field = |i: usize, prefix: &str| {
+            let line = lines.get(i).map(|l| l.trim_start()).unwrap_or("");
+
+            line.strip_prefix(prefix)
+                .ok_or_else(|| ParseError::new(line, &format!("\"{}...\"", prefix)))
+                .map_err(|e| e.on_line(first_line + i))
+        };
+
+        field(0, "Monkey ")?;
+        let starting_items: Vec<Item> = field(1, "Starting items: ")?
             .split(", ")
-            .map(|n| n.parse().unwrap())
-            .collect();
-        let operation = Operation::from_str(&lines[2]);
-        let rule = MonkeyRule::from_lines(&lines[3..]);
-
-        Monkey {
+            .map(|n| parse_number(n, "a worry level").map_err(|e| e.on_line(first_line + 1)))
+            .collect::<Result<_, _>>()?;
+        let operation = Operation::from_str(field(2, "Operation: new = old ")?)
+            .map_err(|e| e.on_line(first_line + 2))?;
+        let rule = MonkeyRule {
+            divisor: parse_number(field(3, "Test: divisible by ")?, "a divisor")
+                .map_err(|e| e.on_line(first_line + 3))?,
+            true_dest: parse_number(field(4, "If true: throw to monkey ")?, "a monkey number")
+                .map_err(|e| e.on_line(first_line + 4))?,
+            false_dest: parse_number(field(5, "If false: throw to monkey ")?, "a monkey number")
+                .map_err(|e| e.on_line(first_line + 5))?,
+        };
+
+        Ok(Monkey {
             starting_items,
             operation,
             rule,
-        }
+        })
     }
 
     fn calc_new_item_value(&self, item: Item) -> Item {
@@ -86,35 +132,33 @@ impl Monkey {
 }
 
 #[derive(Debug)]
-enum Symbol {
+pub enum Symbol {
     Old,
     Value(Item),
 }
 
 impl Symbol {
-    fn from_str(s: &str) -> Symbol {
+    fn from_str(s: &str) -> Result<Symbol, ParseError> {
         match s {
-            "old" => Symbol::Old,
-            _ => Symbol::Value(s.parse().unwrap()),
+            "old" => Ok(Symbol::Old),
+            _ => Ok(Symbol::Value(parse_number(s, "\"old\" or a number")?)),
         }
     }
 }
 
 #[derive(Debug)]
-enum Operation {
+pub enum Operation {
     Add(Symbol),
     Multiply(Symbol),
 }
 
 impl Operation {
-    fn from_str(line: &str) -> Operation {
-        let op = *line.split(' ').collect::<Vec<&str>>().get(6).unwrap();
-        let value = Symbol::from_str(line.split(' ').last().unwrap());
-
-        match op {
-            "+" => Operation::Add(value),
-            "*" => Operation::Multiply(value),
-            _ => panic!(),
+    /// Parses the part of an operation after "new = old", such as "* 19".
+    fn from_str(s: &str) -> Result<Operation, ParseError> {
+        match s.split_once(' ') {
+            Some(("+", value)) => Ok(Operation::Add(Symbol::from_str(value)?)),
+            Some(("*", value)) => Ok(Operation::Multiply(Symbol::from_str(value)?)),
+            _ => Err(ParseError::new(s, "\"+\" or \"*\" followed by a value")),
         }
     }
 
@@ -129,25 +173,13 @@ impl Operation {
 }
 
 #[derive(Debug)]
-struct MonkeyRule {
-    divisor: Item,
-    true_dest: usize,
-    false_dest: usize,
+pub struct MonkeyRule {
+    pub divisor: Item,
+    pub true_dest: usize,
+    pub false_dest: usize,
 }
 
 impl MonkeyRule {
-    fn from_lines(lines: &[String]) -> MonkeyRule {
-        let divisor = lines[0].split(' ').last().unwrap().parse().unwrap();
-        let true_dest = lines[1].split(' ').last().unwrap().parse().unwrap();
-        let false_dest = lines[2].split(' ').last().unwrap().parse().unwrap();
-
-        MonkeyRule {
-            divisor,
-            true_dest,
-            false_dest,
-        }
-    }
-
     fn apply(&self, value: Item) -> usize {
         if value % self.divisor == 0 {
             self.true_dest
@@ -157,31 +189,35 @@ impl MonkeyRule {
     }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Monkey> {
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Monkey>, ParseError> {
     let mut monkeys: Vec<Monkey> = Vec::new();
     let mut lines_buffer = vec![];
-    for line in reader.lines() {
-        let line = line.unwrap();
+    let mut buffer_start = 1;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
 
         if line.is_empty() {
-            monkeys.push(Monkey::from_lines(&lines_buffer));
+            monkeys.push(Monkey::from_lines(&lines_buffer, buffer_start)?);
             lines_buffer.clear();
+            buffer_start = i + 2;
             continue;
         }
 
         lines_buffer.push(line);
     }
 
-    monkeys.push(Monkey::from_lines(&lines_buffer));
+    monkeys.push(Monkey::from_lines(&lines_buffer, buffer_start)?);
     lines_buffer.clear();
 
-    monkeys
+    Ok(monkeys)
 }
 
-fn calc_monkey_business(monkeys: &[Monkey]) -> i32 {
+/// The product of the two highest inspection counts after `rounds`, with relief dividing worry
+/// levels by three.
+pub fn calc_monkey_business(monkeys: &[Monkey], rounds: i64) -> i32 {
     let mut prev_state = State::from_monkeys(monkeys);
     let mut inspection_counts: HashMap<usize, i32> = HashMap::new();
-    for _ in 0..20 {
+    for _ in 0..rounds {
         let mut new_state = State::new();
         for (num, monkey) in monkeys.iter().enumerate() {
             for item in prev_state
@@ -214,12 +250,13 @@ fn calc_monkey_business(monkeys: &[Monkey]) -> i32 {
     counts.iter().take(2).product()
 }
 
-fn calc_monkey_business_2(monkeys: &[Monkey]) -> i64 {
+/// The product of the two highest inspection counts after `rounds`, with no relief.
+pub fn calc_monkey_business_2(monkeys: &[Monkey], rounds: i64) -> i64 {
     let resolution: Item = monkeys.iter().map(|m| m.rule.divisor).product();
 
     let mut prev_state = State::from_monkeys(monkeys);
     let mut inspection_counts: HashMap<usize, i64> = HashMap::new();
-    for _ in 0..10000 {
+    for _ in 0..rounds {
         let mut new_state = State::new();
         for (num, monkey) in monkeys.iter().enumerate() {
             for item in prev_state
@@ -256,56 +293,45 @@ fn calc_monkey_business_2(monkeys: &[Monkey]) -> i64 {
     counts.iter().take(2).product()
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/eleven_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 10605;
-        let actual = calc_monkey_business(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/eleven.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 119715;
-        let actual = calc_monkey_business(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/eleven_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 2713310158;
-        let actual = calc_monkey_business_2(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/eleven.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 18085004878;
-        let actual = calc_monkey_business_2(&values);
-
-        assert_eq!(expected, actual)
-    }
+/// Generates `size` monkeys (between two and eight).
+///
+/// Operations only add or multiply by up to three, as with anything larger worry levels in part one
+/// can grow past an `i64` before they are divided back down.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let num_monkeys = size.clamp(2, 8);
+    let mut divisors = vec![2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut divisors);
+
+    let monkeys: Vec<String> = (0..num_monkeys)
+        .map(|i| {
+            let items: Vec<String> = (0..rng.between(1, 5))
+                .map(|_| rng.between(50, 99).to_string())
+                .collect();
+            let operation = match rng.below(3) {
+                0 => "+ old".to_string(),
+                1 => format!("* {}", rng.between(2, 3)),
+                _ => format!("+ {}", rng.between(1, 8)),
+            };
+            let mut other_monkey = || (i + 1 + rng.below(num_monkeys - 1)) % num_monkeys;
+
+            format!(
+                concat!(
+                    "Monkey {}:\n",
+                    "  Starting items: {}\n",
+                    "  Operation: new = old {}\n",
+                    "  Test: divisible by {}\n",
+                    "    If true: throw to monkey {}\n",
+                    "    If false: throw to monkey {}\n",
+                ),
+                i,
+                items.join(", "),
+                operation,
+                divisors[i],
+                other_monkey(),
+                other_monkey()
+            )
+        })
+        .collect();
+
+    monkeys.join("\n")
 }
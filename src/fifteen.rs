@@ -1,50 +1,123 @@
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+use std::iter;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_num_impossible_positions(&values, 2000000);
+use crate::answer::Answer;
+use crate::grid::{Bounds, Position};
+use crate::image::{Color, Image};
+use crate::params::{Param, Params};
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Part, Solution, SolveError};
+use crate::visualize::{Frame, Frames};
 
-    println!("{}", answer);
-}
+pub struct DayFifteen;
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_beacon_frequency(&values, 4000000);
+impl Solution for DayFifteen {
+    type Input = Vec<Sensor>;
 
-    println!("{}", answer);
-}
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Sensor> {
-    let mut sensors: Vec<Sensor> = Vec::new();
-    for line in reader.lines() {
-        sensors.push(Sensor::from_str(line.unwrap().as_str()));
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::integer(
+                "row",
+                2000000,
+                "Row to count the impossible beacon positions of",
+            )
+            .with_example_default(10)
+            .with_range(i32::MIN as i64..=i32::MAX as i64),
+            Param::integer(
+                "bound",
+                4000000,
+                "Largest x and y coordinate that the distress beacon can be at",
+            )
+            .with_example_default(20)
+            .with_range(0..=i32::MAX as i64),
+        ]
     }
 
-    sensors
-}
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_num_impossible_positions(input, params.integer("row") as i32).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        let bound = params.integer("bound") as i32;
+        find_beacon_frequency(input, bound)
+            .map(Answer::from)
+            .ok_or_else(|| no_hidden_beacon(bound))
+    }
+
+    fn frames(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        coverage_frames(input, part, params).map(Some)
+    }
+
+    fn image(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        coverage_image(input, part, params)
+    }
 
-#[derive(Debug)]
-struct Sensor {
-    sensor_position: Position,
-    beacon_position: Position,
-    dist: i32,
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-impl Sensor {
-    fn from_str(s: &str) -> Sensor {
-        let parts: Vec<&str> = s.split(": ").collect();
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Sensor>, ParseError> {
+    let sensors = parse_lines(reader, Sensor::from_str)?;
+    if sensors.is_empty() {
+        return Err(ParseError::new("", "at least one sensor").on_line(1));
+    }
+
+    Ok(sensors)
+}
 
-        let sensor_position = Position::from_str(parts[0].split(" at ").last().unwrap());
-        let beacon_position = Position::from_str(parts[1].split(" at ").last().unwrap());
+fn no_hidden_beacon(bound: i32) -> SolveError {
+    SolveError::new(&format!(
+        "There is nowhere from 0 to {} that the distress beacon could be",
+        bound
+    ))
+}
 
-        let dist = sensor_position.manhatten_distance(&beacon_position);
+#[derive(Debug, Clone)]
+pub struct Sensor {
+    pub sensor_position: Position,
+    pub beacon_position: Position,
+    pub dist: i32,
+}
 
-        Sensor {
+impl Sensor {
+    fn from_str(s: &str) -> Result<Sensor, ParseError> {
+        let (sensor, beacon) = s
+            .strip_prefix("Sensor at ")
+            .and_then(|rest| rest.split_once(": closest beacon is at "))
+            .ok_or_else(|| {
+                ParseError::new(
+                    s,
+                    "\"Sensor at x=<x>, y=<y>: closest beacon is at x=<x>, y=<y>\"",
+                )
+            })?;
+
+        let sensor_position = parse_position(sensor)?;
+        let beacon_position = parse_position(beacon)?;
+
+        let dist = sensor_position.manhattan_distance(&beacon_position);
+
+        Ok(Sensor {
             sensor_position,
             beacon_position,
             dist,
-        }
+        })
     }
 
     /*
@@ -68,7 +141,7 @@ impl Sensor {
         let bottom = self.sensor_position.moved(self.dist + 1, 0);
 
         let mut lines = vec![];
-        for position in vec![top, bottom] {
+        for position in [top, bottom] {
             for slope in &[-1, 1] {
                 let line = Line::from_position_and_slope(&position, *slope);
 
@@ -80,114 +153,202 @@ impl Sensor {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Debug, Clone)]
-struct Position {
-    row: i32,
-    column: i32,
-}
-
-impl Position {
-    fn new(r: i32, c: i32) -> Position {
-        Position { row: r, column: c }
-    }
+fn parse_position(s: &str) -> Result<Position, ParseError> {
+    let (x, y) = s
+        .strip_prefix("x=")
+        .and_then(|rest| rest.split_once(", y="))
+        .ok_or_else(|| ParseError::new(s, "a position, such as \"x=2, y=18\""))?;
 
-    fn from_str(s: &str) -> Position {
-        let parts: Vec<&str> = s.split(", ").collect();
+    Ok(Position::new(
+        parse_number(y, "a y coordinate")?,
+        parse_number(x, "an x coordinate")?,
+    ))
+}
 
-        Position {
-            row: parts[1].split('=').last().unwrap().parse().unwrap(),
-            column: parts[0].split('=').last().unwrap().parse().unwrap(),
+/// The most characters across and down that the frames are drawn in.
+const FRAME_WIDTH: usize = 120;
+const FRAME_HEIGHT: usize = 60;
+
+/// Adds the sensors one at a time, drawing the area each has ruled out, then picks out the answer.
+fn coverage_frames(sensors: &[Sensor], part: Part, params: &Params) -> Result<Frames, SolveError> {
+    let sensors = sensors.to_vec();
+    let bounds = match coverage_bounds(&sensors, part, params) {
+        Some(bounds) => bounds,
+        None => return Ok(Box::new(iter::empty())),
+    };
+
+    let (row, bound) = (params.integer("row") as i32, params.integer("bound") as i32);
+    let hidden_beacon = match part {
+        Part::One => None,
+        Part::Two => {
+            Some(find_hidden_beacon(&sensors, bound).ok_or_else(|| no_hidden_beacon(bound))?)
+        }
+    };
+    let num_sensors = sensors.len();
+    let frames = (1..=num_sensors + 1).map(move |n| {
+        if n <= num_sensors {
+            let sensor = &sensors[n - 1];
+            let caption = format!(
+                "Sensor at {},{} can see {} away",
+                sensor.sensor_position.column, sensor.sensor_position.row, sensor.dist
+            );
+
+            return Frame::new(caption, render_coverage(&sensors[..n], &bounds, None, None));
         }
-    }
-
-    fn moved(&self, row_change: i32, column_change: i32) -> Position {
-        let new_row = row_change + self.row;
-        let new_column = column_change + self.column;
-
-        Position::new(new_row, new_column)
-    }
-
-    fn manhatten_distance(&self, other: &Position) -> i32 {
-        (other.row - self.row).abs() + (other.column - self.column).abs()
-    }
-}
 
-/*
-#[derive(Clone, Copy, Eq, PartialEq)]
-enum CellState {
-    Beacon, Sensor, KnownEmpty, Unknown
-}
+        match part {
+            Part::One => Frame::new(
+                format!(
+                    "{} positions in row {} can't have a beacon",
+                    find_num_impossible_positions(&sensors, row),
+                    row
+                ),
+                render_coverage(&sensors, &bounds, Some(row), None),
+            ),
+            Part::Two => {
+                let hidden_beacon = hidden_beacon.expect("found before drawing any frames");
+                Frame::new(
+                    format!(
+                        "The distress beacon is at {},{}",
+                        hidden_beacon.column, hidden_beacon.row
+                    ),
+                    render_coverage(&sensors, &bounds, None, Some(hidden_beacon)),
+                )
+            }
+        }
+    });
 
-struct Grid {
-    cells: HashMap<Position, CellState>,
+    Ok(Box::new(frames))
 }
 
-impl Grid {
-    fn new() -> Grid {
-        Grid {
-            cells: HashMap::new(),
+/// The area to draw: everything that the sensors can see in part one, or the area that the
+/// distress beacon can be in for part two.
+fn coverage_bounds(sensors: &[Sensor], part: Part, params: &Params) -> Option<Bounds> {
+    match part {
+        Part::One => {
+            let corners: Vec<Position> = sensors
+                .iter()
+                .flat_map(|s| {
+                    [
+                        s.sensor_position.moved(-s.dist, -s.dist),
+                        s.sensor_position.moved(s.dist, s.dist),
+                    ]
+                })
+                .collect();
+            Bounds::from_positions(&corners)
         }
-    }
-
-    fn set(&mut self, position: &Position, value: CellState) {
-        self.cells.insert(position.clone(), value);
-    }
-
-    fn set_if_empty(&mut self, position: &Position, value: CellState) {
-        if !self.cells.contains_key(position) {
-            self.cells.insert(position.clone(), value);
+        Part::Two => {
+            let bound = params.integer("bound") as i32;
+            Bounds::from_positions(&[Position::origin(), Position::new(bound, bound)])
         }
     }
+}
 
-    fn fill_blanks_within_distance(&mut self, source: &Position, manhatten_distance: i32, value: CellState) {
-        let mut visited: HashSet<Position> = HashSet::new();
-        visited.insert(source.clone());
-
-        let mut to_visit = source.get_neighbors();
-        while !to_visit.is_empty() {
-            let current = to_visit.remove(0);
-            //println!("{current:?}");
-
-            if visited.contains(&current) || source.manhatten_distance(&current) > manhatten_distance {
-                continue;
+/// Colors the area that each sensor can see, with the answer picked out as in `coverage_frames`.
+fn coverage_image(
+    sensors: &[Sensor],
+    part: Part,
+    params: &Params,
+) -> Result<Option<Image>, SolveError> {
+    const MAX_SIZE: usize = 800;
+    const SENSOR_COLORS: [Color; 6] = [
+        Color::new(70, 90, 160),
+        Color::new(60, 140, 120),
+        Color::new(150, 80, 140),
+        Color::new(160, 120, 60),
+        Color::new(90, 140, 60),
+        Color::new(150, 70, 70),
+    ];
+
+    let bounds = match coverage_bounds(sensors, part, params) {
+        Some(bounds) => bounds,
+        None => return Ok(None),
+    };
+    let highlight_row = match part {
+        Part::One => Some(params.integer("row") as i32),
+        Part::Two => None,
+    };
+    let hidden_beacon = match part {
+        Part::One => None,
+        Part::Two => {
+            let bound = params.integer("bound") as i32;
+            Some(find_hidden_beacon(sensors, bound).ok_or_else(|| no_hidden_beacon(bound))?)
+        }
+    };
+
+    Ok(Some(Image::from_blocks(
+        &bounds,
+        MAX_SIZE,
+        MAX_SIZE,
+        |block| {
+            if hidden_beacon.is_some_and(|p| block.contains(&p)) {
+                return Color::new(255, 40, 40);
+            }
+            if sensors.iter().any(|s| block.contains(&s.sensor_position)) {
+                return Color::WHITE;
+            }
+            if sensors.iter().any(|s| block.contains(&s.beacon_position)) {
+                return Color::new(60, 120, 255);
+            }
+            if highlight_row
+                .is_some_and(|row| (block.top_left.row..=block.bottom_right.row).contains(&row))
+            {
+                return Color::new(255, 220, 0);
             }
-            //println!("new {current:?}");
 
-            visited.insert(current.clone());
+            let center = Position::new(
+                (block.top_left.row + block.bottom_right.row) / 2,
+                (block.top_left.column + block.bottom_right.column) / 2,
+            );
+            sensors
+                .iter()
+                .position(|s| s.sensor_position.manhattan_distance(&center) <= s.dist)
+                .map_or(Color::BLACK, |i| SENSOR_COLORS[i % SENSOR_COLORS.len()])
+        },
+    )))
+}
 
-            self.set_if_empty(&current, value);
-            for neighbor in current.get_neighbors() {
-                if visited.contains(&neighbor) || source.manhatten_distance(&neighbor) > manhatten_distance {
-                } else {
-                    to_visit.push(neighbor);
-                }
-            }
+/// Draws the sensors, their beacons and the positions that they rule out, scaled down to fit in a
+/// frame. The positions in `highlight_row` are drawn as '=' if ruled out and '-' otherwise.
+fn render_coverage(
+    sensors: &[Sensor],
+    bounds: &Bounds,
+    highlight_row: Option<i32>,
+    hidden_beacon: Option<Position>,
+) -> String {
+    bounds.render_scaled(FRAME_WIDTH, FRAME_HEIGHT, |block| {
+        if hidden_beacon.is_some_and(|p| block.contains(&p)) {
+            return 'X';
+        }
+        if sensors.iter().any(|s| block.contains(&s.sensor_position)) {
+            return 'S';
+        }
+        if sensors.iter().any(|s| block.contains(&s.beacon_position)) {
+            return 'B';
         }
-    }
-
-    fn get(&self, position: &Position) -> CellState {
-        self.cells.get(position).cloned().unwrap_or(CellState::Unknown)
-    }
 
-    fn print(&self, top_left: &Position, bottom_right: &Position) {
-        for row in top_left.row..bottom_right.row + 1 {
-            for column in top_left.column..bottom_right.column + 1 {
-                print!("{}", match self.get(&Position::new(row, column)) {
-                    CellState::Unknown => '.',
-                    CellState::Beacon => 'B',
-                    CellState::Sensor => 'S',
-                    CellState::KnownEmpty => '#',
-                });
-            }
-            println!("");
+        let center = Position::new(
+            (block.top_left.row + block.bottom_right.row) / 2,
+            (block.top_left.column + block.bottom_right.column) / 2,
+        );
+        let covered = sensors
+            .iter()
+            .any(|s| s.sensor_position.manhattan_distance(&center) <= s.dist);
+        let highlighted = highlight_row
+            .is_some_and(|row| (block.top_left.row..=block.bottom_right.row).contains(&row));
+
+        match (covered, highlighted) {
+            (true, true) => '=',
+            (true, false) => '#',
+            (false, true) => '-',
+            (false, false) => '.',
         }
-    }
+    })
 }
-*/
 
 fn would_be_visible_but_not_known(position: &Position, sensors: &[Sensor]) -> bool {
     for sensor in sensors.iter() {
-        if sensor.sensor_position.manhatten_distance(position) <= sensor.dist
+        if sensor.sensor_position.manhattan_distance(position) <= sensor.dist
             && *position != sensor.sensor_position
             && *position != sensor.beacon_position
         {
@@ -200,7 +361,7 @@ fn would_be_visible_but_not_known(position: &Position, sensors: &[Sensor]) -> bo
 
 fn would_be_visible(position: &Position, sensors: &[Sensor]) -> bool {
     for sensor in sensors.iter() {
-        if sensor.sensor_position.manhatten_distance(position) <= sensor.dist
+        if sensor.sensor_position.manhattan_distance(position) <= sensor.dist
             || *position == sensor.beacon_position
         {
             return true;
@@ -210,7 +371,8 @@ fn would_be_visible(position: &Position, sensors: &[Sensor]) -> bool {
     false
 }
 
-fn find_num_impossible_positions(sensors: &[Sensor], search_row: i32) -> i32 {
+/// How many positions in `search_row` can't have a beacon.
+pub fn find_num_impossible_positions(sensors: &[Sensor], search_row: i32) -> i32 {
     let mut min_col_extent = None;
     let mut max_col_extent = None;
     for sensor in sensors.iter() {
@@ -232,8 +394,8 @@ fn find_num_impossible_positions(sensors: &[Sensor], search_row: i32) -> i32 {
         }
     }
 
-    let min_col_extent = min_col_extent.unwrap();
-    let max_col_extent = max_col_extent.unwrap();
+    let min_col_extent = min_col_extent.expect("read_input checks for at least one sensor");
+    let max_col_extent = max_col_extent.expect("read_input checks for at least one sensor");
 
     let mut total = 0;
     for column in min_col_extent..max_col_extent + 1 {
@@ -276,8 +438,16 @@ impl Line {
     }
 }
 
-fn find_beacon_frequency(sensors: &[Sensor], range_value: i32) -> i64 {
-    //let range = 0..20 + 1;
+/// The tuning frequency of the distress beacon, if it can be found.
+pub fn find_beacon_frequency(sensors: &[Sensor], range_value: i32) -> Option<i64> {
+    let hidden_beacon = find_hidden_beacon(sensors, range_value)?;
+
+    Some(hidden_beacon.column as i64 * 4000000 + hidden_beacon.row as i64)
+}
+
+/// The position from 0 to `range_value` in both directions that none of the sensors can see, if
+/// there is one.
+pub fn find_hidden_beacon(sensors: &[Sensor], range_value: i32) -> Option<Position> {
     let range = 0..range_value + 1;
 
     let lines: Vec<Line> = sensors.iter().flat_map(|s| s.calc_extent_lines()).collect();
@@ -295,12 +465,57 @@ fn find_beacon_frequency(sensors: &[Sensor], range_value: i32) -> i64 {
         }
 
         if !would_be_visible(position, sensors) {
-            hidden_beacon = Some(position.clone());
+            hidden_beacon = Some(*position);
         }
     }
 
-    let hidden_beacon = hidden_beacon.unwrap();
-    hidden_beacon.column as i64 * 4000000 + hidden_beacon.row as i64
+    hidden_beacon
+}
+
+/// Generates `size` sensors (at least four) around a hidden beacon, at the scale of the real input.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    generate_sensors(rng, size, 4000000)
+}
+
+/// Generates `num_sensors` sensors (at least four) around a hidden beacon somewhere from 0 to
+/// `bound` in both directions.
+///
+/// Each sensor's closest beacon is just close enough for the hidden beacon to be out of its range,
+/// and there is a sensor on each diagonal of the hidden beacon so that it is boxed in.
+fn generate_sensors(rng: &mut Rng, num_sensors: usize, bound: i64) -> String {
+    let (hidden_column, hidden_row) = (rng.between(0, bound), rng.between(0, bound));
+
+    (0..num_sensors.max(4))
+        .map(|i| {
+            // The first four sensors take a diagonal each, the rest are anywhere in the area
+            let (column, row) = match i {
+                0..=3 => {
+                    let (dc, dr) = [(1, 1), (1, -1), (-1, 1), (-1, -1)][i];
+                    let mut offset = || rng.between(2, (bound / 4).max(2));
+                    (hidden_column + dc * offset(), hidden_row + dr * offset())
+                }
+                _ => loop {
+                    let column = rng.between(0, bound);
+                    let row = rng.between(0, bound);
+                    if (column - hidden_column).abs() + (row - hidden_row).abs() > 1 {
+                        break (column, row);
+                    }
+                },
+            };
+
+            let radius = (column - hidden_column).abs() + (row - hidden_row).abs() - 1;
+            let dc = rng.between(-radius, radius);
+            let dr = (radius - dc.abs()) * if rng.chance(0.5) { 1 } else { -1 };
+
+            format!(
+                "Sensor at x={}, y={}: closest beacon is at x={}, y={}\n",
+                column,
+                row,
+                column + dc,
+                row + dr
+            )
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -308,51 +523,56 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/fifteen_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 26;
-        let actual = find_num_impossible_positions(&values, 10);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/fifteen.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 4737443;
-        let actual = find_num_impossible_positions(&values, 2000000);
+    /// Finds every position from 0 to `bound` that is out of range of all of the sensors, by
+    /// checking each row a position at a time.
+    fn scan_for_hidden_beacons(sensors: &[Sensor], bound: i32) -> Vec<Position> {
+        let mut hidden = vec![];
+        for row in 0..=bound {
+            for column in 0..=bound {
+                let position = Position::new(row, column);
+                if sensors
+                    .iter()
+                    .all(|s| s.sensor_position.manhattan_distance(&position) > s.dist)
+                {
+                    hidden.push(position);
+                }
+            }
+        }
 
-        assert_eq!(expected, actual)
+        hidden
     }
 
     #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/fifteen_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 56000011;
-        let actual = find_beacon_frequency(&values, 20);
-
-        assert_eq!(expected, actual)
+    fn test_hidden_beacon_matches_scan() {
+        for seed in 0..200 {
+            let mut rng = Rng::new(seed);
+            let num_sensors = rng.between(4, 30) as usize;
+            let input = generate_sensors(&mut rng, num_sensors, 20);
+            let sensors = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
+
+            let hidden = scan_for_hidden_beacons(&sensors, 20);
+            let actual = find_hidden_beacon(&sensors, 20).unwrap();
+
+            assert!(
+                hidden.contains(&actual),
+                "seed {}: found {:?}, but only {:?} are hidden\n{}",
+                seed,
+                actual,
+                hidden,
+                input
+            );
+        }
     }
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/fifteen.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 11482462818989;
-        let actual = find_beacon_frequency(&values, 4000000);
-
-        assert_eq!(expected, actual)
+    fn test_no_sensors_or_hidden_beacon() {
+        let error = read_input(&mut BufReader::new("".as_bytes())).unwrap_err();
+        assert_eq!(Some(1), error.line);
+
+        let input = "Sensor at x=10, y=10: closest beacon is at x=40, y=10\n";
+        let sensors = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
+        let params = Params::defaults(&DayFifteen.params(), true);
+        assert!(DayFifteen.part_two(&sensors, &params).is_err());
+        assert!(DayFifteen.image(&sensors, Part::Two, &params).is_err());
     }
 }
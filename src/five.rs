@@ -1,29 +1,48 @@
+use log::trace;
 use regex::Regex;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-type Crate = Vec<char>;
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-pub fn part_one() {
-    let (mut crates, moves) = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_top_crates_after_moves(&mut crates, &moves);
+pub type Crate = Vec<char>;
 
-    println!("{}", answer);
-}
+pub struct DayFive;
+
+impl Solution for DayFive {
+    type Input = (Vec<Crate>, Vec<Move>);
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_top_crates_after_moves(&mut input.0.clone(), &input.1).into())
+    }
 
-pub fn part_two() {
-    let (mut crates, moves) = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_top_crates_after_moves_2(&mut crates, &moves);
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_top_crates_after_moves_2(&mut input.0.clone(), &input.1).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> (Vec<Crate>, Vec<Move>) {
+fn read_input<T: std::io::Read>(
+    reader: &mut BufReader<T>,
+) -> Result<(Vec<Crate>, Vec<Move>), ParseError> {
     let mut crates: Vec<Crate> = Vec::new();
     let mut moves: Vec<Move> = Vec::new();
+    // How many crates each stack has once the earlier moves are made, to check each move against
+    let mut stack_sizes: Vec<usize> = Vec::new();
     let mut at_moves = false;
-    for line in reader.lines() {
-        let line = line.unwrap();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
 
         if line.is_empty() {
             // Hanlde the line separating the crates from the moves
@@ -31,31 +50,61 @@ fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> (Vec<Crate>, Vec<M
             continue;
         }
 
-        if line.chars().collect::<Vec<char>>()[1] == '1' {
+        if line.chars().nth(1) == Some('1') {
             // Skip the crate indices line, since we can recalulate them as needed and thus don't need to store them
             continue;
         }
 
         if !at_moves {
-            if crates.is_empty() {
-                // Haven't created the crates yet, so let's calculate how many we need and create them as empty
-                let num_crates = (line.len() as f32 / 4.0).ceil() as usize;
-                for _ in 0..num_crates {
+            // Populate all of the crates that have entries in this row of text, allowing for
+            // trailing spaces having been trimmed off
+            let row: Vec<char> = line.chars().collect();
+            for (j, cell) in row.chunks(4).enumerate() {
+                if crates.len() <= j {
                     crates.push(Vec::new());
                 }
-            }
 
-            // Populate all of the crates that have entries in this row of text
-            for (i, cr) in crates.iter_mut().enumerate() {
-                let c = line.chars().collect::<Vec<char>>()[i * 4 + 1];
-                if c == ' ' {
-                    continue;
+                match cell {
+                    ['[', c, ']'] | ['[', c, ']', ' '] if c.is_ascii_uppercase() => {
+                        crates[j].push(*c)
+                    }
+                    _ if cell.iter().all(|c| *c == ' ') => continue,
+                    _ => {
+                        return Err(
+                            ParseError::new(&line, "a row of crates, such as \"[A] [B]\"")
+                                .on_line(i + 1),
+                        )
+                    }
                 }
-
-                cr.push(c);
             }
         } else {
-            moves.push(Move::from_line(&line));
+            if stack_sizes.is_empty() {
+                stack_sizes = crates.iter().map(|cr| cr.len()).collect();
+            }
+
+            let m = Move::from_line(&line).map_err(|e| e.on_line(i + 1))?;
+            if m.src == 0 || m.src > crates.len() || m.dest == 0 || m.dest > crates.len() {
+                return Err(ParseError::new(
+                    &line,
+                    &format!("a move between stacks 1 to {}", crates.len()),
+                )
+                .on_line(i + 1));
+            }
+            if m.count > stack_sizes[m.src - 1] {
+                return Err(ParseError::new(
+                    &line,
+                    &format!(
+                        "a move of at most the {} crates on stack {}",
+                        stack_sizes[m.src - 1],
+                        m.src
+                    ),
+                )
+                .on_line(i + 1));
+            }
+
+            stack_sizes[m.src - 1] -= m.count;
+            stack_sizes[m.dest - 1] += m.count;
+            moves.push(m);
         }
     }
 
@@ -64,56 +113,66 @@ fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> (Vec<Crate>, Vec<M
         cr.reverse();
     }
 
-    (crates, moves)
+    Ok((crates, moves))
 }
 
 #[derive(Debug)]
-struct Move {
-    count: i32,
-    src: usize,
-    dest: usize,
+pub struct Move {
+    pub count: usize,
+    pub src: usize,
+    pub dest: usize,
 }
 
 impl Move {
-    fn from_line(line: &str) -> Move {
-        let re = Regex::new(r"move ([0-9]+) from ([0-9]+) to ([0-9]+)").unwrap();
-        let matches = re.captures_iter(line).next().unwrap();
-
-        Move {
-            count: matches.get(1).unwrap().as_str().parse().unwrap(),
-            src: matches.get(2).unwrap().as_str().parse().unwrap(),
-            dest: matches.get(3).unwrap().as_str().parse().unwrap(),
-        }
+    fn from_line(line: &str) -> Result<Move, ParseError> {
+        let re = Regex::new(r"^move ([0-9]+) from ([0-9]+) to ([0-9]+)$").unwrap();
+        let matches = re
+            .captures(line)
+            .ok_or_else(|| ParseError::new(line, "a move, such as \"move 1 from 2 to 1\""))?;
+
+        Ok(Move {
+            count: parse_number(&matches[1], "a number of crates")?,
+            src: parse_number(&matches[2], "a stack number")?,
+            dest: parse_number(&matches[3], "a stack number")?,
+        })
     }
 }
 
-fn find_top_crates_after_moves(state: &mut [Crate], moves: &[Move]) -> String {
+/// The crate on top of each stack after moving the crates one at a time.
+pub fn find_top_crates_after_moves(state: &mut [Crate], moves: &[Move]) -> String {
     for m in moves.iter() {
         preform_move(state, m);
+        trace!("{:?}\n{}", m, render_state(state));
     }
 
-    state.iter().map(|cr| cr.last().unwrap()).collect()
+    state.iter().filter_map(|cr| cr.last()).collect()
 }
 
 fn preform_move(state: &mut [Crate], m: &Move) {
     for _ in 0..m.count {
-        let value = state[m.src - 1].pop().unwrap();
+        let value = state[m.src - 1]
+            .pop()
+            .expect("moves are checked against the stack sizes by read_input");
         state[m.dest - 1].push(value);
     }
 }
 
-fn find_top_crates_after_moves_2(state: &mut [Crate], moves: &[Move]) -> String {
+/// The crate on top of each stack after moving the crates of each move all at once.
+pub fn find_top_crates_after_moves_2(state: &mut [Crate], moves: &[Move]) -> String {
     for m in moves.iter() {
         preform_move_2(state, m);
+        trace!("{:?}\n{}", m, render_state(state));
     }
 
-    state.iter().map(|cr| cr.last().unwrap()).collect()
+    state.iter().filter_map(|cr| cr.last()).collect()
 }
 
 fn preform_move_2(state: &mut [Crate], m: &Move) {
     let mut stack = Vec::new();
     for _ in 0..m.count {
-        let value = state[m.src - 1].pop().unwrap();
+        let value = state[m.src - 1]
+            .pop()
+            .expect("moves are checked against the stack sizes by read_input");
         stack.push(value);
     }
 
@@ -124,66 +183,104 @@ fn preform_move_2(state: &mut [Crate], m: &Move) {
     }
 }
 
-/*fn print_state(state: &[Crate]) {
-    for cr in state.iter() {
-        for c in cr.iter() {
-            print!("{} ", c);
-        }
-        println!();
+/// Lists the crates in each stack from the bottom up, one stack per line.
+fn render_state(state: &[Crate]) -> String {
+    state
+        .iter()
+        .map(|cr| cr.iter().map(|c| format!("{} ", c)).collect::<String>() + "\n")
+        .collect()
+}
+
+/// Generates a drawing of up to nine stacks followed by `size` moves.
+///
+/// Moves never take the last crate from a stack, so that every stack has a crate on top at the end.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let num_stacks = rng.between(2, 9) as usize;
+    let mut stacks: Vec<Crate> = (0..num_stacks)
+        .map(|_| {
+            (0..rng.between(1, 8))
+                .map(|_| (b'A' + rng.below(26) as u8) as char)
+                .collect()
+        })
+        .collect();
+    // Moves need a stack with a crate to spare
+    stacks[0].push('Z');
+
+    let mut drawing = String::new();
+    let height = stacks.iter().map(|s| s.len()).max().unwrap();
+    for row in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .map(|s| match s.get(row) {
+                Some(c) => format!("[{}]", c),
+                None => "   ".to_string(),
+            })
+            .collect();
+        drawing.push_str(&cells.join(" "));
+        drawing.push('\n');
+    }
+    let numbers: Vec<String> = (1..=num_stacks).map(|i| format!(" {} ", i)).collect();
+    drawing.push_str(&numbers.join(" "));
+    drawing.push_str("\n\n");
+
+    for _ in 0..size.max(1) {
+        let sources: Vec<usize> = (0..num_stacks).filter(|&i| stacks[i].len() > 1).collect();
+        let src = *rng.choose(&sources);
+        let dest = (src + 1 + rng.below(num_stacks - 1)) % num_stacks;
+        let count = rng.between(1, stacks[src].len() as i64 - 1) as usize;
+
+        let remaining = stacks[src].len() - count;
+        let moved = stacks[src].split_off(remaining);
+        stacks[dest].extend(moved);
+        drawing.push_str(&format!(
+            "move {} from {} to {}\n",
+            count,
+            src + 1,
+            dest + 1
+        ));
     }
-    println!();
-}*/
+
+    drawing
+}
 
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
     #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/five_example.txt").unwrap();
-        let (mut crates, moves) = read_input(&mut BufReader::new(f));
+    fn test_malformed_move() {
+        let input = "    [D]\n[N] [C]\n 1   2\n\nmove 1 from 2 to 1\nmove one from 1 to 2\n";
 
-        let expected = "CMZ".to_string();
-        let actual = find_top_crates_after_moves(&mut crates, &moves);
+        let error = read_input(&mut BufReader::new(input.as_bytes())).unwrap_err();
 
-        assert_eq!(expected, actual)
+        assert_eq!(Some(6), error.line);
+        assert_eq!("move one from 1 to 2", error.text);
     }
 
-    #[ignore]
     #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/five.txt").unwrap();
-        let (mut crates, moves) = read_input(&mut BufReader::new(f));
+    fn test_move_to_missing_stack() {
+        let input = "[N] [C]\n 1   2\n\nmove 1 from 2 to 3\n";
 
-        let expected = "JRVNHHCSJ".to_string();
-        let actual = find_top_crates_after_moves(&mut crates, &moves);
+        let error = read_input(&mut BufReader::new(input.as_bytes())).unwrap_err();
 
-        assert_eq!(expected, actual)
+        assert_eq!(Some(4), error.line);
     }
 
     #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/five_example.txt").unwrap();
-        let (mut crates, moves) = read_input(&mut BufReader::new(f));
+    fn test_move_too_many_crates() {
+        let input = "[N] [C]\n 1   2\n\nmove 1 from 2 to 1\nmove 3 from 1 to 2\n";
 
-        let expected = "MCD".to_string();
-        let actual = find_top_crates_after_moves_2(&mut crates, &moves);
+        let error = read_input(&mut BufReader::new(input.as_bytes())).unwrap_err();
 
-        assert_eq!(expected, actual)
+        assert_eq!(Some(5), error.line);
+        assert_eq!("move 3 from 1 to 2", error.text);
     }
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/five.txt").unwrap();
-        let (mut crates, moves) = read_input(&mut BufReader::new(f));
-
-        let expected = "GNFBSBJLH".to_string();
-        let actual = find_top_crates_after_moves_2(&mut crates, &moves);
+    fn test_malformed_crates() {
+        let error = read_input(&mut BufReader::new("x\n".as_bytes())).unwrap_err();
 
-        assert_eq!(expected, actual)
+        assert_eq!(Some(1), error.line);
     }
 }
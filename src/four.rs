@@ -1,48 +1,61 @@
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_contained_ranges(&values);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayFour;
 
-pub fn part_two() {
-    let numbers = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_overlapping_ranges(&numbers);
+impl Solution for DayFour {
+    type Input = Vec<(Range, Range)>;
 
-    println!("{}", answer);
-}
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-type Range = (i32, i32);
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_contained_ranges(input).into())
+    }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<(Range, Range)> {
-    let mut numbers: Vec<((i32, i32), (i32, i32))> = Vec::new();
-    for line in reader.lines() {
-        let line = line.unwrap();
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_overlapping_ranges(input).into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
 
-        let mut parts = line.split(',');
+pub type Range = (i32, i32);
 
-        let mut part_1 = parts.next().unwrap().split('-');
-        let mut part_2 = parts.next().unwrap().split('-');
+fn read_input<T: std::io::Read>(
+    reader: &mut BufReader<T>,
+) -> Result<Vec<(Range, Range)>, ParseError> {
+    parse_lines(reader, |line| {
+        let (part_1, part_2) = line
+            .split_once(',')
+            .ok_or_else(|| ParseError::new(line, "a pair of ranges, such as \"2-4,6-8\""))?;
 
-        let a: (i32, i32) = (
-            part_1.next().unwrap().parse().unwrap(),
-            part_1.next().unwrap().parse().unwrap(),
-        );
-        let b: (i32, i32) = (
-            part_2.next().unwrap().parse().unwrap(),
-            part_2.next().unwrap().parse().unwrap(),
-        );
+        Ok((parse_range(part_1)?, parse_range(part_2)?))
+    })
+}
 
-        numbers.push((a, b));
-    }
+fn parse_range(s: &str) -> Result<Range, ParseError> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| ParseError::new(s, "a range, such as \"2-4\""))?;
 
-    numbers
+    Ok((
+        parse_number(start, "the start of a range")?,
+        parse_number(end, "the end of a range")?,
+    ))
 }
 
-fn find_contained_ranges(ranges: &[(Range, Range)]) -> i32 {
+/// How many pairs have one range fully containing the other.
+pub fn find_contained_ranges(ranges: &[(Range, Range)]) -> i32 {
     let mut total = 0;
 
     for (a, b) in ranges.iter() {
@@ -54,7 +67,8 @@ fn find_contained_ranges(ranges: &[(Range, Range)]) -> i32 {
     total
 }
 
-fn find_overlapping_ranges(ranges: &[(Range, Range)]) -> i32 {
+/// How many pairs have ranges that overlap at all.
+pub fn find_overlapping_ranges(ranges: &[(Range, Range)]) -> i32 {
     let mut total = 0;
 
     for (a, b) in ranges.iter() {
@@ -80,56 +94,17 @@ fn overlaps(a: &Range, b: &Range) -> bool {
     a_s <= b_e && b_s <= a_e
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/four_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 2;
-        let actual = find_contained_ranges(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/four.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 485;
-        let actual = find_contained_ranges(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/four_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 4;
-        let actual = find_overlapping_ranges(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/four.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 857;
-        let actual = find_overlapping_ranges(&values);
-
-        assert_eq!(expected, actual)
-    }
+/// Generates `size` pairs of section assignments.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut assignment = || {
+        let start = rng.between(1, 99);
+        (start, rng.between(start, 99))
+    };
+
+    (0..size.max(1))
+        .map(|_| {
+            let ((a, b), (c, d)) = (assignment(), assignment());
+            format!("{}-{},{}-{}\n", a, b, c, d)
+        })
+        .collect()
 }
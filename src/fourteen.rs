@@ -1,162 +1,210 @@
-use std::collections::HashMap;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+use std::iter;
+
+use log::trace;
+
+use crate::answer::Answer;
+use crate::grid::{Position, SparseGrid};
+use crate::image::{Color, Image};
+use crate::params::{Param, Params};
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Part, Solution, SolveError};
+use crate::visualize::{Frame, Frames};
+
+pub struct DayFourteen;
+
+impl Solution for DayFourteen {
+    type Input = Cave;
+
+    fn params(&self) -> Vec<Param> {
+        vec![Param::point(
+            "source",
+            (500, 0),
+            "Point (x,y) that the sand pours in from",
+        )
+        .with_range(i32::MIN as i64..=i32::MAX as i64)]
+    }
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_first_fall_sand(&mut input.clone(), &sand_source(params)).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_sand_reach_source(&mut input.clone(), &sand_source(params)).into())
+    }
 
-pub fn part_one() {
-    let mut grid = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_first_fall_sand(&mut grid);
+    fn frames(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        Ok(Some(sand_frames(
+            input,
+            &sand_source(params),
+            part == Part::Two,
+        )))
+    }
+
+    fn image(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        let mut cave = input.clone();
+        let sand_source = sand_source(params);
+        match part {
+            Part::One => find_first_fall_sand(&mut cave, &sand_source),
+            Part::Two => find_sand_reach_source(&mut cave, &sand_source),
+        };
 
-    println!("{}", answer);
+        Ok(Some(cave.image(part == Part::Two)))
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-pub fn part_two() {
-    let mut grid = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_sand_reach_source(&mut grid);
+fn sand_source(params: &Params) -> Position {
+    let (x, y) = params.point("source");
 
-    println!("{}", answer);
+    Position::new(y as i32, x as i32)
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Grid {
-    let mut grid = Grid::new();
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let lines = Lines::from_str(&line);
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Cave, ParseError> {
+    let paths = parse_lines(reader, Lines::from_str)?;
+    if paths.is_empty() {
+        return Err(ParseError::new("", "at least one path of rock"));
+    }
 
-        grid.add_lines(&lines, Tile::Rock);
+    let mut cave = Cave::new();
+    for lines in paths.iter() {
+        cave.add_lines(lines, Tile::Rock);
     }
 
-    grid.set_bottom();
+    cave.set_bottom();
 
-    grid
+    Ok(cave)
 }
 
-fn find_sand_reach_source(grid: &mut Grid) -> i32 {
-    let sand_source = Position::from_str("500,0");
-
-    grid.override_bottom(grid.tiles.keys().map(|p| p.row).max().unwrap() + 2);
+/// How many units of sand come to rest before the source is blocked, once the cave has a floor.
+pub fn find_sand_reach_source(cave: &mut Cave, sand_source: &Position) -> i32 {
+    cave.add_floor();
 
     let mut current_sand = 1;
-    loop {
-        grid.set(&sand_source, Tile::Sand);
-
-        let mut sand_position = sand_source.clone();
-        loop {
-            let result = grid.step(&sand_position, true);
-            match result {
-                Some(p2) => sand_position = p2,
-                None => {
-                    if sand_position == sand_source {
-                        return current_sand;
-                    }
-
-                    break;
-                }
-            }
-        }
-
+    while cave.drop_sand(sand_source, true) != Some(*sand_source) {
         current_sand += 1;
     }
+    trace!("\n{}", cave.render());
+
+    current_sand
 }
 
-fn find_first_fall_sand(grid: &mut Grid) -> i32 {
-    let sand_source = Position::from_str("500,0");
+/// How many units of sand come to rest before sand starts falling into the abyss.
+pub fn find_first_fall_sand(cave: &mut Cave, sand_source: &Position) -> i32 {
+    let mut current_sand = 0;
+    while let Some(rest) = cave.drop_sand(sand_source, false) {
+        current_sand += 1;
+
+        // Rock close under the source can fill up and block it before any sand falls out
+        if rest == *sand_source {
+            break;
+        }
+    }
+    trace!("\n{}", cave.render());
 
-    let mut current_sand = 1;
-    loop {
-        grid.set(&sand_source, Tile::Sand);
-
-        let mut sand_position = sand_source.clone();
-        loop {
-            let result = grid.step(&sand_position, false);
-            match result {
-                Some(p2) => sand_position = p2,
-                None => break, // sand stopped moving
-            }
+    current_sand
+}
 
-            if sand_position.row > grid.bottom.unwrap() {
-                return current_sand - 1;
-            }
+/// Pours sand in one unit at a time until it stops settling, drawing the cave after each unit.
+fn sand_frames(cave: &Cave, sand_source: &Position, has_floor: bool) -> Frames {
+    let mut cave = cave.clone();
+    if has_floor {
+        cave.add_floor();
+    }
+    let sand_source = *sand_source;
+
+    let mut current_sand = 0;
+    let mut finished = false;
+    Box::new(iter::from_fn(move || {
+        if finished {
+            return None;
         }
 
         current_sand += 1;
-    }
+        let caption = match cave.drop_sand(&sand_source, has_floor) {
+            Some(rest) => {
+                finished = rest == sand_source;
+                format!(
+                    "Sand {} came to rest at {},{}",
+                    current_sand, rest.column, rest.row
+                )
+            }
+            None => {
+                finished = true;
+                format!("Sand {} fell into the abyss", current_sand)
+            }
+        };
+
+        Some(Frame::new(caption, cave.render()))
+    }))
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum Tile {
+pub enum Tile {
     Empty,
     Rock,
     Sand,
 }
 
 #[derive(Clone)]
-struct Bounds {
-    top_left: Position,
-    bottom_right: Position,
+pub struct Cave {
+    pub tiles: SparseGrid<Tile>,
+    pub bottom: i32,
 }
 
-impl Bounds {
-    fn from_position(position: &Position) -> Bounds {
-        Bounds {
-            top_left: position.clone(),
-            bottom_right: position.clone(),
+impl Cave {
+    fn new() -> Cave {
+        Cave {
+            tiles: SparseGrid::new(),
+            bottom: 0,
         }
     }
 
-    fn update(&mut self, position: &Position) {
-        if position.row < self.top_left.row {
-            self.top_left = self.top_left.changed_row(position.row);
-        }
+    /// Adds sand at the source and lets it fall, returning where it came to rest or `None` if it
+    /// fell past the lowest rock.
+    fn drop_sand(&mut self, sand_source: &Position, has_floor: bool) -> Option<Position> {
+        self.set(sand_source, Tile::Sand);
 
-        if position.column < self.top_left.column {
-            self.top_left = self.top_left.changed_column(position.column);
-        }
+        let mut sand_position = *sand_source;
+        while let Some(next) = self.step(&sand_position, has_floor) {
+            sand_position = next;
 
-        if position.row > self.bottom_right.row {
-            self.bottom_right = self.bottom_right.changed_row(position.row);
+            if !has_floor && sand_position.row > self.bottom {
+                self.set(&sand_position, Tile::Empty);
+                return None;
+            }
         }
 
-        if position.column > self.bottom_right.column {
-            self.bottom_right = self.bottom_right.changed_column(position.column);
-        }
-    }
-}
-
-struct Grid {
-    tiles: HashMap<Position, Tile>,
-    bounds: Option<Bounds>,
-    bottom: Option<i32>,
-}
-
-impl Grid {
-    fn new() -> Grid {
-        Grid {
-            tiles: HashMap::new(),
-            bounds: None,
-            bottom: None,
-        }
+        Some(sand_position)
     }
 
     fn step(&mut self, current_sand: &Position, has_floor: bool) -> Option<Position> {
-        let down = current_sand.moved(1, 0);
-        if self.get(&down, has_floor) == Tile::Empty {
-            self.set(current_sand, Tile::Empty);
-            self.set(&down, Tile::Sand);
-            return Some(down);
-        }
-
-        let down_left = current_sand.moved(1, -1);
-        if self.get(&down_left, has_floor) == Tile::Empty {
-            self.set(current_sand, Tile::Empty);
-            self.set(&down_left, Tile::Sand);
-            return Some(down_left);
-        }
-
-        let down_right = current_sand.moved(1, 1);
-        if self.get(&down_right, has_floor) == Tile::Empty {
-            self.set(current_sand, Tile::Empty);
-            self.set(&down_right, Tile::Sand);
-            return Some(down_right);
+        for column_change in [0, -1, 1] {
+            let next = current_sand.moved(1, column_change);
+            if self.get(&next, has_floor) == Tile::Empty {
+                self.set(current_sand, Tile::Empty);
+                self.set(&next, Tile::Sand);
+                return Some(next);
+            }
         }
 
         None
@@ -169,119 +217,58 @@ impl Grid {
     }
 
     fn set(&mut self, position: &Position, value: Tile) {
-        self.tiles.insert(position.clone(), value);
-
-        if self.bounds.is_none() {
-            self.bounds = Some(Bounds::from_position(position));
-        }
-
-        self.bounds.as_mut().unwrap().update(position);
+        self.tiles.insert(*position, value);
     }
 
     fn get(&self, position: &Position, has_floor: bool) -> Tile {
-        if has_floor && position.row == self.bottom.unwrap() {
+        if has_floor && position.row == self.bottom {
             return Tile::Rock;
         }
 
         self.tiles.get(position).cloned().unwrap_or(Tile::Empty)
     }
 
-    /*fn print(&self, has_floor: bool) {
-        let bounds = self.bounds.clone().unwrap();
-        for row in bounds.top_left.row..bounds.bottom_right.row + 1 {
-            for column in bounds.top_left.column..bounds.bottom_right.column + 1 {
-                print!("{}", match self.get(&Position::new(row, column), has_floor) {
-                    Tile::Empty => '.',
-                    Tile::Rock => '#',
-                    Tile::Sand => 'O',
-                });
-            }
-            println!("");
+    /// Draws the rock in grey and the sand in yellow, including the floor if there is one.
+    fn image(&self, has_floor: bool) -> Image {
+        let mut bounds = self.tiles.bounds().unwrap();
+        if has_floor {
+            bounds.include(&Position::new(self.bottom, bounds.top_left.column));
         }
-    }*/
 
-    fn set_bottom(&mut self) {
-        self.bottom = Some(self.bounds.clone().unwrap().bottom_right.row);
+        Image::from_bounds(&bounds, |p| match self.get(p, has_floor) {
+            Tile::Rock => Color::new(120, 110, 100),
+            Tile::Sand => Color::new(230, 190, 90),
+            Tile::Empty => Color::new(20, 20, 30),
+        })
     }
 
-    fn override_bottom(&mut self, bottom: i32) {
-        self.bottom = Some(bottom);
+    fn render(&self) -> String {
+        self.tiles.render(|tile| match tile {
+            Some(Tile::Rock) => '#',
+            Some(Tile::Sand) => 'O',
+            Some(Tile::Empty) | None => '.',
+        })
     }
-}
-
-#[derive(Eq, PartialEq, Hash, Debug, Clone)]
-struct Position {
-    row: i32,
-    column: i32,
-}
-
-impl Position {
-    fn new(r: i32, c: i32) -> Position {
-        Position { row: r, column: c }
-    }
-
-    fn from_str(s: &str) -> Position {
-        let parts: Vec<&str> = s.split(',').collect();
-
-        Position {
-            row: parts[1].parse().unwrap(),
-            column: parts[0].parse().unwrap(),
-        }
-    }
-
-    fn changed_row(&self, row: i32) -> Position {
-        Position::new(row, self.column)
-    }
-
-    fn changed_column(&self, column: i32) -> Position {
-        Position::new(self.row, column)
-    }
-
-    fn moved(&self, row_change: i32, column_change: i32) -> Position {
-        let new_row = row_change + self.row;
-        let new_column = column_change + self.column;
 
-        Position::new(new_row, new_column)
-    }
-
-    fn moved_by(&self, other: &Position) -> Position {
-        self.moved(other.row, other.column)
+    fn set_bottom(&mut self) {
+        self.bottom = self.tiles.bounds().unwrap().bottom_right.row;
     }
 
-    fn subtracted(&self, other: &Position) -> Position {
-        Position::new(self.row - other.row, self.column - other.column)
+    /// Puts the floor two rows below the lowest rock.
+    fn add_floor(&mut self) {
+        self.bottom = self.tiles.bounds().unwrap().bottom_right.row + 2;
     }
+}
 
-    fn normalized_cardinal(&self) -> Position {
-        let row = if self.row != 0 {
-            self.row / self.row.abs()
-        } else {
-            0
-        };
-
-        let column = if self.column != 0 {
-            self.column / self.column.abs()
-        } else {
-            0
-        };
-
-        Position::new(row, column)
-    }
-
-    fn get_points_to(&self, other: &Position) -> Vec<Position> {
-        let diff = other.subtracted(self).normalized_cardinal();
-
-        let mut points = Vec::new();
-
-        let mut current = self.clone();
-        while current != *other {
-            points.push(current.clone());
-            current = current.moved_by(&diff);
-        }
-        points.push(current);
+fn parse_position(s: &str) -> Result<Position, ParseError> {
+    let (column, row) = s
+        .split_once(',')
+        .ok_or_else(|| ParseError::new(s, "a point, such as \"498,4\""))?;
 
-        points
-    }
+    Ok(Position::new(
+        parse_number(row, "a y coordinate")?,
+        parse_number(column, "an x coordinate")?,
+    ))
 }
 
 struct Lines {
@@ -289,70 +276,69 @@ struct Lines {
 }
 
 impl Lines {
-    fn from_str(s: &str) -> Lines {
-        Lines {
-            points: s.split(" -> ").map(Position::from_str).collect(),
+    fn from_str(s: &str) -> Result<Lines, ParseError> {
+        let points = s
+            .split(" -> ")
+            .map(parse_position)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (p1, p2) in points.iter().zip(points.iter().skip(1)) {
+            if p1.row != p2.row && p1.column != p2.column {
+                return Err(ParseError::new(
+                    s,
+                    "a path of only horizontal and vertical lines",
+                ));
+            }
         }
+
+        Ok(Lines { points })
     }
 
     fn get_positions(&self) -> Vec<Position> {
         self.points
             .iter()
             .zip(self.points.iter().skip(1))
-            .flat_map(|(p1, p2)| p1.get_points_to(p2))
+            .flat_map(|(p1, p2)| p1.line_to(p2))
             .collect()
     }
 }
 
+/// Generates `size` paths of rock below the sand's default source at 500,0.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1) as i64;
+    let (width, depth) = (size + 10, size + 5);
+
+    (0..size)
+        .map(|_| {
+            let mut x = rng.between(500 - width, 500 + width);
+            let mut y = rng.between(1, depth);
+            let mut points = vec![format!("{},{}", x, y)];
+
+            for i in 0..rng.between(1, 4) {
+                if i % 2 == 0 {
+                    x = rng.between(x - 5, x + 5);
+                } else {
+                    y = rng.between((y - 5).max(1), y + 5);
+                }
+                points.push(format!("{},{}", x, y));
+            }
+
+            format!("{}\n", points.join(" -> "))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/fourteen_example.txt").unwrap();
-        let mut grid = read_input(&mut BufReader::new(f));
-
-        let expected = 24;
-        let actual = find_first_fall_sand(&mut grid);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/fourteen.txt").unwrap();
-        let mut grid = read_input(&mut BufReader::new(f));
-
-        let expected = 618;
-        let actual = find_first_fall_sand(&mut grid);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/fourteen_example.txt").unwrap();
-        let mut grid = read_input(&mut BufReader::new(f));
-
-        let expected = 93;
-        let actual = find_sand_reach_source(&mut grid);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/fourteen.txt").unwrap();
-        let mut grid = read_input(&mut BufReader::new(f));
+    fn test_part_1_source_blocked() {
+        let mut cave = read_input(&mut BufReader::new("499,1 -> 501,1".as_bytes())).unwrap();
 
-        let expected = 26358;
-        let actual = find_sand_reach_source(&mut grid);
+        let expected = 1;
+        let actual = find_first_fall_sand(&mut cave, &Position::new(0, 500));
 
         assert_eq!(expected, actual)
     }
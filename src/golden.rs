@@ -0,0 +1,24 @@
+use std::path::{Path, PathBuf};
+
+use crate::answers::{ExpectedAnswers, ANSWERS_PATH};
+use crate::get_day;
+use crate::runner::run_file;
+use crate::solution::Part;
+
+/// Solves the part for `inputs/<input>.txt` and compares the answer with the one in answers.txt.
+fn check(day: u32, part: Part, input: &str) {
+    let answers = ExpectedAnswers::load(Path::new(ANSWERS_PATH)).unwrap();
+    let expected = answers.get(day, part, input).unwrap();
+
+    let day = get_day(day).unwrap_or_else(|| panic!("There is no solution for day {}", day));
+    let result = run_file(&day, part, &PathBuf::from(format!("inputs/{}.txt", input)));
+
+    match result.outcome {
+        Ok(answer) => assert_eq!(expected, answer.to_string()),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+// A test per known answer whose input is present, such as `day_9_part_two_nine_example_2`, written
+// by build.rs
+include!(concat!(env!("OUT_DIR"), "/golden_tests.rs"));
@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::iter;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use num_traits::{PrimInt, Signed};
+
+use crate::parse::ParseError;
+
+/// An integer type that positions can be made of, such as `i32` or `i64`.
+pub trait Coord: PrimInt + Signed + Hash + Debug {}
+
+impl<T: PrimInt + Signed + Hash + Debug> Coord for T {}
+
+/// A position on a 2D grid, with rows increasing downwards and columns increasing to the right.
+///
+/// Also used for offsets between positions, such as the step taken in a `Direction`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Position<T: Coord = i32> {
+    pub row: T,
+    pub column: T,
+}
+
+impl<T: Coord> Position<T> {
+    pub const fn new(row: T, column: T) -> Position<T> {
+        Position { row, column }
+    }
+
+    pub fn origin() -> Position<T> {
+        Position::new(T::zero(), T::zero())
+    }
+
+    pub fn moved(&self, row_diff: T, column_diff: T) -> Position<T> {
+        Position::new(self.row + row_diff, self.column + column_diff)
+    }
+
+    /// The position `num_steps` away in the given direction.
+    pub fn step<D: Into<Position<T>>>(&self, direction: D, num_steps: T) -> Position<T> {
+        *self + direction.into() * num_steps
+    }
+
+    pub fn manhattan_length(&self) -> T {
+        self.row.abs() + self.column.abs()
+    }
+
+    pub fn manhattan_distance(&self, other: &Position<T>) -> T {
+        (*other - *self).manhattan_length()
+    }
+
+    /// The number of king's moves between two positions, where diagonal steps count as one.
+    pub fn chebyshev_distance(&self, other: &Position<T>) -> T {
+        let diff = *other - *self;
+
+        diff.row.abs().max(diff.column.abs())
+    }
+
+    /// The offset with each of its components reduced to -1, 0 or 1.
+    pub fn signum(&self) -> Position<T> {
+        Position::new(self.row.signum(), self.column.signum())
+    }
+
+    /// The positions directly above, below, left and right of this one.
+    pub fn neighbors4(&self) -> impl Iterator<Item = Position<T>> {
+        let position = *self;
+
+        IntoIterator::into_iter(Direction::all()).map(move |d| position.step(d, T::one()))
+    }
+
+    /// The positions around this one, including the diagonals.
+    pub fn neighbors8(&self) -> impl Iterator<Item = Position<T>> {
+        let position = *self;
+
+        IntoIterator::into_iter(Direction8::all()).map(move |d| position.step(d, T::one()))
+    }
+
+    /// The positions along the straight or diagonal line from this position to `other`,
+    /// including both ends.
+    pub fn line_to(&self, other: &Position<T>) -> Vec<Position<T>> {
+        let diff = *other - *self;
+        assert!(
+            diff.row.is_zero() || diff.column.is_zero() || diff.row.abs() == diff.column.abs(),
+            "{:?} to {:?} is not a straight or diagonal line",
+            self,
+            other
+        );
+
+        let step = diff.signum();
+        let mut points = vec![*self];
+        let mut current = *self;
+        while current != *other {
+            current = current + step;
+            points.push(current);
+        }
+
+        points
+    }
+}
+
+impl<T: Coord> Add for Position<T> {
+    type Output = Position<T>;
+
+    fn add(self, other: Position<T>) -> Position<T> {
+        Position::new(self.row + other.row, self.column + other.column)
+    }
+}
+
+impl<T: Coord> Sub for Position<T> {
+    type Output = Position<T>;
+
+    fn sub(self, other: Position<T>) -> Position<T> {
+        Position::new(self.row - other.row, self.column - other.column)
+    }
+}
+
+impl<T: Coord> Neg for Position<T> {
+    type Output = Position<T>;
+
+    fn neg(self) -> Position<T> {
+        Position::new(-self.row, -self.column)
+    }
+}
+
+impl<T: Coord> Mul<T> for Position<T> {
+    type Output = Position<T>;
+
+    fn mul(self, scale: T) -> Position<T> {
+        Position::new(self.row * scale, self.column * scale)
+    }
+}
+
+/// One of the four directions along the rows and columns of a grid.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// All of the directions, clockwise from north.
+    pub fn all() -> [Direction; 4] {
+        [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+        ]
+    }
+
+    pub fn turned_right(&self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn turned_left(&self) -> Direction {
+        self.reversed().turned_right()
+    }
+
+    pub fn reversed(&self) -> Direction {
+        self.turned_right().turned_right()
+    }
+
+    pub fn offset<T: Coord>(&self) -> Position<T> {
+        let (zero, one) = (T::zero(), T::one());
+
+        match self {
+            Direction::North => Position::new(-one, zero),
+            Direction::East => Position::new(zero, one),
+            Direction::South => Position::new(one, zero),
+            Direction::West => Position::new(zero, -one),
+        }
+    }
+}
+
+impl<T: Coord> From<Direction> for Position<T> {
+    fn from(direction: Direction) -> Position<T> {
+        direction.offset()
+    }
+}
+
+/// One of the eight directions of a compass, including the diagonals.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    /// All of the directions, clockwise from north.
+    pub fn all() -> [Direction8; 8] {
+        [
+            Direction8::North,
+            Direction8::NorthEast,
+            Direction8::East,
+            Direction8::SouthEast,
+            Direction8::South,
+            Direction8::SouthWest,
+            Direction8::West,
+            Direction8::NorthWest,
+        ]
+    }
+
+    pub fn offset<T: Coord>(&self) -> Position<T> {
+        let north = Direction::North.offset::<T>();
+        let east = Direction::East.offset::<T>();
+
+        match self {
+            Direction8::North => north,
+            Direction8::NorthEast => north + east,
+            Direction8::East => east,
+            Direction8::SouthEast => -north + east,
+            Direction8::South => -north,
+            Direction8::SouthWest => -north - east,
+            Direction8::West => -east,
+            Direction8::NorthWest => north - east,
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Direction8 {
+        match direction {
+            Direction::North => Direction8::North,
+            Direction::East => Direction8::East,
+            Direction::South => Direction8::South,
+            Direction::West => Direction8::West,
+        }
+    }
+}
+
+impl<T: Coord> From<Direction8> for Position<T> {
+    fn from(direction: Direction8) -> Position<T> {
+        direction.offset()
+    }
+}
+
+/// The smallest rectangle containing a set of positions, including its edges.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Bounds<T: Coord = i32> {
+    pub top_left: Position<T>,
+    pub bottom_right: Position<T>,
+}
+
+impl<T: Coord> Bounds<T> {
+    pub fn from_position(position: &Position<T>) -> Bounds<T> {
+        Bounds {
+            top_left: *position,
+            bottom_right: *position,
+        }
+    }
+
+    /// The bounds of the given positions, or `None` if there aren't any.
+    pub fn from_positions<'a>(
+        positions: impl IntoIterator<Item = &'a Position<T>>,
+    ) -> Option<Bounds<T>>
+    where
+        T: 'a,
+    {
+        let mut positions = positions.into_iter();
+        let mut bounds = Bounds::from_position(positions.next()?);
+        for position in positions {
+            bounds.include(position);
+        }
+
+        Some(bounds)
+    }
+
+    /// Grows the bounds to contain the given position.
+    pub fn include(&mut self, position: &Position<T>) {
+        self.top_left.row = self.top_left.row.min(position.row);
+        self.top_left.column = self.top_left.column.min(position.column);
+        self.bottom_right.row = self.bottom_right.row.max(position.row);
+        self.bottom_right.column = self.bottom_right.column.max(position.column);
+    }
+
+    pub fn contains(&self, position: &Position<T>) -> bool {
+        (self.top_left.row..=self.bottom_right.row).contains(&position.row)
+            && (self.top_left.column..=self.bottom_right.column).contains(&position.column)
+    }
+
+    pub fn width(&self) -> T {
+        self.bottom_right.column - self.top_left.column + T::one()
+    }
+
+    pub fn height(&self) -> T {
+        self.bottom_right.row - self.top_left.row + T::one()
+    }
+
+    pub fn area(&self) -> T {
+        self.width() * self.height()
+    }
+
+    /// All of the positions within the bounds, row by row from the top left.
+    pub fn positions(&self) -> impl Iterator<Item = Position<T>> {
+        let (top_left, bottom_right) = (self.top_left, self.bottom_right);
+
+        range_inclusive(top_left.row, bottom_right.row).flat_map(move |row| {
+            range_inclusive(top_left.column, bottom_right.column)
+                .map(move |column| Position::new(row, column))
+        })
+    }
+
+    /// Draws each position within the bounds as a character, with a line per row.
+    pub fn render(&self, draw: impl Fn(&Position<T>) -> char) -> String {
+        let mut text = String::new();
+        for position in self.positions() {
+            text.push(draw(&position));
+            if position.column == self.bottom_right.column {
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    /// Splits the bounds into at most `max_width` by `max_height` equally sized blocks, row by row,
+    /// for drawing bounds that are too large to draw one position at a time.
+    pub fn blocks(&self, max_width: usize, max_height: usize) -> Vec<Vec<Bounds<T>>> {
+        let block_size = |length: T, max: usize| {
+            let max = T::from(max.max(1)).unwrap_or_else(T::max_value);
+            ((length + max - T::one()) / max).max(T::one())
+        };
+        let block_width = block_size(self.width(), max_width);
+        let block_height = block_size(self.height(), max_height);
+
+        let mut rows = vec![];
+        let mut top = self.top_left.row;
+        while top <= self.bottom_right.row {
+            let mut row = vec![];
+            let mut left = self.top_left.column;
+            while left <= self.bottom_right.column {
+                row.push(Bounds {
+                    top_left: Position::new(top, left),
+                    bottom_right: Position::new(
+                        (top + block_height - T::one()).min(self.bottom_right.row),
+                        (left + block_width - T::one()).min(self.bottom_right.column),
+                    ),
+                });
+
+                left = left + block_width;
+            }
+            rows.push(row);
+
+            top = top + block_height;
+        }
+
+        rows
+    }
+
+    /// Draws the bounds in at most `max_width` by `max_height` characters, see `blocks`.
+    pub fn render_scaled(
+        &self,
+        max_width: usize,
+        max_height: usize,
+        draw: impl Fn(&Bounds<T>) -> char,
+    ) -> String {
+        self.blocks(max_width, max_height)
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(&draw)
+                    .chain(iter::once('\n'))
+                    .collect::<String>()
+            })
+            .collect()
+    }
+}
+
+fn range_inclusive<T: Coord>(start: T, end: T) -> impl Iterator<Item = T> {
+    iter::successors(Some(start).filter(|s| *s <= end), move |&n| {
+        Some(n + T::one()).filter(|n| *n <= end)
+    })
+}
+
+/// A rectangular grid with a value at every position, starting from (0, 0) in the top left.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Parses a map with a character per position, such as a puzzle input. `parse_char` returns
+    /// `None` for characters that aren't allowed, and `expected` describes what a row should be.
+    pub fn parse<S: AsRef<str>>(
+        lines: &[S],
+        expected: &str,
+        parse_char: impl Fn(char) -> Option<T>,
+    ) -> Result<Grid<T>, ParseError> {
+        let width = lines
+            .first()
+            .map(|l| l.as_ref().chars().count())
+            .unwrap_or(0);
+        if width == 0 {
+            return Err(ParseError::new("", expected));
+        }
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (i, line) in lines.iter().enumerate() {
+            let line = line.as_ref();
+            let row: Option<Vec<T>> = line.chars().map(&parse_char).collect();
+
+            match row {
+                Some(row) if row.len() == width => cells.extend(row),
+                _ => return Err(ParseError::new(line, expected).on_line(i + 1)),
+            }
+        }
+
+        Ok(Grid {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            top_left: Position::origin(),
+            bottom_right: Position::new(self.height as i32 - 1, self.width as i32 - 1),
+        }
+    }
+
+    pub fn contains(&self, position: &Position) -> bool {
+        position.row >= 0
+            && position.column >= 0
+            && (position.row as usize) < self.height
+            && (position.column as usize) < self.width
+    }
+
+    fn index(&self, position: &Position) -> Option<usize> {
+        if self.contains(position) {
+            Some(position.row as usize * self.width + position.column as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, position: &Position) -> Option<&T> {
+        self.index(position).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, position: &Position) -> Option<&mut T> {
+        self.index(position).map(move |i| &mut self.cells[i])
+    }
+
+    /// Sets the value at a position, panicking if it is outside of the grid.
+    pub fn set(&mut self, position: &Position, value: T) {
+        match self.get_mut(position) {
+            Some(cell) => *cell = value,
+            None => panic!("{:?} is outside of the grid", position),
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        self.bounds().positions()
+    }
+
+    /// Each position in the grid with its value, row by row from the top left.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The neighbours of a position that are within the grid, not including diagonals.
+    pub fn neighbors4<'a>(&'a self, position: &Position) -> impl Iterator<Item = Position> + 'a {
+        position.neighbors4().filter(move |p| self.contains(p))
+    }
+
+    pub fn render(&self, draw: impl Fn(&T) -> char) -> String {
+        self.bounds()
+            .render(|p| draw(self.get(p).expect("position is within the bounds")))
+    }
+}
+
+/// A grid that only stores the positions that have values, for grids that are unbounded or
+/// mostly empty.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SparseGrid<V, T: Coord = i32> {
+    values: HashMap<Position<T>, V>,
+    bounds: Option<Bounds<T>>,
+}
+
+impl<V, T: Coord> Default for SparseGrid<V, T> {
+    fn default() -> SparseGrid<V, T> {
+        SparseGrid {
+            values: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<V, T: Coord> SparseGrid<V, T> {
+    pub fn new() -> SparseGrid<V, T> {
+        SparseGrid::default()
+    }
+
+    /// Parses a map with a character per position. Characters in `empty` leave their position
+    /// without a value, and `parse_char` returns `None` for characters that aren't allowed.
+    pub fn parse<S: AsRef<str>>(
+        lines: &[S],
+        empty: &[char],
+        expected: &str,
+        parse_char: impl Fn(char) -> Option<V>,
+    ) -> Result<SparseGrid<V, T>, ParseError> {
+        let mut grid = SparseGrid::new();
+        for (row, line) in lines.iter().enumerate() {
+            let line = line.as_ref();
+            for (column, c) in line.chars().enumerate() {
+                if empty.contains(&c) {
+                    continue;
+                }
+
+                let value = parse_char(c)
+                    .ok_or_else(|| ParseError::new(line, expected).on_line(row + 1))?;
+                let position = Position::new(
+                    T::from(row).expect("row fits in the coordinate type"),
+                    T::from(column).expect("column fits in the coordinate type"),
+                );
+
+                grid.insert(position, value);
+            }
+        }
+
+        Ok(grid)
+    }
+
+    pub fn get(&self, position: &Position<T>) -> Option<&V> {
+        self.values.get(position)
+    }
+
+    pub fn contains(&self, position: &Position<T>) -> bool {
+        self.values.contains_key(position)
+    }
+
+    /// Sets the value at a position, growing the bounds to include it.
+    pub fn insert(&mut self, position: Position<T>, value: V) -> Option<V> {
+        match self.bounds.as_mut() {
+            Some(bounds) => bounds.include(&position),
+            None => self.bounds = Some(Bounds::from_position(&position)),
+        }
+
+        self.values.insert(position, value)
+    }
+
+    /// Removes the value at a position. The bounds stay as they were, so they are the bounds of
+    /// every position that has ever had a value.
+    pub fn remove(&mut self, position: &Position<T>) -> Option<V> {
+        self.values.remove(position)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn bounds(&self) -> Option<Bounds<T>> {
+        self.bounds
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Position<T>, &V)> {
+        self.values.iter()
+    }
+
+    /// Draws the grid within its bounds, with `draw` given `None` for positions without a value.
+    pub fn render(&self, draw: impl Fn(Option<&V>) -> char) -> String {
+        match self.bounds {
+            Some(bounds) => bounds.render(|p| draw(self.get(p))),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_position_arithmetic() {
+        let a = Position::new(1, 2);
+        let b = Position::new(4, -2);
+
+        assert_eq!(Position::new(5, 0), a + b);
+        assert_eq!(Position::new(3, -4), b - a);
+        assert_eq!(7, a.manhattan_distance(&b));
+        assert_eq!(4, a.chebyshev_distance(&b));
+        assert_eq!(Position::new(1, -1), (b - a).signum());
+        assert_eq!(Position::new(-2, 2), a.step(Direction::North, 3));
+        assert_eq!(Position::new(2, 1), a.step(Direction8::SouthWest, 1));
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let position: Position<i64> = Position::new(0, 0);
+
+        let neighbors4: Vec<_> = position.neighbors4().collect();
+        assert_eq!(4, neighbors4.len());
+        assert!(neighbors4.iter().all(|n| n.manhattan_length() == 1));
+
+        let neighbors8: Vec<_> = position.neighbors8().collect();
+        assert_eq!(8, neighbors8.len());
+        assert!(neighbors8
+            .iter()
+            .all(|n| position.chebyshev_distance(n) == 1));
+    }
+
+    #[test]
+    fn test_directions() {
+        for direction in Direction::all() {
+            assert_eq!(direction, direction.turned_left().turned_right());
+            assert_eq!(
+                -direction.offset::<i32>(),
+                direction.reversed().offset::<i32>()
+            );
+        }
+
+        assert_eq!(Direction::West, Direction::North.turned_left());
+    }
+
+    #[test]
+    fn test_line_to() {
+        assert_eq!(
+            vec![
+                Position::new(4, 498),
+                Position::new(5, 498),
+                Position::new(6, 498)
+            ],
+            Position::new(4, 498).line_to(&Position::new(6, 498))
+        );
+        assert_eq!(
+            vec![Position::new(0, 0)],
+            Position::new(0, 0).line_to(&Position::new(0, 0))
+        );
+    }
+
+    #[test]
+    fn test_bounds() {
+        let positions = [
+            Position::new(2, 3),
+            Position::new(-1, 5),
+            Position::new(0, 0),
+        ];
+        let bounds = Bounds::from_positions(&positions).unwrap();
+
+        assert_eq!(Position::new(-1, 0), bounds.top_left);
+        assert_eq!(Position::new(2, 5), bounds.bottom_right);
+        assert_eq!(24, bounds.area());
+        assert_eq!(24, bounds.positions().count());
+        assert!(bounds.contains(&Position::new(1, 1)));
+        assert!(!bounds.contains(&Position::new(3, 1)));
+        assert!(Bounds::<i32>::from_positions(&[]).is_none());
+    }
+
+    #[test]
+    fn test_bounds_render_scaled() {
+        let bounds = Bounds::from_positions(&[Position::new(0, 0), Position::new(3, 4)]).unwrap();
+        let draw = |block: &Bounds| std::char::from_digit(block.area() as u32, 10).unwrap();
+
+        assert_eq!("221\n221\n221\n221\n", bounds.render_scaled(3, 10, draw));
+        assert_eq!("442\n442\n", bounds.render_scaled(3, 2, draw));
+        assert_eq!(bounds.render(|_| '1'), bounds.render_scaled(10, 10, draw));
+    }
+
+    #[test]
+    fn test_grid_parse_and_render() {
+        let lines = ["30373", "25512"];
+        let grid = Grid::parse(&lines, "a row of digits", |c| c.to_digit(10)).unwrap();
+
+        assert_eq!(5, grid.width());
+        assert_eq!(2, grid.height());
+        assert_eq!(Some(&5), grid.get(&Position::new(1, 1)));
+        assert_eq!(None, grid.get(&Position::new(2, 0)));
+        assert_eq!(2, grid.neighbors4(&Position::new(0, 0)).count());
+        assert_eq!(
+            "30373\n25512\n",
+            grid.render(|d| std::char::from_digit(*d, 10).unwrap())
+        );
+
+        let error = Grid::parse(&["123", "45"], "a row of digits", |c| c.to_digit(10)).unwrap_err();
+        assert_eq!(Some(2), error.line);
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let lines = ["  .#", "#..."];
+        let mut grid: SparseGrid<bool> =
+            SparseGrid::parse(&lines, &[' '], "a row of tiles", |c| match c {
+                '#' => Some(true),
+                '.' => Some(false),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(6, grid.len());
+        assert_eq!(Some(&true), grid.get(&Position::new(0, 3)));
+        assert!(!grid.contains(&Position::new(0, 0)));
+
+        grid.insert(Position::new(-1, 0), true);
+        assert_eq!(Position::new(-1, 0), grid.bounds().unwrap().top_left);
+
+        let render = |tile: Option<&bool>| match tile {
+            Some(true) => '#',
+            Some(false) => '.',
+            None => ' ',
+        };
+        assert_eq!("#   \n  .#\n#...\n", grid.render(render));
+    }
+}
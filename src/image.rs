@@ -0,0 +1,443 @@
+use std::fs;
+use std::path::Path;
+
+use crate::grid::{Bounds, Coord, Position};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(255, 255, 255);
+
+    pub const fn new(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+
+    /// The color `t` of the way from this color to `other`, where `t` is from 0 to 1.
+    pub fn mix(&self, other: &Color, t: f64) -> Color {
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+
+        Color::new(
+            channel(self.red, other.red),
+            channel(self.green, other.green),
+            channel(self.blue, other.blue),
+        )
+    }
+
+    /// A heatmap color for `t` from 0 to 1, going from black through red and yellow to white.
+    pub fn heat(t: f64) -> Color {
+        const STOPS: [Color; 4] = [
+            Color::BLACK,
+            Color::new(200, 0, 0),
+            Color::new(255, 220, 0),
+            Color::WHITE,
+        ];
+
+        let scaled = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f64;
+        let i = (scaled.floor() as usize).min(STOPS.len() - 2);
+
+        STOPS[i].mix(&STOPS[i + 1], scaled - i as f64)
+    }
+
+    /// The color used for a character of a text frame, see `Image::from_text`.
+    pub fn for_char(c: char) -> Color {
+        match c {
+            ' ' => Color::BLACK,
+            '.' => Color::new(40, 40, 50),
+            '#' => Color::new(140, 140, 150),
+            'O' => Color::new(230, 190, 90),
+            '@' => Color::new(240, 100, 40),
+            'S' => Color::WHITE,
+            'B' => Color::new(60, 120, 255),
+            'X' => Color::new(255, 40, 40),
+            '=' => Color::new(200, 200, 120),
+            '-' => Color::new(90, 90, 60),
+            '^' | '>' | 'v' | '<' => Color::new(60, 220, 90),
+            '0'..='9' => Color::heat(c.to_digit(10).unwrap() as f64 / 9.0),
+            _ => {
+                // Any other character gets a bright color of its own
+                let hash = (c as u32).wrapping_mul(2654435761);
+                Color::new(
+                    128 | (hash >> 24) as u8,
+                    128 | (hash >> 16) as u8,
+                    128 | (hash >> 8) as u8,
+                )
+            }
+        }
+    }
+
+    fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// The format matching the extension of a file name, e.g. "out.png".
+    pub fn from_path(path: &Path) -> Result<ImageFormat, String> {
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        ImageFormat::from_extension(&extension).ok_or_else(|| {
+            format!(
+                "Unknown image format for {}, expected a .ppm, .png or .svg file",
+                path.display()
+            )
+        })
+    }
+
+    pub fn from_extension(extension: &str) -> Option<ImageFormat> {
+        match extension {
+            "ppm" => Some(ImageFormat::Ppm),
+            "png" => Some(ImageFormat::Png),
+            "svg" => Some(ImageFormat::Svg),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+            ImageFormat::Svg => "svg",
+        }
+    }
+}
+
+/// A picture made of colored pixels, row by row from the top left.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, fill: Color) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// An image with a pixel for each position within the bounds.
+    pub fn from_bounds<T: Coord>(
+        bounds: &Bounds<T>,
+        color: impl Fn(&Position<T>) -> Color,
+    ) -> Image {
+        let width = bounds.width().to_usize().unwrap_or(0);
+        let height = bounds.height().to_usize().unwrap_or(0);
+
+        Image {
+            width,
+            height,
+            pixels: bounds.positions().map(|p| color(&p)).collect(),
+        }
+    }
+
+    /// An image of at most `max_width` by `max_height` pixels, where each pixel is a block of
+    /// positions within the bounds, see `Bounds::blocks`.
+    pub fn from_blocks<T: Coord>(
+        bounds: &Bounds<T>,
+        max_width: usize,
+        max_height: usize,
+        color: impl Fn(&Bounds<T>) -> Color,
+    ) -> Image {
+        let blocks = bounds.blocks(max_width, max_height);
+
+        Image {
+            width: blocks.first().map_or(0, |row| row.len()),
+            height: blocks.len(),
+            pixels: blocks.iter().flatten().map(color).collect(),
+        }
+    }
+
+    /// An image of a text picture, such as a visualization frame, with a pixel per character.
+    /// Short lines are padded with black.
+    pub fn from_text(text: &str, color: impl Fn(char) -> Color) -> Image {
+        let lines: Vec<&str> = text.lines().collect();
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+        let mut image = Image::new(width, lines.len(), Color::BLACK);
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                image.set(x, y, color(c));
+            }
+        }
+
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Color> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Sets the color of a pixel, panicking if it is outside of the image.
+    pub fn set(&mut self, x: usize, y: usize, color: Color) {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) is outside of the image",
+            x,
+            y
+        );
+
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// The image with each pixel drawn as a `factor` by `factor` square.
+    pub fn scaled(&self, factor: usize) -> Image {
+        let factor = factor.max(1);
+        let mut image = Image::new(self.width * factor, self.height * factor, Color::BLACK);
+        for y in 0..image.height {
+            for x in 0..image.width {
+                image.set(x, y, self.pixels[(y / factor) * self.width + x / factor]);
+            }
+        }
+
+        image
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            ImageFormat::Ppm => self.to_ppm(),
+            ImageFormat::Png => self.to_png(),
+            ImageFormat::Svg => self.to_svg().into_bytes(),
+        }
+    }
+
+    /// Writes the image in the format matching the file's extension.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let format = ImageFormat::from_path(path)?;
+
+        fs::write(path, self.encode(format))
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    /// A binary PPM (P6) image.
+    fn to_ppm(&self) -> Vec<u8> {
+        let mut bytes = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for pixel in self.pixels.iter() {
+            bytes.extend_from_slice(&[pixel.red, pixel.green, pixel.blue]);
+        }
+
+        bytes
+    }
+
+    /// A truecolor PNG image. The image data isn't compressed, which keeps the encoder small at the
+    /// cost of larger files.
+    fn to_png(&self) -> Vec<u8> {
+        let mut header = vec![];
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bits per channel, truecolor, deflate, adaptive filtering, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        // Each row starts with its filter type, which is always none
+        let mut data = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            data.push(0);
+            for pixel in row {
+                data.extend_from_slice(&[pixel.red, pixel.green, pixel.blue]);
+            }
+        }
+
+        let mut bytes = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+        write_png_chunk(&mut bytes, b"IHDR", &header);
+        write_png_chunk(&mut bytes, b"IDAT", &zlib_stored(&data));
+        write_png_chunk(&mut bytes, b"IEND", &[]);
+
+        bytes
+    }
+
+    /// An SVG image, with a rectangle for each run of same colored pixels in a row.
+    fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" shape-rendering=\"crispEdges\">\n",
+            self.width, self.height
+        );
+
+        for y in 0..self.height {
+            let row = &self.pixels[y * self.width..(y + 1) * self.width];
+            let mut x = 0;
+            while x < row.len() {
+                let run = row[x..].iter().take_while(|c| **c == row[x]).count();
+                svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" fill=\"{}\"/>\n",
+                    x,
+                    y,
+                    run,
+                    row[x].to_hex()
+                ));
+                x += run;
+            }
+        }
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+}
+
+fn write_png_chunk(bytes: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = bytes.len();
+    bytes.extend_from_slice(kind);
+    bytes.extend_from_slice(data);
+    let crc = crc32(&bytes[start..]);
+
+    bytes.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps the data in a zlib stream made of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const MAX_BLOCK: usize = 65535;
+
+    let mut bytes = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        bytes.extend_from_slice(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+
+        bytes.push(is_final as u8);
+        bytes.extend_from_slice(&length.to_le_bytes());
+        bytes.extend_from_slice(&(!length).to_le_bytes());
+        bytes.extend_from_slice(block);
+    }
+    bytes.extend_from_slice(&adler32(data).to_be_bytes());
+
+    bytes
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn checkerboard() -> Image {
+        let bounds = Bounds::from_positions(&[Position::new(0, 0), Position::new(1, 2)]).unwrap();
+
+        Image::from_bounds(&bounds, |p| {
+            if (p.row + p.column) % 2 == 0 {
+                Color::WHITE
+            } else {
+                Color::BLACK
+            }
+        })
+    }
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(0xae426082, crc32(b"IEND"));
+        assert_eq!(0x11e60398, adler32(b"Wikipedia"));
+    }
+
+    #[test]
+    fn test_ppm() {
+        let ppm = checkerboard().encode(ImageFormat::Ppm);
+
+        assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+        assert_eq!(11 + 3 * 2 * 3, ppm.len());
+        assert_eq!(&[255, 255, 255, 0, 0, 0], &ppm[11..17]);
+    }
+
+    #[test]
+    fn test_png() {
+        let png = checkerboard().encode(ImageFormat::Png);
+
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(b"IHDR", &png[12..16]);
+        assert_eq!(&[0, 0, 0, 3, 0, 0, 0, 2], &png[16..24]);
+        assert_eq!(b"IEND", &png[png.len() - 8..png.len() - 4]);
+
+        // Two rows of a filter byte and three pixels, in one final stored block
+        let data = zlib_stored(&[0; 20]);
+        assert_eq!(&[0x78, 0x01, 1, 20, 0, 0xeb, 0xff], &data[..7]);
+        assert_eq!(2 + 5 + 20 + 4, data.len());
+    }
+
+    #[test]
+    fn test_svg() {
+        let svg = checkerboard().encode(ImageFormat::Svg);
+        let svg = String::from_utf8(svg).unwrap();
+
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(6, svg.matches("<rect").count());
+        assert!(svg.contains("<rect x=\"1\" y=\"0\" width=\"1\" height=\"1\" fill=\"#000000\"/>"));
+    }
+
+    #[test]
+    fn test_from_text_and_scaled() {
+        let image = Image::from_text("#.\n#\n", Color::for_char);
+        assert_eq!((2, 2), (image.width(), image.height()));
+        assert_eq!(Some(Color::BLACK), image.get(1, 1));
+
+        let scaled = image.scaled(3);
+        assert_eq!((6, 6), (scaled.width(), scaled.height()));
+        assert_eq!(image.get(0, 1), scaled.get(2, 5));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            Ok(ImageFormat::Png),
+            ImageFormat::from_path(Path::new("out/eight.PNG"))
+        );
+        assert!(ImageFormat::from_path(Path::new("eight.gif")).is_err());
+    }
+}
@@ -1,3 +1,7 @@
+pub mod answer;
+pub mod answers;
+pub mod bench;
+pub mod cycle;
 pub mod eight;
 pub mod eighteen;
 pub mod eleven;
@@ -5,18 +9,236 @@ pub mod fifteen;
 pub mod five;
 pub mod four;
 pub mod fourteen;
+#[cfg(test)]
+mod golden;
+pub mod grid;
+pub mod image;
+pub mod logging;
 pub mod nine;
 pub mod nineteen;
 pub mod one;
+pub mod params;
+pub mod parse;
+pub mod progress;
+pub mod random;
+pub mod runner;
+pub mod scaffold;
+pub mod search;
 pub mod seven;
 pub mod seventeen;
 pub mod six;
 pub mod sixteen;
+pub mod solution;
 pub mod ten;
 pub mod thirteen;
 pub mod three;
 pub mod twelve;
+pub mod twenty;
+pub mod twentyfive;
+pub mod twentyfour;
 pub mod twentyone;
 pub mod twentythree;
 pub mod twentytwo;
 pub mod two;
+pub mod visualize;
+
+use std::error;
+use std::fmt;
+use std::io::{self, Read};
+
+pub use answer::Answer;
+pub use parse::ParseError;
+pub use solution::{Day, Part, Solution, SolveError};
+
+/// Why `solve` couldn't give an answer.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    UnknownDay(u32),
+    /// The input couldn't be read at all, as opposed to being read but not making sense.
+    Io(io::Error),
+    Parse(ParseError),
+    Solve(SolveError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownDay(day) => write!(f, "There is no solution for day {}", day),
+            Error::Io(e) => write!(f, "Could not read the input: {}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Solve(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::UnknownDay(_) => None,
+            Error::Io(e) => Some(e),
+            Error::Parse(e) => Some(e),
+            Error::Solve(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl From<SolveError> for Error {
+    fn from(e: SolveError) -> Error {
+        Error::Solve(e)
+    }
+}
+
+/// Solves one part of a day for the given puzzle input, with the parameters for the real input.
+///
+/// This is the way in for using the solutions as a library. For more control, such as choosing the
+/// parameters, get the day with `get_day`, or use a day's module directly: its solution implements
+/// `Solution`, which parses the input into the day's own type and solves each part from that.
+pub fn solve(day: u32, part: Part, input: &str) -> Result<Answer, Error> {
+    solve_reader(day, part, input.as_bytes())
+}
+
+/// Like `solve`, but reads the input, such as from a file.
+pub fn solve_reader(day: u32, part: Part, mut reader: impl Read) -> Result<Answer, Error> {
+    let day = get_day(day).ok_or(Error::UnknownDay(day))?;
+
+    day.run(part, &mut reader)
+}
+
+/// The day with the given number, if there is a solution for it.
+pub fn get_day(number: u32) -> Option<Day> {
+    get_days().into_iter().find(|d| d.number == number)
+}
+
+/// Builds the registry of days returned by `get_days` from each day's module and solution. The
+/// modules themselves are declared above, so that tools like rustfmt can find them.
+///
+/// Entries are kept in order of day, which is where `new-day` inserts new ones.
+macro_rules! days {
+    ($($number:literal => $name:ident::$solution:ident,)*) => {
+        pub fn get_days() -> Vec<Day> {
+            vec![
+                $(Day {
+                    number: $number,
+                    name: stringify!($name),
+                    solution: &$name::$solution,
+                },)*
+            ]
+        }
+    };
+}
+
+days! {
+    1 => one::DayOne,
+    2 => two::DayTwo,
+    3 => three::DayThree,
+    4 => four::DayFour,
+    5 => five::DayFive,
+    6 => six::DaySix,
+    7 => seven::DaySeven,
+    8 => eight::DayEight,
+    9 => nine::DayNine,
+    10 => ten::DayTen,
+    11 => eleven::DayEleven,
+    12 => twelve::DayTwelve,
+    13 => thirteen::DayThirteen,
+    14 => fourteen::DayFourteen,
+    15 => fifteen::DayFifteen,
+    16 => sixteen::DaySixteen,
+    17 => seventeen::DaySeventeen,
+    18 => eighteen::DayEighteen,
+    19 => nineteen::DayNineteen,
+    20 => twenty::DayTwenty,
+    21 => twentyone::DayTwentyOne,
+    22 => twentytwo::DayTwentyTwo,
+    23 => twentythree::DayTwentyThree,
+    24 => twentyfour::DayTwentyFour,
+    25 => twentyfive::DayTwentyFive,
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use std::io::BufReader;
+
+    use crate::twentyfive::DayTwentyFive;
+
+    const EXAMPLE: &str =
+        "1=-0-2\n12111\n2=0=\n21\n2=01\n111\n20012\n112\n1=-1=\n1-12\n12\n1=\n122\n";
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(
+            Answer::Text("2=-1=0".to_string()),
+            solve(25, Part::One, EXAMPLE).unwrap()
+        );
+        assert_eq!(
+            solve(25, Part::Two, EXAMPLE).unwrap(),
+            solve_reader(25, Part::Two, EXAMPLE.as_bytes()).unwrap()
+        );
+
+        assert!(matches!(
+            solve(26, Part::One, EXAMPLE),
+            Err(Error::UnknownDay(26))
+        ));
+        match solve(25, Part::One, "12\n3\n") {
+            Err(Error::Parse(e)) => assert_eq!((Some(25), Some(2)), (e.day, e.line)),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_solve_with_typed_input() {
+        let input = DayTwentyFive
+            .read_input(&mut BufReader::new(EXAMPLE.as_bytes()))
+            .unwrap();
+        let params = get_day(25).unwrap().default_params(false);
+
+        assert_eq!(13, input.len());
+        assert_eq!(
+            solve(25, Part::Two, EXAMPLE).unwrap(),
+            DayTwentyFive.part_two(&input, &params).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_solve_with_solver_functions() {
+        let input = one::DayOne
+            .read_input(&mut BufReader::new("1000\n2000\n\n4000\n".as_bytes()))
+            .unwrap();
+
+        assert_eq!(4000, one::find_max_calories(&input));
+        assert_eq!(7000, one::find_top_three_calories(&input));
+    }
+
+    /// A reader that fails, like a file that goes away part way through.
+    struct BrokenReader;
+
+    impl Read for BrokenReader {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("the disk went away"))
+        }
+    }
+
+    #[test]
+    fn test_solve_unreadable() {
+        match solve_reader(25, Part::One, BrokenReader) {
+            Err(Error::Io(e)) => assert_eq!("the disk went away", e.to_string()),
+            result => panic!("expected a read error, got {:?}", result),
+        }
+    }
+}
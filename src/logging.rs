@@ -0,0 +1,125 @@
+use std::io::{self, Write};
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Writes log messages to stderr, so that they don't get mixed in with the answers.
+pub struct StderrLogger {
+    level: LevelFilter,
+    /// The module names of the days to log messages from, or empty for every day.
+    days: Vec<String>,
+    /// The module names of every day, to tell them apart from modules that the days share, such
+    /// as `cycle` and `search`, which always log.
+    day_modules: Vec<&'static str>,
+}
+
+impl StderrLogger {
+    /// A verbosity of 0 only logs warnings, 1 adds info, 2 adds debug and 3 or more adds trace.
+    pub fn new(verbosity: u64, days: Vec<String>) -> StderrLogger {
+        let level = match verbosity {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        };
+
+        StderrLogger {
+            level,
+            days,
+            day_modules: crate::get_days().iter().map(|d| d.name).collect(),
+        }
+    }
+
+    /// Installs the logger for the rest of the program.
+    pub fn init(self) -> Result<(), String> {
+        log::set_max_level(self.level);
+        log::set_boxed_logger(Box::new(self))
+            .map_err(|e| format!("Could not set up logging: {}", e))
+    }
+}
+
+/// The module within this crate that a log message came from, e.g. "sixteen".
+fn module_of(target: &str) -> &str {
+    let path = target
+        .strip_prefix(concat!(env!("CARGO_CRATE_NAME"), "::"))
+        .unwrap_or(target);
+
+    path.split("::").next().unwrap_or(path)
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        let module = module_of(metadata.target());
+
+        metadata.level() <= self.level
+            && (self.days.is_empty()
+                || self.days.iter().any(|d| d == module)
+                || !self.day_modules.contains(&module))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "[{} {}] {}",
+                record.level(),
+                module_of(record.target()),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {
+        let _ = io::stderr().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use log::Level;
+
+    fn is_enabled(logger: &StderrLogger, level: Level, target: &str) -> bool {
+        logger.enabled(&Metadata::builder().level(level).target(target).build())
+    }
+
+    #[test]
+    fn test_module_of() {
+        assert_eq!("sixteen", module_of("advent_of_code_2022::sixteen"));
+        assert_eq!("grid", module_of("advent_of_code_2022::grid::tests"));
+        assert_eq!("other", module_of("other"));
+    }
+
+    #[test]
+    fn test_enabled() {
+        let logger = StderrLogger::new(2, vec![]);
+        assert!(is_enabled(&logger, Level::Warn, "advent_of_code_2022::one"));
+        assert!(is_enabled(
+            &logger,
+            Level::Debug,
+            "advent_of_code_2022::one"
+        ));
+        assert!(!is_enabled(
+            &logger,
+            Level::Trace,
+            "advent_of_code_2022::one"
+        ));
+
+        let logger = StderrLogger::new(3, vec!["sixteen".to_string()]);
+        assert!(is_enabled(
+            &logger,
+            Level::Trace,
+            "advent_of_code_2022::sixteen"
+        ));
+        assert!(!is_enabled(
+            &logger,
+            Level::Warn,
+            "advent_of_code_2022::seventeen"
+        ));
+        assert!(is_enabled(
+            &logger,
+            Level::Trace,
+            "advent_of_code_2022::search"
+        ));
+    }
+}
@@ -1,152 +1,1130 @@
 extern crate clap;
-use clap::{App, Arg, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use std::fs::File;
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
 
 extern crate advent_of_code_2022;
 
+use advent_of_code_2022::answers::{Check, ExpectedAnswers, ANSWERS_PATH};
+use advent_of_code_2022::bench::{bench, is_regression, Baseline};
+use advent_of_code_2022::get_days;
+use advent_of_code_2022::image::ImageFormat;
+use advent_of_code_2022::logging::StderrLogger;
+use advent_of_code_2022::params::Params;
+use advent_of_code_2022::progress::ProgressBar;
+use advent_of_code_2022::runner::{
+    format_duration, input_name, is_example, run_file, run_file_with_params, run_reader, RunError,
+    RunResult,
+};
+use advent_of_code_2022::scaffold::new_day;
+use advent_of_code_2022::search::Limits;
+use advent_of_code_2022::solution::{Day, Part};
+use advent_of_code_2022::visualize::{every_nth, export_frames, Player};
+use advent_of_code_2022::Error;
+
 fn main() {
-    let a = App::new("advent_of_code_2022").author("Christopher Wells <cwellsny@gmail.com>");
+    let a = App::new("advent_of_code_2022")
+        .author("Christopher Wells <cwellsny@gmail.com>")
+        .arg(
+            Arg::with_name("format")
+                .long("format")
+                .global(true)
+                .takes_value(true)
+                .possible_values(&["text", "json"])
+                .default_value("text")
+                .help("Prints results as human readable text or as JSON (one object per line)"),
+        )
+        .arg(
+            Arg::with_name("verbose")
+                .long("verbose")
+                .short("v")
+                .global(true)
+                .multiple(true)
+                .help("Logs what the solutions are doing to stderr, more for each -v (up to -vvv)"),
+        )
+        .arg(
+            Arg::with_name("log-day")
+                .long("log-day")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name("DAY")
+                .help(
+                    "Only logs messages from the given day (e.g. day16) and the code it shares \
+                     with other days, can be repeated",
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("all")
+                .about("Runs both parts of every day against its inputs file"),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Re-runs the solutions for every known answer in answers.txt and reports any differences")
+                .arg(
+                    Arg::with_name("day")
+                        .long("day")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Only verifies the answers for day N"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("bench")
+                .about("Benchmarks parsing and solving a day, comparing against a saved baseline")
+                .arg(
+                    Arg::with_name("day")
+                        .help("Selects the day to benchmark (e.g. day16)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("part")
+                        .help("Selects the part to benchmark (one, two), by default both")
+                        .index(2),
+                )
+                .args(&input_args())
+                .arg(param_arg())
+                .arg(
+                    Arg::with_name("iterations")
+                        .long("iterations")
+                        .short("n")
+                        .takes_value(true)
+                        .default_value("10")
+                        .help("Number of timed runs"),
+                )
+                .arg(
+                    Arg::with_name("warmup")
+                        .long("warmup")
+                        .takes_value(true)
+                        .default_value("2")
+                        .help("Number of untimed runs before the timed ones"),
+                )
+                .arg(
+                    Arg::with_name("baseline")
+                        .long("baseline")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .default_value("bench_baseline.txt")
+                        .help("File of earlier benchmark results to compare against"),
+                )
+                .arg(
+                    Arg::with_name("save-baseline")
+                        .long("save-baseline")
+                        .help("Records the results of this run in the baseline file"),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .takes_value(true)
+                        .value_name("PERCENT")
+                        .default_value("10")
+                        .help("How much slower than the baseline a median can be before it is flagged"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("visualize")
+                .about("Shows the simulation behind a day as an animation in the terminal")
+                .arg(
+                    Arg::with_name("day")
+                        .help("Selects the day to visualize (e.g. day14)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("part")
+                        .help("Selects the part to visualize (one, two)")
+                        .default_value("one")
+                        .index(2),
+                )
+                .args(&input_args())
+                .arg(param_arg())
+                .arg(
+                    Arg::with_name("fps")
+                        .long("fps")
+                        .takes_value(true)
+                        .default_value("10")
+                        .help("Frames shown per second"),
+                )
+                .arg(
+                    Arg::with_name("step")
+                        .long("step")
+                        .help("Waits for enter to be pressed before showing the next frame"),
+                )
+                .arg(
+                    Arg::with_name("every")
+                        .long("every")
+                        .takes_value(true)
+                        .value_name("N")
+                        .default_value("1")
+                        .help("Only shows every Nth frame, plus the last one"),
+                )
+                .arg(
+                    Arg::with_name("export")
+                        .long("export")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .help("Saves the frames as images in DIR instead of showing them"),
+                )
+                .arg(
+                    Arg::with_name("image-format")
+                        .long("image-format")
+                        .takes_value(true)
+                        .possible_values(&["png", "ppm", "svg"])
+                        .default_value("png")
+                        .help("Format of the images saved by --export"),
+                )
+                .arg(
+                    Arg::with_name("scale")
+                        .long("scale")
+                        .takes_value(true)
+                        .value_name("N")
+                        .default_value("4")
+                        .help("Draws each tile as an N by N square of pixels"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Saves a picture of the final state of a day as a .png, .ppm or .svg image")
+                .arg(
+                    Arg::with_name("day")
+                        .help("Selects the day to draw (e.g. day8)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("part")
+                        .help("Selects the part to draw (one, two)")
+                        .default_value("one")
+                        .index(2),
+                )
+                .args(&input_args())
+                .arg(param_arg())
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .required(true)
+                        .help("The image to write, in the format matching its extension"),
+                )
+                .arg(
+                    Arg::with_name("scale")
+                        .long("scale")
+                        .takes_value(true)
+                        .value_name("N")
+                        .default_value("4")
+                        .help("Draws each tile as an N by N square of pixels"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("generate")
+                .about("Prints a random input for a day, for stress testing")
+                .arg(
+                    Arg::with_name("day")
+                        .help("Selects the day to generate an input for (e.g. day7)")
+                        .required(true)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name("seed")
+                        .long("seed")
+                        .takes_value(true)
+                        .value_name("N")
+                        .default_value("0")
+                        .help("Seeds the random number generator, the same seed gives the same input"),
+                )
+                .arg(
+                    Arg::with_name("size")
+                        .long("size")
+                        .takes_value(true)
+                        .value_name("N")
+                        .default_value("10")
+                        .help("How big an input to generate, usually its number of lines or items"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("new-day")
+                .about("Writes the module and example input for a new day and registers it")
+                .arg(
+                    Arg::with_name("day")
+                        .help("Selects the day to create (e.g. day24)")
+                        .required(true)
+                        .index(1),
+                ),
+        );
 
     let days = get_days();
-    let app = days.iter().map(|d| d.0.clone()).fold(a, |b, day| {
-        b.subcommand(
-            SubCommand::with_name(&format!("day{}", day)).arg(
-                Arg::with_name("part")
-                    .help("Selects the part to run (one, two)")
-                    .required(true)
-                    .index(1),
-            ),
-        )
-    });
+    let params_help: Vec<String> = days.iter().map(describe_params).collect();
+    let app = days
+        .iter()
+        .zip(params_help.iter())
+        .fold(a, |b, (day, params_help)| {
+            b.subcommand(
+                SubCommand::with_name(&format!("day{}", day.number))
+                    .arg(
+                        Arg::with_name("part")
+                            .help("Selects the part to run (one, two)")
+                            .required(true)
+                            .index(1),
+                    )
+                    .args(&input_args())
+                    .arg(param_arg())
+                    .args(&limit_args())
+                    .after_help(params_help.as_str()),
+            )
+        });
     let matches = app.get_matches();
+    init_logging(&days, matches.subcommand().1.unwrap_or(&matches));
 
-    for (day, part_one, part_two) in days {
-        let day_string = format!("day{}", day);
+    if let Some(matches) = matches.subcommand_matches("all") {
+        run_all(&days, output_format(matches));
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("verify") {
+        let day_filter = matches.value_of("day").map(|d| match d.parse::<u32>() {
+            Ok(d) => d,
+            Err(_) => {
+                eprintln!("Invalid day: {}", d);
+                process::exit(1);
+            }
+        });
+
+        if !verify(&days, day_filter, output_format(matches)) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("bench") {
+        if !run_bench(&days, matches) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("visualize") {
+        run_visualize(&days, matches);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("export") {
+        run_export(&days, matches);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("generate") {
+        run_generate(&days, matches);
+        return;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("new-day") {
+        let format = output_format(matches);
+        let name = matches.value_of("day").unwrap();
+        let number = match name.trim_start_matches("day").parse::<u32>() {
+            Ok(number) => number,
+            Err(_) => exit_with_error(&format!("Invalid day: {}", name), format),
+        };
+
+        match new_day(Path::new("."), number) {
+            Ok(paths) => print_written(&paths, format),
+            Err(message) => exit_with_error(&message, format),
+        }
+        return;
+    }
+
+    for day in days {
+        let day_string = format!("day{}", day.number);
 
         if let Some(matches) = matches.subcommand_matches(day_string) {
-            let part = matches.value_of("part").unwrap();
+            let format = output_format(matches);
+            let part = match matches.value_of("part").unwrap().parse::<Part>() {
+                Ok(part) => part,
+                Err(message) => exit_with_error(&message, format),
+            };
+
+            let path = input_path(&day, matches);
+            let params = match params_for(&day, matches, path.as_deref()) {
+                Ok(params) => params,
+                Err(message) => exit_with_error(&message, format),
+            };
+            let limits = match search_limits(matches) {
+                Ok(limits) => limits,
+                Err(message) => exit_with_error(&message, format),
+            };
 
-            match part {
-                "one" => part_one(),
-                "two" => part_two(),
-                p => println!("Unknown part: {}", p),
+            let progress = show_progress(matches).then(|| {
+                let label = format!("day {} part {}", day.number, part.name());
+                Arc::new(ProgressBar::new(&label, limits.deadline))
+            });
+            let limits = match &progress {
+                Some(bar) => {
+                    let bar = bar.clone();
+                    limits.with_progress(move |p| bar.update(p))
+                }
+                None => limits,
+            };
+            cancel_on_ctrl_c(&limits);
+            let params = params.with_limits(limits);
+
+            let result = match path {
+                Some(path) => run_file_with_params(&day, part, &path, &params),
+                None => run_reader(&day, part, Path::new("-"), &mut io::stdin(), &params),
+            };
+            if let Some(bar) = progress {
+                bar.finish();
+            }
+
+            match (format, &result.outcome) {
+                (Format::Json, _) => {
+                    let mut json = result.to_json();
+                    json["params"] = params.iter().collect();
+                    println!("{}", json)
+                }
+                (Format::Text, Ok(answer)) => {
+                    println!("{}", answer);
+                    if !answer.is_complete() {
+                        eprintln!(
+                            "The search was stopped early, so this is the best answer found so far"
+                        );
+                    }
+                }
+                (Format::Text, Err(RunError::MissingInput(e))) => {
+                    eprintln!(
+                        "Could not open input file {}: {}",
+                        result.input.display(),
+                        e
+                    )
+                }
+                (Format::Text, Err(RunError::Parse(e))) => {
+                    eprintln!("Could not parse {}: {}", result.input.display(), e)
+                }
+                (Format::Text, Err(RunError::Solve(e))) => {
+                    eprintln!("Could not solve {}: {}", result.input.display(), e)
+                }
+            }
+
+            if result.outcome.is_err() {
+                process::exit(1);
             }
         }
     }
 }
 
-type AdventOfCodeDay = (String, fn(), fn());
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
 
-fn get_days() -> Vec<AdventOfCodeDay> {
-    vec![
-        (
-            "1".to_string(),
-            advent_of_code_2022::one::part_one as fn(),
-            advent_of_code_2022::one::part_two as fn(),
-        ),
-        (
-            "2".to_string(),
-            advent_of_code_2022::two::part_one as fn(),
-            advent_of_code_2022::two::part_two as fn(),
-        ),
-        (
-            "3".to_string(),
-            advent_of_code_2022::three::part_one as fn(),
-            advent_of_code_2022::three::part_two as fn(),
-        ),
-        (
-            "4".to_string(),
-            advent_of_code_2022::four::part_one as fn(),
-            advent_of_code_2022::four::part_two as fn(),
-        ),
-        (
-            "5".to_string(),
-            advent_of_code_2022::five::part_one as fn(),
-            advent_of_code_2022::five::part_two as fn(),
-        ),
-        (
-            "6".to_string(),
-            advent_of_code_2022::six::part_one as fn(),
-            advent_of_code_2022::six::part_two as fn(),
-        ),
-        (
-            "7".to_string(),
-            advent_of_code_2022::seven::part_one as fn(),
-            advent_of_code_2022::seven::part_two as fn(),
-        ),
-        (
-            "8".to_string(),
-            advent_of_code_2022::eight::part_one as fn(),
-            advent_of_code_2022::eight::part_two as fn(),
-        ),
-        (
-            "9".to_string(),
-            advent_of_code_2022::nine::part_one as fn(),
-            advent_of_code_2022::nine::part_two as fn(),
-        ),
-        (
-            "10".to_string(),
-            advent_of_code_2022::ten::part_one as fn(),
-            advent_of_code_2022::ten::part_two as fn(),
-        ),
-        (
-            "11".to_string(),
-            advent_of_code_2022::eleven::part_one as fn(),
-            advent_of_code_2022::eleven::part_two as fn(),
-        ),
-        (
-            "12".to_string(),
-            advent_of_code_2022::twelve::part_one as fn(),
-            advent_of_code_2022::twelve::part_two as fn(),
-        ),
-        (
-            "13".to_string(),
-            advent_of_code_2022::thirteen::part_one as fn(),
-            advent_of_code_2022::thirteen::part_two as fn(),
-        ),
-        (
-            "14".to_string(),
-            advent_of_code_2022::fourteen::part_one as fn(),
-            advent_of_code_2022::fourteen::part_two as fn(),
-        ),
-        (
-            "15".to_string(),
-            advent_of_code_2022::fifteen::part_one as fn(),
-            advent_of_code_2022::fifteen::part_two as fn(),
-        ),
-        (
-            "16".to_string(),
-            advent_of_code_2022::sixteen::part_one as fn(),
-            advent_of_code_2022::sixteen::part_two as fn(),
-        ),
-        (
-            "17".to_string(),
-            advent_of_code_2022::seventeen::part_one as fn(),
-            advent_of_code_2022::seventeen::part_two as fn(),
-        ),
-        (
-            "18".to_string(),
-            advent_of_code_2022::eighteen::part_one as fn(),
-            advent_of_code_2022::eighteen::part_two as fn(),
+fn output_format(matches: &ArgMatches) -> Format {
+    match matches.value_of("format") {
+        Some("json") => Format::Json,
+        _ => Format::Text,
+    }
+}
+
+fn exit_with_error(message: &str, format: Format) -> ! {
+    match format {
+        Format::Json => println!("{}", json!({ "error": message })),
+        Format::Text => eprintln!("{}", message),
+    }
+    process::exit(1);
+}
+
+fn input_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("input")
+            .long("input")
+            .takes_value(true)
+            .value_name("FILE")
+            .help("Reads the puzzle input from FILE (\"-\" for stdin) instead of inputs/<day>.txt"),
+        Arg::with_name("example")
+            .long("example")
+            .conflicts_with("input")
+            .help("Reads the puzzle input from inputs/<day>_example.txt"),
+    ]
+}
+
+fn param_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("param")
+        .long("param")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("NAME=VALUE")
+        .help("Overrides one of the day's parameters, see below")
+}
+
+fn limit_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("timeout")
+            .long("timeout")
+            .takes_value(true)
+            .value_name("SECONDS")
+            .help("Stops searching after SECONDS and prints the best answer found so far, marked as incomplete"),
+        Arg::with_name("max-expanded")
+            .long("max-expanded")
+            .takes_value(true)
+            .value_name("N")
+            .help("Stops each search after expanding N states, like --timeout"),
+    ]
+}
+
+/// The limits selected by `--timeout` and `--max-expanded`, for the days that search for their
+/// answers.
+fn search_limits(matches: &ArgMatches) -> Result<Limits, String> {
+    let mut limits = Limits::default();
+
+    if let Some(timeout) = matches.value_of("timeout") {
+        let timeout = timeout
+            .parse::<f64>()
+            .ok()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| {
+                format!(
+                    "Invalid timeout: expected a number of seconds, found \"{}\"",
+                    timeout
+                )
+            })?;
+        limits = limits.with_timeout(timeout);
+    }
+
+    if let Some(max_expanded) = matches.value_of("max-expanded") {
+        let max_expanded = max_expanded.parse::<usize>().map_err(|_| {
+            format!(
+                "Invalid --max-expanded: expected a number of states, found \"{}\"",
+                max_expanded
+            )
+        })?;
+        limits = limits.with_max_expanded(max_expanded);
+    }
+
+    Ok(limits)
+}
+
+/// Progress is only drawn for people watching a terminal, and not over the top of log messages.
+fn show_progress(matches: &ArgMatches) -> bool {
+    io::stderr().is_terminal() && matches.occurrences_of("verbose") == 0
+}
+
+/// Stops the searches on the first Ctrl-C, so that the best answer found so far is printed, and
+/// quits on the second.
+fn cancel_on_ctrl_c(limits: &Limits) {
+    let limits = limits.clone();
+    let result = ctrlc::set_handler(move || {
+        if limits.is_cancelled() {
+            process::exit(130);
+        }
+
+        eprintln!("\nStopping, press Ctrl-C again to quit");
+        limits.cancel();
+    });
+
+    if let Err(e) = result {
+        eprintln!("Could not handle Ctrl-C: {}", e);
+    }
+}
+
+fn describe_params(day: &Day) -> String {
+    let params = day.solution.params();
+    if params.is_empty() {
+        return "This day has no parameters.".to_string();
+    }
+
+    let mut description = "PARAMETERS:".to_string();
+    for param in params {
+        let example = match &param.example_default {
+            Some(value) => format!(", {} for examples", value),
+            None => String::new(),
+        };
+
+        description.push_str(&format!(
+            "\n    {}={}{}\n            {} ({})",
+            param.name,
+            param.default,
+            example,
+            param.description,
+            param.expected()
+        ));
+    }
+
+    description
+}
+
+/// The parameters selected by `--param` overrides on top of the defaults for the input, which
+/// differ for the examples on some days.
+fn params_for(day: &Day, matches: &ArgMatches, path: Option<&Path>) -> Result<Params, String> {
+    let mut params = day.default_params(path.map(is_example).unwrap_or(false));
+    params.set_all(matches.values_of("param").into_iter().flatten())?;
+
+    Ok(params)
+}
+
+/// The input file selected by the `--input` and `--example` arguments, or `None` for stdin.
+fn input_path(day: &Day, matches: &ArgMatches) -> Option<PathBuf> {
+    match matches.value_of("input") {
+        Some("-") => None,
+        Some(path) => Some(PathBuf::from(path)),
+        None if matches.is_present("example") => Some(day.example_path()),
+        None => Some(day.input_path()),
+    }
+}
+
+fn open_input(day: &Day, matches: &ArgMatches) -> Box<dyn Read> {
+    let path = match input_path(day, matches) {
+        Some(path) => path,
+        None => return Box::new(io::stdin()),
+    };
+
+    match File::open(&path) {
+        Ok(f) => Box::new(f),
+        Err(e) => {
+            eprintln!("Could not open input file {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_all(days: &[Day], format: Format) {
+    let expected = match ExpectedAnswers::load(Path::new(ANSWERS_PATH)) {
+        Ok(expected) => expected,
+        Err(message) => {
+            eprintln!("{}", message);
+            ExpectedAnswers::default()
+        }
+    };
+
+    if format == Format::Text {
+        println!(
+            "{:>3}  {:<4}  {:<40}  {:>9}  Check",
+            "Day", "Part", "Answer", "Time"
+        );
+    }
+
+    let (mut passed, mut failed, mut unknown, mut missing) = (0, 0, 0, 0);
+    for day in days.iter() {
+        for part in Part::all().iter() {
+            let result = run_file(day, *part, &day.input_path());
+            let check = result
+                .outcome
+                .as_ref()
+                .ok()
+                .map(|answer| expected.check(day.number, *part, &result.input_name(), answer));
+
+            let failed_to_run =
+                matches!(result.outcome, Err(RunError::Parse(_) | RunError::Solve(_)));
+
+            match &check {
+                Some(Check::Pass) => passed += 1,
+                Some(Check::Fail(_)) => failed += 1,
+                Some(Check::Unknown) => unknown += 1,
+                None if failed_to_run => failed += 1,
+                None => missing += 1,
+            }
+
+            if format == Format::Json {
+                let mut json = result.to_json();
+                match check {
+                    Some(Check::Pass) => json["check"] = json!("pass"),
+                    Some(Check::Fail(expected_answer)) => {
+                        json["check"] = json!("fail");
+                        json["expected"] = json!(expected_answer);
+                    }
+                    Some(Check::Unknown) => json["check"] = json!("unknown"),
+                    None if failed_to_run => json["check"] = json!("fail"),
+                    None => {}
+                }
+                println!("{}", json);
+                continue;
+            }
+
+            let check = match check {
+                Some(Check::Pass) => "pass".to_string(),
+                Some(Check::Fail(expected_answer)) if expected_answer.contains('\n') => {
+                    "FAIL".to_string()
+                }
+                Some(Check::Fail(expected_answer)) => {
+                    format!("FAIL (expected {})", expected_answer)
+                }
+                Some(Check::Unknown) => "?".to_string(),
+                None if failed_to_run => "FAIL".to_string(),
+                None => "-".to_string(),
+            };
+
+            print_result_row(&result, &check);
+        }
+    }
+
+    if format == Format::Json {
+        return;
+    }
+
+    println!();
+    println!(
+        "{} passed, {} failed, {} unknown, {} missing input",
+        passed, failed, unknown, missing
+    );
+}
+
+fn print_result_row(result: &RunResult, check: &str) {
+    let (answer, time) = match &result.outcome {
+        Ok(answer) => (answer.to_string(), format_duration(result.elapsed)),
+        Err(RunError::MissingInput(_)) => ("(missing input)".to_string(), "-".to_string()),
+        // The diagnostic goes on the next line, as it is usually too long for the answer column
+        Err(RunError::Parse(e)) => (
+            format!("(invalid input)\n{}", e),
+            format_duration(result.elapsed),
         ),
-        (
-            "19".to_string(),
-            advent_of_code_2022::nineteen::part_one as fn(),
-            advent_of_code_2022::nineteen::part_two as fn(),
+        Err(RunError::Solve(e)) => (
+            format!("(no answer)\n{}", e),
+            format_duration(result.elapsed),
         ),
-        (
-            "21".to_string(),
-            advent_of_code_2022::twentyone::part_one as fn(),
-            advent_of_code_2022::twentyone::part_two as fn(),
+    };
+    let mut lines = answer.lines();
+
+    println!(
+        "{:>3}  {:<4}  {:<40}  {:>9}  {}",
+        result.day,
+        result.part.name(),
+        lines.next().unwrap_or(""),
+        time,
+        check
+    );
+
+    // Multi-line answers (images) continue underneath the answer column
+    for line in lines {
+        println!("{:>3}  {:<4}  {}", "", "", line);
+    }
+}
+
+/// Returns whether all of the answers that could be checked matched.
+fn verify(days: &[Day], day_filter: Option<u32>, format: Format) -> bool {
+    let expected = match ExpectedAnswers::load(Path::new(ANSWERS_PATH)) {
+        Ok(expected) => expected,
+        Err(message) => {
+            eprintln!("{}", message);
+            return false;
+        }
+    };
+
+    let (mut matched, mut mismatched, mut skipped) = (0, 0, 0);
+    for (day_number, part, input, expected_answer) in expected.iter() {
+        if day_filter.is_some() && day_filter != Some(day_number) {
+            continue;
+        }
+
+        let label = format!("day {} part {} ({})", day_number, part.name(), input);
+
+        let day = match days.iter().find(|d| d.number == day_number) {
+            Some(day) => day,
+            None => {
+                match format {
+                    Format::Json => println!(
+                        "{}",
+                        json!({
+                            "day": day_number,
+                            "part": part.name(),
+                            "input": input,
+                            "status": "mismatch",
+                            "expected": expected_answer,
+                            "error": format!("no solution for day {}", day_number),
+                        })
+                    ),
+                    Format::Text => {
+                        println!("{}: MISMATCH, no solution for day {}", label, day_number)
+                    }
+                }
+                mismatched += 1;
+                continue;
+            }
+        };
+
+        let result = run_file(day, part, &PathBuf::from(format!("inputs/{}.txt", input)));
+        let status = match &result.outcome {
+            Ok(answer) if answer.to_string() == expected_answer => {
+                matched += 1;
+                "ok"
+            }
+            Ok(_) => {
+                mismatched += 1;
+                "mismatch"
+            }
+            Err(RunError::MissingInput(_)) => {
+                skipped += 1;
+                "skipped"
+            }
+            Err(RunError::Parse(_) | RunError::Solve(_)) => {
+                mismatched += 1;
+                "mismatch"
+            }
+        };
+
+        if format == Format::Json {
+            let mut json = result.to_json();
+            json["status"] = json!(status);
+            json["expected"] = json!(expected_answer);
+            println!("{}", json);
+            continue;
+        }
+
+        match &result.outcome {
+            Ok(_) if status == "ok" => println!("{}: ok", label),
+            Ok(answer) => {
+                println!("{}: MISMATCH", label);
+                print_labelled("expected", expected_answer);
+                print_labelled("actual", &answer.to_string());
+            }
+            Err(RunError::MissingInput(_)) => {
+                println!("{}: skipped, missing {}", label, result.input.display())
+            }
+            Err(RunError::Parse(e)) => println!("{}: MISMATCH, {}", label, e),
+            Err(RunError::Solve(e)) => println!("{}: MISMATCH, {}", label, e),
+        }
+    }
+
+    if format == Format::Json {
+        return mismatched == 0;
+    }
+
+    println!();
+    println!(
+        "{} matched, {} mismatched, {} skipped",
+        matched, mismatched, skipped
+    );
+
+    mismatched == 0
+}
+
+fn print_labelled(label: &str, value: &str) {
+    for (i, line) in value.lines().enumerate() {
+        let label = if i == 0 { label } else { "" };
+        println!("    {:<9} {}", label, line);
+    }
+}
+
+fn init_logging(days: &[Day], matches: &ArgMatches) {
+    let log_days = matches
+        .values_of("log-day")
+        .map(|names| names.map(|name| find_day(days, name).name.to_string()))
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let logger = StderrLogger::new(matches.occurrences_of("verbose"), log_days);
+    if let Err(message) = logger.init() {
+        exit_with_error(&message, output_format(matches));
+    }
+}
+
+fn find_day<'a>(days: &'a [Day], name: &str) -> &'a Day {
+    let number = name.trim_start_matches("day").parse::<u32>().ok();
+
+    match days.iter().find(|d| Some(d.number) == number) {
+        Some(day) => day,
+        None => {
+            eprintln!("Unknown day: {}", name);
+            process::exit(1);
+        }
+    }
+}
+
+fn parse_arg<T: std::str::FromStr>(matches: &ArgMatches, name: &str) -> T {
+    let value = matches.value_of(name).unwrap();
+
+    match value.parse() {
+        Ok(value) => value,
+        Err(_) => {
+            eprintln!("Invalid value for --{}: {}", name, value);
+            process::exit(1);
+        }
+    }
+}
+
+fn run_visualize(days: &[Day], matches: &ArgMatches) {
+    let format = output_format(matches);
+    let day = find_day(days, matches.value_of("day").unwrap());
+    let part = match matches.value_of("part").unwrap().parse::<Part>() {
+        Ok(part) => part,
+        Err(message) => exit_with_error(&message, format),
+    };
+    let fps: f64 = parse_arg(matches, "fps");
+    if fps <= 0.0 {
+        exit_with_error("--fps must be more than 0", format);
+    }
+
+    let path = input_path(day, matches);
+    let params = match params_for(day, matches, path.as_deref()) {
+        Ok(params) => params,
+        Err(message) => exit_with_error(&message, format),
+    };
+
+    let frames = match day.frames(part, &mut open_input(day, matches), &params) {
+        Ok(Some(frames)) => frames,
+        Ok(None) => exit_with_error(
+            &format!(
+                "Day {} part {} has no visualization",
+                day.number,
+                part.name()
+            ),
+            format,
         ),
-        (
-            "22".to_string(),
-            advent_of_code_2022::twentytwo::part_one as fn(),
-            advent_of_code_2022::twentytwo::part_two as fn(),
+        Err(Error::Io(e)) => exit_with_error(&format!("Could not read input: {}", e), format),
+        Err(Error::Parse(e)) => exit_with_error(&format!("Could not parse input: {}", e), format),
+        Err(e) => exit_with_error(&format!("Could not solve: {}", e), format),
+    };
+
+    if let Some(dir) = matches.value_of("export") {
+        let image_format = ImageFormat::from_extension(matches.value_of("image-format").unwrap())
+            .expect("clap only allows known formats");
+        let frames = every_nth(frames, parse_arg(matches, "every"));
+
+        match export_frames(
+            frames,
+            Path::new(dir),
+            image_format,
+            parse_arg(matches, "scale"),
+        ) {
+            Ok(paths) => print_written(&paths, format),
+            Err(message) => exit_with_error(&message, format),
+        }
+        return;
+    }
+
+    if format == Format::Json {
+        for (i, frame) in frames.enumerate() {
+            println!(
+                "{}",
+                json!({ "frame": i, "caption": frame.caption, "picture": frame.picture })
+            );
+        }
+        return;
+    }
+
+    let player = Player {
+        delay: if matches.is_present("step") {
+            None
+        } else {
+            Some(Duration::from_secs_f64(1.0 / fps))
+        },
+        every: parse_arg(matches, "every"),
+    };
+    if let Err(e) = player.play(frames, &mut io::stdout(), &mut io::stdin().lock()) {
+        exit_with_error(&format!("Could not show the frames: {}", e), format);
+    }
+}
+
+fn run_export(days: &[Day], matches: &ArgMatches) {
+    let format = output_format(matches);
+    let day = find_day(days, matches.value_of("day").unwrap());
+    let part = match matches.value_of("part").unwrap().parse::<Part>() {
+        Ok(part) => part,
+        Err(message) => exit_with_error(&message, format),
+    };
+    let output = PathBuf::from(matches.value_of("output").unwrap());
+    if let Err(message) = ImageFormat::from_path(&output) {
+        exit_with_error(&message, format);
+    }
+
+    let path = input_path(day, matches);
+    let params = match params_for(day, matches, path.as_deref()) {
+        Ok(params) => params,
+        Err(message) => exit_with_error(&message, format),
+    };
+
+    let image = match day.image(part, &mut open_input(day, matches), &params) {
+        Ok(Some(image)) => image,
+        Ok(None) => exit_with_error(
+            &format!("Day {} part {} has no image", day.number, part.name()),
+            format,
         ),
-        (
-            "23".to_string(),
-            advent_of_code_2022::twentythree::part_one as fn(),
-            advent_of_code_2022::twentythree::part_two as fn(),
+        Err(Error::Io(e)) => exit_with_error(&format!("Could not read input: {}", e), format),
+        Err(Error::Parse(e)) => exit_with_error(&format!("Could not parse input: {}", e), format),
+        Err(e) => exit_with_error(&format!("Could not solve: {}", e), format),
+    };
+
+    match image.scaled(parse_arg(matches, "scale")).save(&output) {
+        Ok(()) => print_written(&[output], format),
+        Err(message) => exit_with_error(&message, format),
+    }
+}
+
+fn run_generate(days: &[Day], matches: &ArgMatches) {
+    let format = output_format(matches);
+    let day = find_day(days, matches.value_of("day").unwrap());
+    let seed: u64 = parse_arg(matches, "seed");
+    let size: usize = parse_arg(matches, "size");
+
+    let input = match day.generate(seed, size) {
+        Some(input) => input,
+        None => exit_with_error(&format!("Day {} has no generator", day.number), format),
+    };
+
+    match format {
+        Format::Text => print!("{}", input),
+        Format::Json => println!(
+            "{}",
+            json!({ "day": day.number, "seed": seed, "size": size, "input": input })
         ),
-    ]
+    }
+}
+
+fn print_written(paths: &[PathBuf], format: Format) {
+    for path in paths {
+        match format {
+            Format::Text => println!("Wrote {}", path.display()),
+            Format::Json => println!("{}", json!({ "wrote": path })),
+        }
+    }
+}
+
+/// Returns whether every benchmark was within the threshold of its baseline.
+fn run_bench(days: &[Day], matches: &ArgMatches) -> bool {
+    let format = output_format(matches);
+    let day = find_day(days, matches.value_of("day").unwrap());
+    let parts = match matches.value_of("part").map(|p| p.parse::<Part>()) {
+        None => Part::all().to_vec(),
+        Some(Ok(part)) => vec![part],
+        Some(Err(message)) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+    let iterations: usize = parse_arg(matches, "iterations");
+    let warmup: usize = parse_arg(matches, "warmup");
+    let threshold: f64 = parse_arg::<f64>(matches, "threshold") / 100.0;
+
+    let path = input_path(day, matches);
+    let params = match params_for(day, matches, path.as_deref()) {
+        Ok(params) => params,
+        Err(message) => exit_with_error(&message, format),
+    };
+
+    let (input_name, mut input) = match &path {
+        Some(path) => (input_name(path), open_input(day, matches)),
+        None => ("stdin".to_string(), open_input(day, matches)),
+    };
+    let mut contents = String::new();
+    if let Err(e) = input.read_to_string(&mut contents) {
+        eprintln!("Could not read input: {}", e);
+        process::exit(1);
+    }
+
+    let baseline_path = Path::new(matches.value_of("baseline").unwrap());
+    let mut baseline = match Baseline::load(baseline_path) {
+        Ok(baseline) => baseline,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+    };
+
+    let mut all_within_threshold = true;
+    for part in parts {
+        let result = match bench(day, part, &contents, &params, warmup, iterations) {
+            Ok(result) => result,
+            Err(e) => exit_with_error(&e.to_string(), format),
+        };
+        let previous = baseline.get(day.number, part, &input_name);
+
+        if format == Format::Json {
+            let mut json = json!({
+                "day": day.number,
+                "part": part.name(),
+                "input": input_name,
+                "iterations": iterations,
+                "warmup": warmup,
+                "parse": result.parse.to_json(),
+                "solve": result.solve.to_json(),
+            });
+            if let Some((parse, solve)) = previous {
+                json["parse"]["baseline_median_ns"] = json!(parse.as_nanos() as u64);
+                json["solve"]["baseline_median_ns"] = json!(solve.as_nanos() as u64);
+                json["parse"]["regression"] =
+                    json!(is_regression(parse, result.parse.median, threshold));
+                json["solve"]["regression"] =
+                    json!(is_regression(solve, result.solve.median, threshold));
+                if json["parse"]["regression"] == true || json["solve"]["regression"] == true {
+                    all_within_threshold = false;
+                }
+            }
+            println!("{}", json);
+
+            baseline.record(&result, &input_name);
+            continue;
+        }
+
+        println!(
+            "day {} part {} ({}), {} iterations after {} warmup",
+            day.number,
+            part.name(),
+            input_name,
+            iterations,
+            warmup
+        );
+        println!(
+            "       {:>9}  {:>9}  {:>9}  {:>9}  {:>9}",
+            "min", "median", "mean", "std dev", "baseline"
+        );
+
+        for (label, stats, previous) in [
+            ("parse", result.parse, previous.map(|p| p.0)),
+            ("solve", result.solve, previous.map(|p| p.1)),
+        ] {
+            let comparison = match previous {
+                None => format!("{:>9}", "-"),
+                Some(previous) => {
+                    let change = stats.median.as_secs_f64() / previous.as_secs_f64() - 1.0;
+                    let flag = if is_regression(previous, stats.median, threshold) {
+                        all_within_threshold = false;
+                        "  REGRESSION"
+                    } else {
+                        ""
+                    };
+
+                    format!(
+                        "{:>9}  {:+.1}%{}",
+                        format_duration(previous),
+                        change * 100.0,
+                        flag
+                    )
+                }
+            };
+
+            println!(
+                "{:<5}  {:>9}  {:>9}  {:>9}  {:>9}  {}",
+                label,
+                format_duration(stats.min),
+                format_duration(stats.median),
+                format_duration(stats.mean),
+                format_duration(stats.std_dev),
+                comparison
+            );
+        }
+        println!();
+
+        baseline.record(&result, &input_name);
+    }
+
+    if matches.is_present("save-baseline") {
+        if let Err(message) = baseline.save(baseline_path) {
+            eprintln!("{}", message);
+            process::exit(1);
+        }
+        if format == Format::Text {
+            println!("Saved baseline to {}", baseline_path.display());
+        }
+    }
+
+    all_within_threshold
 }
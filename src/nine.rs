@@ -1,87 +1,49 @@
 use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = count_tail_visits(&values, 0);
+use crate::answer::Answer;
+use crate::grid::{Direction, Position};
+use crate::params::Params;
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
-
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = count_tail_visits(&values, 8);
+pub struct DayNine;
 
-    println!("{}", answer);
-}
+impl Solution for DayNine {
+    type Input = Vec<Move>;
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Move> {
-    let mut moves: Vec<Move> = Vec::new();
-    for line in reader.lines() {
-        moves.push(Move::from_str(&line.unwrap()));
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
     }
 
-    moves
-}
-
-fn count_tail_visits(moves: &[Move], num_segments: usize) -> i32 {
-    let mut state = State::new(num_segments);
-
-    for m in moves.iter() {
-        state.apply_move(m)
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(count_tail_visits(input, 0).into())
     }
 
-    state.known_tail_locations.len() as i32
-}
-
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
-struct Position {
-    row: i32,
-    column: i32,
-}
-
-impl Position {
-    fn new(r: i32, c: i32) -> Position {
-        Position { row: r, column: c }
-    }
-
-    fn diff(&self, other: &Position) -> DistanceVector {
-        DistanceVector {
-            row_diff: other.row - self.row,
-            column_diff: other.column - self.column,
-        }
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(count_tail_visits(input, 8).into())
     }
 
-    fn moved(&self, vector: &DistanceVector) -> Position {
-        Position::new(self.row + vector.row_diff, self.column + vector.column_diff)
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
     }
 }
 
-#[derive(Debug)]
-struct DistanceVector {
-    row_diff: i32,
-    column_diff: i32,
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Move>, ParseError> {
+    parse_lines(reader, Move::from_str)
 }
 
-impl DistanceVector {
-    fn new(r: i32, c: i32) -> DistanceVector {
-        DistanceVector {
-            row_diff: r,
-            column_diff: c,
-        }
-    }
+/// How many positions the tail visits, with `num_segments` knots between the head and the tail.
+pub fn count_tail_visits(moves: &[Move], num_segments: usize) -> i32 {
+    let mut state = State::new(num_segments);
 
-    fn euclidean_distance(&self) -> f32 {
-        f32::sqrt((self.row_diff.pow(2) + self.column_diff.pow(2)) as f32)
+    for m in moves.iter() {
+        state.apply_move(m)
     }
 
-    fn to_diagonal_move(&self) -> DistanceVector {
-        let row_diff = self.row_diff / self.row_diff.abs();
-        let column_diff = self.column_diff / self.column_diff.abs();
-
-        DistanceVector::new(row_diff, column_diff)
-    }
+    state.known_tail_locations.len() as i32
 }
 
 #[derive(Debug)]
@@ -96,211 +58,80 @@ impl State {
     fn new(num_segments: usize) -> State {
         let mut known_tail_locations = HashSet::new();
 
-        known_tail_locations.insert(Position::new(0, 0));
+        known_tail_locations.insert(Position::origin());
 
         State {
-            head: Position::new(0, 0),
-            segments: vec![Position::new(0, 0); num_segments],
-            tail: Position::new(0, 0),
+            head: Position::origin(),
+            segments: vec![Position::origin(); num_segments],
+            tail: Position::origin(),
             known_tail_locations,
         }
     }
 
     fn apply_move(&mut self, m: &Move) {
         for _ in 0..m.distance {
-            self.head = self.head.moved(&m.to_distance_vector());
+            self.head = self.head.step(m.direction, 1);
 
-            let num_segments = self.segments.len();
-            for i in 0..num_segments {
-                self.update_segment(i);
+            let mut leader = self.head;
+            for segment in self.segments.iter_mut() {
+                *segment = State::follow(segment, &leader);
+                leader = *segment;
             }
 
-            self.update_tail();
+            self.tail = State::follow(&self.tail, &leader);
+            self.known_tail_locations.insert(self.tail);
         }
     }
 
-    fn update_segment(&mut self, segment: usize) {
-        let dist = self.segments[segment].diff(&self.get_prev_segment_pos(segment));
-
-        let vector = match dist {
-            DistanceVector {
-                row_diff: 2,
-                column_diff: 0,
-            } => DistanceVector::new(1, 0),
-            DistanceVector {
-                row_diff: -2,
-                column_diff: 0,
-            } => DistanceVector::new(-1, 0),
-            DistanceVector {
-                row_diff: 0,
-                column_diff: 2,
-            } => DistanceVector::new(0, 1),
-            DistanceVector {
-                row_diff: 0,
-                column_diff: -2,
-            } => DistanceVector::new(0, -1),
-            _ => {
-                if dist.euclidean_distance() > 1.5000001 {
-                    dist.to_diagonal_move()
-                } else {
-                    DistanceVector::new(0, 0)
-                }
-            }
-        };
-
-        self.segments[segment] = self.segments[segment].moved(&vector);
-    }
-
-    fn get_prev_segment_pos(&self, segment: usize) -> Position {
-        if segment == 0 {
-            self.head.clone()
+    /// Where a knot moves to once the knot ahead of it has moved, staying put if they are still
+    /// touching and otherwise stepping towards it (diagonally if they aren't in line).
+    fn follow(knot: &Position, leader: &Position) -> Position {
+        if knot.chebyshev_distance(leader) > 1 {
+            *knot + (*leader - *knot).signum()
         } else {
-            self.segments[segment - 1].clone()
+            *knot
         }
     }
-
-    fn get_last_segment_pos(&self) -> Position {
-        if !self.segments.is_empty() {
-            self.segments.last().unwrap().clone()
-        } else {
-            self.head.clone()
-        }
-    }
-
-    fn update_tail(&mut self) {
-        let dist = self.tail.diff(&self.get_last_segment_pos());
-
-        let vector = match dist {
-            DistanceVector {
-                row_diff: 2,
-                column_diff: 0,
-            } => DistanceVector::new(1, 0),
-            DistanceVector {
-                row_diff: -2,
-                column_diff: 0,
-            } => DistanceVector::new(-1, 0),
-            DistanceVector {
-                row_diff: 0,
-                column_diff: 2,
-            } => DistanceVector::new(0, 1),
-            DistanceVector {
-                row_diff: 0,
-                column_diff: -2,
-            } => DistanceVector::new(0, -1),
-            _ => {
-                if dist.euclidean_distance() > 1.5000001 {
-                    dist.to_diagonal_move()
-                } else {
-                    DistanceVector::new(0, 0)
-                }
-            }
-        };
-
-        self.tail = self.tail.moved(&vector);
-
-        self.known_tail_locations.insert(self.tail.clone());
-    }
 }
 
-#[derive(Debug)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn from_str(s: &str) -> Direction {
-        match s {
-            "U" => Direction::Up,
-            "D" => Direction::Down,
-            "R" => Direction::Right,
-            "L" => Direction::Left,
-            _ => panic!(),
-        }
+fn parse_direction(s: &str) -> Result<Direction, ParseError> {
+    match s {
+        "U" => Ok(Direction::North),
+        "D" => Ok(Direction::South),
+        "R" => Ok(Direction::East),
+        "L" => Ok(Direction::West),
+        _ => Err(ParseError::new(s, "a direction (U, D, L or R)")),
     }
 }
 
 #[derive(Debug)]
-struct Move {
-    direction: Direction,
-    distance: usize,
+pub struct Move {
+    pub direction: Direction,
+    pub distance: usize,
 }
 
 impl Move {
-    fn from_str(line: &str) -> Move {
-        let mut parts = line.split(' ');
-
-        let direction = Direction::from_str(parts.next().unwrap());
-        let distance = parts.next().unwrap().parse().unwrap();
-
-        Move {
-            direction,
-            distance,
-        }
-    }
+    fn from_str(line: &str) -> Result<Move, ParseError> {
+        let (direction, distance) = line
+            .split_once(' ')
+            .ok_or_else(|| ParseError::new(line, "a move, such as \"R 4\""))?;
 
-    fn to_distance_vector(&self) -> DistanceVector {
-        match self.direction {
-            Direction::Up => DistanceVector::new(1, 0),
-            Direction::Down => DistanceVector::new(-1, 0),
-            Direction::Left => DistanceVector::new(0, -1),
-            Direction::Right => DistanceVector::new(0, 1),
-        }
+        Ok(Move {
+            direction: parse_direction(direction)?,
+            distance: parse_number(distance, "a distance")?,
+        })
     }
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/nine_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 13;
-        let actual = count_tail_visits(&values, 0);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/nine.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 6498;
-        let actual = count_tail_visits(&values, 0);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/nine_example_2.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 36;
-        let actual = count_tail_visits(&values, 8);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/nine.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 2531;
-        let actual = count_tail_visits(&values, 8);
-
-        assert_eq!(expected, actual)
-    }
+/// Generates `size` motions of the head of the rope.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}\n",
+                rng.choose(&['U', 'D', 'L', 'R']),
+                rng.between(1, 20)
+            )
+        })
+        .collect()
 }
@@ -1,226 +1,213 @@
+use regex::Regex;
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = sum_all_quality_levels(&values);
+use crate::answer::Answer;
+use crate::params::{Param, Params};
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::search::{self, Best, Limits};
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayNineteen;
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = multiply_max_geodes(&values);
+impl Solution for DayNineteen {
+    type Input = Vec<Blueprint>;
 
-    println!("{}", answer);
-}
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Blueprint> {
-    let mut blueprints: Vec<Blueprint> = Vec::new();
-    for line in reader.lines() {
-        blueprints.push(Blueprint::from_str(&line.unwrap()));
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::integer(
+                "part_one_minutes",
+                24,
+                "Minutes to collect geodes for in part one",
+            )
+            .with_range(0..=i32::MAX as i64),
+            Param::integer(
+                "part_two_minutes",
+                32,
+                "Minutes to collect geodes for in part two",
+            )
+            .with_range(0..=i32::MAX as i64),
+            Param::integer(
+                "part_two_blueprints",
+                3,
+                "Blueprints that are left in part two",
+            )
+            .with_range(0..=100),
+        ]
     }
 
-    blueprints
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(sum_all_quality_levels(
+            input,
+            params.integer("part_one_minutes") as i32,
+            params.limits(),
+        )
+        .into())
+    }
+
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(multiply_max_geodes(
+            input,
+            params.integer("part_two_minutes") as i32,
+            params.integer("part_two_blueprints") as usize,
+            params.limits(),
+        )?
+        .into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Blueprint>, ParseError> {
+    parse_lines(reader, Blueprint::from_str)
 }
 
-fn sum_all_quality_levels(blueprints: &[Blueprint]) -> i32 {
-    let num_steps = 24;
+/// The sum of each blueprint's quality level, its number times the most geodes it can open.
+pub fn sum_all_quality_levels(
+    blueprints: &[Blueprint],
+    num_steps: i32,
+    limits: &Limits,
+) -> Best<i64> {
     let start_state = State::new(
         vec![(Resource::OreRobot, 1)].into_iter().collect(),
         num_steps,
     );
 
-    blueprints
-        .iter()
-        .enumerate()
-        .map(|(i, b)| calc_largest_geode_total(b, &start_state) * (i as i32 + 1))
-        .sum()
-}
-
-fn calc_largest_geode_total(blueprint: &Blueprint, starting_state: &State) -> i32 {
-    let mut states_to_visit: BinaryHeap<(State, Path)> =
-        vec![(starting_state.clone(), Path::new())]
-            .into_iter()
-            .collect();
-    let mut visited_states: HashSet<State> = HashSet::new();
+    let mut total = Best {
+        value: 0,
+        complete: true,
+    };
+    for (i, blueprint) in blueprints.iter().enumerate() {
+        let geodes = calc_largest_geode_total(blueprint, &start_state, limits);
 
-    let mut max_geodes = 0;
-
-    while !states_to_visit.is_empty() {
-        let (state, path) = states_to_visit.pop().unwrap();
-
-        if visited_states.contains(&state) {
-            continue;
-        }
-        visited_states.insert(state.clone());
-
-        let num_geodes = *state.resources.get(&Resource::Geode).unwrap_or(&0);
-        if num_geodes > max_geodes {
-            max_geodes = num_geodes;
-        }
-
-        let best_case_num_geodes = state.get_best_case_num_geodes();
-        if best_case_num_geodes <= max_geodes {
-            continue;
-        }
+        total.value += geodes.value as i64 * (i as i64 + 1);
+        total.complete &= geodes.complete;
+    }
 
-        if state.steps_remaining >= 1 {
-            for action in state.get_possible_actions(blueprint) {
-                let new_state = state.step(action, blueprint);
+    total
+}
 
-                if !visited_states.contains(&new_state) {
-                    states_to_visit.push((new_state, path.appended(&action)));
-                }
+/// The most geodes that the blueprint can open, or the most found before reaching the limits.
+fn calc_largest_geode_total(
+    blueprint: &Blueprint,
+    starting_state: &State,
+    limits: &Limits,
+) -> Best<i32> {
+    let result = search::branch_and_bound_with_limits(
+        starting_state.clone(),
+        |state| {
+            if state.steps_remaining >= 1 {
+                state
+                    .get_possible_actions(blueprint)
+                    .into_iter()
+                    .map(|action| state.step(action, blueprint))
+                    .collect()
+            } else {
+                vec![]
             }
-        }
-    }
+        },
+        |state| *state.resources.get(&Resource::Geode).unwrap_or(&0),
+        |state| state.get_best_case_num_geodes(),
+        limits,
+    );
 
-    max_geodes
+    Best {
+        value: result.best.map_or(0, |(_, geodes)| geodes),
+        complete: result.complete,
+    }
 }
 
-fn multiply_max_geodes(blueprints: &[Blueprint]) -> i32 {
-    let blueprints: Vec<Blueprint> = blueprints.iter().take(3).cloned().collect();
+/// The product of the most geodes that each of the first `num_blueprints` blueprints can open.
+pub fn multiply_max_geodes(
+    blueprints: &[Blueprint],
+    num_steps: i32,
+    num_blueprints: usize,
+    limits: &Limits,
+) -> Result<Best<i64>, SolveError> {
+    let blueprints: Vec<Blueprint> = blueprints.iter().take(num_blueprints).cloned().collect();
 
-    let num_steps = 32;
     let start_state = State::new(
         vec![(Resource::OreRobot, 1)].into_iter().collect(),
         num_steps,
     );
 
-    blueprints
-        .iter()
-        .map(|b| calc_largest_geode_total(b, &start_state))
-        .product()
-}
-
-#[derive(Debug, Eq, PartialEq, Clone, Ord, PartialOrd)]
-struct Path {
-    valves: Vec<Action>,
-}
-
-impl Path {
-    fn new() -> Path {
-        Path { valves: vec![] }
+    let mut total: Best<i64> = Best {
+        value: 1,
+        complete: true,
+    };
+    for blueprint in blueprints.iter() {
+        let geodes = calc_largest_geode_total(blueprint, &start_state, limits);
+
+        total.value = total
+            .value
+            .checked_mul(geodes.value as i64)
+            .ok_or_else(|| SolveError::new("The product of the geodes is too large"))?;
+        total.complete &= geodes.complete;
     }
 
-    fn appended(&self, node: &Action) -> Path {
-        let mut valves = self.valves.clone();
-        valves.push(*node);
-
-        Path { valves }
-    }
+    Ok(total)
 }
 
 #[derive(Debug, Clone)]
-struct Blueprint {
-    costs: HashMap<Resource, HashSet<(Resource, i32)>>,
+pub struct Blueprint {
+    pub costs: HashMap<Resource, HashSet<(Resource, i32)>>,
 }
 
 impl Blueprint {
-    fn from_str(s: &str) -> Blueprint {
-        let parts: Vec<&str> = s.split('.').collect();
+    fn from_str(s: &str) -> Result<Blueprint, ParseError> {
+        let re = Regex::new(concat!(
+            r"^Blueprint [0-9]+: ",
+            r"Each ore robot costs ([0-9]+) ore\. ",
+            r"Each clay robot costs ([0-9]+) ore\. ",
+            r"Each obsidian robot costs ([0-9]+) ore and ([0-9]+) clay\. ",
+            r"Each geode robot costs ([0-9]+) ore and ([0-9]+) obsidian\.$",
+        ))
+        .unwrap();
+        let matches = re.captures(s).ok_or_else(|| {
+            ParseError::new(
+                s,
+                "a blueprint, such as \"Blueprint 1: Each ore robot costs 4 ore. ...\"",
+            )
+        })?;
+        let cost = |i: usize| parse_number::<i32>(&matches[i], "a cost");
 
         let mut costs = HashMap::new();
-
-        let ore_robot_cost: i32 = parts[0]
-            .split("costs ")
-            .last()
-            .unwrap()
-            .split(" ore")
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
         costs.insert(
             Resource::OreRobot,
-            vec![(Resource::Ore, ore_robot_cost)].into_iter().collect(),
+            vec![(Resource::Ore, cost(1)?)].into_iter().collect(),
         );
-
-        let clay_robot_cost: i32 = parts[1]
-            .split("costs ")
-            .last()
-            .unwrap()
-            .split(" ore")
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
         costs.insert(
             Resource::ClayRobot,
-            vec![(Resource::Ore, clay_robot_cost)].into_iter().collect(),
+            vec![(Resource::Ore, cost(2)?)].into_iter().collect(),
         );
-
-        let obsidian_robot_cost_1: i32 = parts[2]
-            .split("costs ")
-            .last()
-            .unwrap()
-            .split(" ore")
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let obsidian_robot_cost_2: i32 = parts[2]
-            .split("costs ")
-            .last()
-            .unwrap()
-            .split(" and ")
-            .last()
-            .unwrap()
-            .split(" clay")
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
         costs.insert(
             Resource::ObsidianRobot,
-            vec![
-                (Resource::Ore, obsidian_robot_cost_1),
-                (Resource::Clay, obsidian_robot_cost_2),
-            ]
-            .into_iter()
-            .collect(),
+            vec![(Resource::Ore, cost(3)?), (Resource::Clay, cost(4)?)]
+                .into_iter()
+                .collect(),
         );
-
-        let geode_robot_cost_1: i32 = parts[3]
-            .split("costs ")
-            .last()
-            .unwrap()
-            .split(" ore")
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
-        let geode_robot_cost_2: i32 = parts[3]
-            .split("costs ")
-            .last()
-            .unwrap()
-            .split(" and ")
-            .last()
-            .unwrap()
-            .split(" obsidian")
-            .next()
-            .unwrap()
-            .parse()
-            .unwrap();
         costs.insert(
             Resource::GeodeRobot,
-            vec![
-                (Resource::Ore, geode_robot_cost_1),
-                (Resource::Obsidian, geode_robot_cost_2),
-            ]
-            .into_iter()
-            .collect(),
+            vec![(Resource::Ore, cost(5)?), (Resource::Obsidian, cost(6)?)]
+                .into_iter()
+                .collect(),
         );
 
-        Blueprint { costs }
+        Ok(Blueprint { costs })
     }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
-enum Resource {
+pub enum Resource {
     OreRobot,
     ClayRobot,
     ObsidianRobot,
@@ -310,10 +297,9 @@ impl State {
 
     fn get_best_case_num_geodes(&self) -> i32 {
         let mut num_geodes = *self.resources.get(&Resource::Geode).unwrap_or(&0);
-        let mut num_geode_robots = *self.resources.get(&Resource::GeodeRobot).unwrap_or(&0);
-        for _ in 0..self.steps_remaining {
-            num_geodes += num_geode_robots;
-            num_geode_robots += 1;
+        let num_geode_robots = *self.resources.get(&Resource::GeodeRobot).unwrap_or(&0);
+        for robots in num_geode_robots..num_geode_robots + self.steps_remaining {
+            num_geodes += robots;
         }
 
         num_geodes
@@ -347,7 +333,29 @@ enum Action {
     DontBuild,
 }
 
-/*
+/// Generates `size` blueprints, with robot costs in the same ranges as the real input.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (1..=size.max(1))
+        .map(|id| {
+            format!(
+                concat!(
+                    "Blueprint {}: Each ore robot costs {} ore. ",
+                    "Each clay robot costs {} ore. ",
+                    "Each obsidian robot costs {} ore and {} clay. ",
+                    "Each geode robot costs {} ore and {} obsidian.\n"
+                ),
+                id,
+                rng.between(2, 4),
+                rng.between(2, 4),
+                rng.between(2, 4),
+                rng.between(5, 20),
+                rng.between(2, 4),
+                rng.between(5, 20)
+            )
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -356,49 +364,22 @@ mod tests {
     use std::fs::File;
 
     #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    fn test_part_1_stops_at_limit() {
+        let f = File::open("inputs/nineteen_example.txt").unwrap();
+        let blueprints = read_input(&mut BufReader::new(f)).unwrap();
 
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
+        let limits = Limits::default().with_max_expanded(1000);
+        let best = sum_all_quality_levels(&blueprints, 24, &limits);
 
-        assert_eq!(expected, actual)
+        assert!(!best.complete);
+        assert!(best.value <= 33);
     }
 
     #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
+    fn test_part_two_blueprints_range() {
+        let mut params = Params::defaults(&DayNineteen.params(), false);
 
-        assert_eq!(expected, actual)
+        assert!(params.set("part_two_blueprints", "100").is_ok());
+        assert!(params.set("part_two_blueprints", "2147483647").is_err());
     }
 }
-*/
@@ -1,21 +1,36 @@
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let numbers = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_max_calories(&numbers);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayOne;
+
+impl Solution for DayOne {
+    type Input = Vec<Vec<i32>>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-pub fn part_two() {
-    let numbers = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_top_three_calories(&numbers);
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_max_calories(input).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_top_three_calories(input).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn find_top_three_calories(elves: &[Vec<i32>]) -> i32 {
+/// The total calories carried by the three elves carrying the most.
+pub fn find_top_three_calories(elves: &[Vec<i32>]) -> i32 {
     let mut totals: Vec<i32> = elves.iter().map(|e| e.iter().sum()).collect();
 
     totals.sort();
@@ -23,7 +38,8 @@ fn find_top_three_calories(elves: &[Vec<i32>]) -> i32 {
     totals.iter().rev().take(3).sum()
 }
 
-fn find_max_calories(elves: &[Vec<i32>]) -> i32 {
+/// The most calories carried by any one elf.
+pub fn find_max_calories(elves: &[Vec<i32>]) -> i32 {
     let mut largest = 0;
 
     for elve in elves.iter() {
@@ -37,75 +53,35 @@ fn find_max_calories(elves: &[Vec<i32>]) -> i32 {
     largest
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Vec<i32>> {
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Vec<i32>>, ParseError> {
     let mut numbers: Vec<Vec<i32>> = Vec::new();
     numbers.push(Vec::new());
-    for line in reader.lines() {
-        let line = line.unwrap();
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
         if line.is_empty() {
             numbers.push(Vec::new());
 
             continue;
         }
 
-        let number: i32 = line.parse().unwrap();
+        let number: i32 =
+            parse_number(&line, "a number of calories").map_err(|e| e.on_line(i + 1))?;
 
         numbers.iter_mut().last().unwrap().push(number);
     }
 
-    numbers
+    Ok(numbers)
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/one_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 24000;
-        let actual = find_max_calories(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/one.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 74394;
-        let actual = find_max_calories(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/one_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 45000;
-        let actual = find_top_three_calories(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/one.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 212836;
-        let actual = find_top_three_calories(&values);
-
-        assert_eq!(expected, actual);
-    }
+/// Generates `size` elves carrying between one and five snacks each.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let elves: Vec<String> = (0..size.max(1))
+        .map(|_| {
+            (0..rng.between(1, 5))
+                .map(|_| format!("{}\n", rng.between(1000, 60000)))
+                .collect()
+        })
+        .collect();
+
+    elves.join("\n")
 }
@@ -0,0 +1,304 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::search::Limits;
+
+/// The kind of value a parameter takes, checked when it is overridden.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParamKind {
+    Integer,
+    Text,
+    /// A pair of integers written as "x,y", such as day 14's sand source.
+    Point,
+}
+
+impl fmt::Display for ParamKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamKind::Integer => f.write_str("integer"),
+            ParamKind::Text => f.write_str("text"),
+            ParamKind::Point => f.write_str("point"),
+        }
+    }
+}
+
+/// A tunable constant of a puzzle, such as the number of rounds to simulate.
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: String,
+    /// The value to use with the example input from the puzzle description, if it differs.
+    pub example_default: Option<String>,
+    pub description: &'static str,
+    /// The values allowed for an integer, or for both coordinates of a point, such as only
+    /// non-negative numbers of rounds.
+    pub range: Option<RangeInclusive<i64>>,
+}
+
+impl Param {
+    pub fn integer(name: &'static str, default: i64, description: &'static str) -> Param {
+        Param::new(name, ParamKind::Integer, default.to_string(), description)
+    }
+
+    pub fn text(name: &'static str, default: &str, description: &'static str) -> Param {
+        Param::new(name, ParamKind::Text, default.to_string(), description)
+    }
+
+    pub fn point(name: &'static str, default: (i64, i64), description: &'static str) -> Param {
+        Param::new(
+            name,
+            ParamKind::Point,
+            format!("{},{}", default.0, default.1),
+            description,
+        )
+    }
+
+    fn new(
+        name: &'static str,
+        kind: ParamKind,
+        default: String,
+        description: &'static str,
+    ) -> Param {
+        Param {
+            name,
+            kind,
+            default,
+            example_default: None,
+            description,
+            range: None,
+        }
+    }
+
+    pub fn with_example_default(mut self, value: i64) -> Param {
+        self.example_default = Some(value.to_string());
+        self
+    }
+
+    /// Only allows values in the range, for parameters that would otherwise be cast into something
+    /// meaningless, like a negative number of rounds.
+    pub fn with_range(mut self, range: RangeInclusive<i64>) -> Param {
+        self.range = Some(range);
+        self
+    }
+
+    /// Describes the values allowed, such as "integer from 0 to 10".
+    pub fn expected(&self) -> String {
+        let range = match &self.range {
+            Some(range) => range,
+            None => return self.kind.to_string(),
+        };
+
+        let bounds = match (*range.start(), *range.end()) {
+            (i64::MIN, end) => format!("of at most {}", end),
+            (start, i64::MAX) => format!("of at least {}", start),
+            (start, end) => format!("from {} to {}", start, end),
+        };
+        match self.kind {
+            ParamKind::Point => format!("point with coordinates {}", bounds),
+            kind => format!("{} {}", kind, bounds),
+        }
+    }
+
+    fn is_valid(&self, value: &str) -> bool {
+        let in_range = |n: i64| self.range.as_ref().is_none_or(|r| r.contains(&n));
+
+        match self.kind {
+            ParamKind::Integer => value.parse::<i64>().is_ok_and(in_range),
+            ParamKind::Text => true,
+            ParamKind::Point => parse_point(value).is_some_and(|(x, y)| in_range(x) && in_range(y)),
+        }
+    }
+}
+
+/// The values of a day's parameters for one run: the defaults, plus any overrides. They also carry
+/// the limits for the days that search for their answers.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: BTreeMap<&'static str, (Param, String)>,
+    limits: Limits,
+}
+
+impl Params {
+    /// The default values of the given parameters, using the example defaults when running
+    /// against an example input.
+    pub fn defaults(params: &[Param], example: bool) -> Params {
+        let values = params
+            .iter()
+            .map(|p| {
+                let value = match (&p.example_default, example) {
+                    (Some(value), true) => value.clone(),
+                    _ => p.default.clone(),
+                };
+
+                (p.name, (p.clone(), value))
+            })
+            .collect();
+
+        Params {
+            values,
+            limits: Limits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Params {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        if !self.values.contains_key(name) {
+            return Err(if self.values.is_empty() {
+                format!("Unknown parameter {}, this day has none", name)
+            } else {
+                format!(
+                    "Unknown parameter {}, expected one of: {}",
+                    name,
+                    self.values.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            });
+        }
+
+        let (param, current) = self.values.get_mut(name).unwrap();
+
+        if !param.is_valid(value) {
+            return Err(format!(
+                "Invalid value for parameter {}: expected {}, found \"{}\"",
+                name,
+                param.expected(),
+                value
+            ));
+        }
+
+        *current = value.to_string();
+        Ok(())
+    }
+
+    /// Applies overrides written as "name=value", as given to `--param`.
+    pub fn set_all<'a>(
+        &mut self,
+        assignments: impl Iterator<Item = &'a str>,
+    ) -> Result<(), String> {
+        for assignment in assignments {
+            let (name, value) = assignment.split_once('=').ok_or_else(|| {
+                format!(
+                    "Expected a parameter as name=value, found \"{}\"",
+                    assignment
+                )
+            })?;
+
+            self.set(name, value)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .map(|(name, (_, value))| (*name, value.as_str()))
+    }
+
+    pub fn integer(&self, name: &str) -> i64 {
+        self.get(name, ParamKind::Integer).parse().unwrap()
+    }
+
+    pub fn text(&self, name: &str) -> &str {
+        self.get(name, ParamKind::Text)
+    }
+
+    pub fn point(&self, name: &str) -> (i64, i64) {
+        parse_point(self.get(name, ParamKind::Point)).unwrap()
+    }
+
+    /// Values are checked against their kind when they are set, so only asking for a parameter
+    /// that the day never declared can fail.
+    fn get(&self, name: &str, kind: ParamKind) -> &str {
+        match self.values.get(name) {
+            Some((param, value)) if param.kind == kind => value,
+            _ => panic!("No {} parameter named {}", kind, name),
+        }
+    }
+}
+
+fn parse_point(s: &str) -> Option<(i64, i64)> {
+    let (x, y) = s.split_once(',')?;
+
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn example_params() -> Vec<Param> {
+        vec![
+            Param::integer("row", 2000000, "Row to count").with_example_default(10),
+            Param::text("start", "AA", "Valve to start at"),
+            Param::point("source", (500, 0), "Where sand comes from").with_range(0..=1000),
+            Param::integer("rounds", 10, "Rounds to simulate").with_range(0..=i64::MAX),
+        ]
+    }
+
+    #[test]
+    fn test_defaults() {
+        let params = Params::defaults(&example_params(), false);
+        assert_eq!(2000000, params.integer("row"));
+        assert_eq!("AA", params.text("start"));
+        assert_eq!((500, 0), params.point("source"));
+
+        let params = Params::defaults(&example_params(), true);
+        assert_eq!(10, params.integer("row"));
+    }
+
+    #[test]
+    fn test_set_all() {
+        let mut params = Params::defaults(&example_params(), false);
+
+        params
+            .set_all(["row=11", "start=BB", "source=1,2"].iter().cloned())
+            .unwrap();
+
+        assert_eq!(11, params.integer("row"));
+        assert_eq!("BB", params.text("start"));
+        assert_eq!((1, 2), params.point("source"));
+    }
+
+    #[test]
+    fn test_set_invalid() {
+        let mut params = Params::defaults(&example_params(), false);
+
+        assert!(params.set("row", "ten").is_err());
+        assert!(params.set("source", "500").is_err());
+        assert!(params.set("column", "1").is_err());
+        assert!(params.set_all(["row"].iter().cloned()).is_err());
+        assert_eq!(2000000, params.integer("row"));
+    }
+
+    #[test]
+    fn test_set_out_of_range() {
+        let mut params = Params::defaults(&example_params(), false);
+
+        assert_eq!(
+            Err(
+                "Invalid value for parameter rounds: expected integer of at least 0, found \"-1\""
+                    .to_string()
+            ),
+            params.set("rounds", "-1")
+        );
+        assert!(params.set("source", "500,-1").is_err());
+        assert!(params.set("source", "1001,0").is_err());
+        assert_eq!(10, params.integer("rounds"));
+        assert_eq!((500, 0), params.point("source"));
+
+        params.set("rounds", "0").unwrap();
+        params.set("source", "1000,1000").unwrap();
+        assert_eq!(0, params.integer("rounds"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+
+/// A problem with a puzzle input: the text that could not be read and what was expected in its
+/// place.
+///
+/// Parsers only know about the text they are given, so the line number is added by whatever split
+/// the input into lines, and the day by the registry.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub day: Option<u32>,
+    /// Line number in the input, starting from 1.
+    pub line: Option<usize>,
+    pub text: String,
+    pub expected: String,
+}
+
+impl ParseError {
+    pub fn new(text: &str, expected: &str) -> ParseError {
+        ParseError {
+            day: None,
+            line: None,
+            text: text.to_string(),
+            expected: expected.to_string(),
+        }
+    }
+
+    /// Records the line the error was on, unless a more specific line is already known.
+    pub fn on_line(mut self, line: usize) -> ParseError {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    pub fn for_day(mut self, day: u32) -> ParseError {
+        self.day = Some(day);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.day, self.line) {
+            (Some(day), Some(line)) => write!(f, "day {}, line {}: ", day, line)?,
+            (Some(day), None) => write!(f, "day {}: ", day)?,
+            (None, Some(line)) => write!(f, "line {}: ", line)?,
+            (None, None) => {}
+        }
+
+        write!(f, "expected {}, found {:?}", self.expected, self.text)
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> ParseError {
+        ParseError::new(&e.to_string(), "readable text")
+    }
+}
+
+/// Reads all of the lines of an input, for inputs that have to be looked at as a whole.
+pub fn read_lines<R: Read>(reader: &mut BufReader<R>) -> Result<Vec<String>, ParseError> {
+    let mut lines = vec![];
+    for (i, line) in reader.lines().enumerate() {
+        lines.push(line.map_err(|e| ParseError::from(e).on_line(i + 1))?);
+    }
+
+    Ok(lines)
+}
+
+/// Parses each line of an input with `parse_line`, adding the line number to any error.
+pub fn parse_lines<R: Read, T>(
+    reader: &mut BufReader<R>,
+    parse_line: impl Fn(&str) -> Result<T, ParseError>,
+) -> Result<Vec<T>, ParseError> {
+    read_lines(reader)?
+        .iter()
+        .enumerate()
+        .map(|(i, line)| parse_line(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+/// Parses a number, or any other `FromStr` value, describing what was expected if it isn't one.
+pub fn parse_number<T: FromStr>(text: &str, expected: &str) -> Result<T, ParseError> {
+    text.parse().map_err(|_| ParseError::new(text, expected))
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::new("move x from 1 to 2", "a number of crates");
+
+        assert_eq!(
+            "expected a number of crates, found \"move x from 1 to 2\"",
+            error.to_string()
+        );
+        assert_eq!(
+            "day 5, line 12: expected a number of crates, found \"move x from 1 to 2\"",
+            error.on_line(12).for_day(5).to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        let input = "1\n2\nthree\n";
+
+        let error = parse_lines(&mut BufReader::new(input.as_bytes()), |l| {
+            parse_number::<i32>(l, "a number")
+        })
+        .unwrap_err();
+
+        assert_eq!(Some(3), error.line);
+        assert_eq!("three", error.text);
+    }
+
+    #[test]
+    fn test_keeps_inner_line() {
+        assert_eq!(Some(2), ParseError::new("", "").on_line(2).on_line(5).line);
+    }
+}
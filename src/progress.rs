@@ -0,0 +1,190 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::runner::format_duration;
+use crate::search::Progress;
+
+/// How long to wait between redraws, and before the first one so that quick runs don't flash a
+/// bar.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+const BAR_WIDTH: usize = 20;
+
+/// A one line progress bar on stderr for long running searches, redrawn in place.
+pub struct ProgressBar {
+    label: String,
+    start: Instant,
+    deadline: Option<Instant>,
+    state: Mutex<BarState>,
+}
+
+#[derive(Default)]
+struct BarState {
+    last_drawn: Option<Instant>,
+    redraws: usize,
+}
+
+impl ProgressBar {
+    /// A bar for a search that stops at `deadline`, if any, which it fills up towards.
+    pub fn new(label: &str, deadline: Option<Instant>) -> ProgressBar {
+        ProgressBar {
+            label: label.to_string(),
+            start: Instant::now(),
+            deadline,
+            state: Mutex::new(BarState::default()),
+        }
+    }
+
+    /// Redraws the bar, unless it was redrawn recently.
+    pub fn update(&self, progress: &Progress) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        if now < state.last_drawn.unwrap_or(self.start) + REDRAW_INTERVAL {
+            return;
+        }
+
+        // Rounded, as the bar is made a moment after the deadline is set
+        let timeout = self.deadline.map(|d| {
+            let millis = d.saturating_duration_since(self.start).as_secs_f64() * 1000.0;
+            Duration::from_millis(millis.round() as u64)
+        });
+        let line = render(
+            &self.label,
+            progress,
+            now - self.start,
+            timeout,
+            state.redraws,
+        );
+        eprint!("\r{}\x1b[K", line);
+
+        state.last_drawn = Some(now);
+        state.redraws += 1;
+    }
+
+    /// Clears the bar, if it was drawn, so that what comes next starts on a clean line.
+    pub fn finish(&self) {
+        if self.state.lock().unwrap().last_drawn.is_some() {
+            eprint!("\r\x1b[K");
+        }
+    }
+}
+
+/// A line such as "day 19 part one [#####---------------] 2.50s of 10.00s, 1.2M expanded, 35.1k
+/// queued, best 9". Without a timeout there is nothing to fill the bar towards, so a marker moves
+/// back and forth along it instead.
+pub fn render(
+    label: &str,
+    progress: &Progress,
+    elapsed: Duration,
+    timeout: Option<Duration>,
+    redraws: usize,
+) -> String {
+    let (bar, time) = match timeout {
+        Some(timeout) => {
+            let fraction = if timeout.is_zero() {
+                1.0
+            } else {
+                (elapsed.as_secs_f64() / timeout.as_secs_f64()).min(1.0)
+            };
+            let filled = (fraction * BAR_WIDTH as f64).round() as usize;
+
+            (
+                "#".repeat(filled) + &"-".repeat(BAR_WIDTH - filled),
+                format!(
+                    "{} of {}",
+                    format_duration(elapsed),
+                    format_duration(timeout)
+                ),
+            )
+        }
+        None => {
+            let position = redraws % (2 * BAR_WIDTH - 2);
+            let position = if position < BAR_WIDTH {
+                position
+            } else {
+                2 * BAR_WIDTH - 2 - position
+            };
+
+            (
+                " ".repeat(position) + "#" + &" ".repeat(BAR_WIDTH - position - 1),
+                format_duration(elapsed),
+            )
+        }
+    };
+
+    let best = match progress.best {
+        Some(best) => best.to_string(),
+        None => "none".to_string(),
+    };
+
+    format!(
+        "{} [{}] {}, {} expanded, {} queued, best {}",
+        label,
+        bar,
+        time,
+        format_count(progress.expanded),
+        format_count(progress.frontier),
+        best
+    )
+}
+
+/// Formats a count with a suffix suited to its size, e.g. "950", "12.3k" or "4.5M".
+fn format_count(count: usize) -> String {
+    if count < 1000 {
+        count.to_string()
+    } else if count < 1_000_000 {
+        format!("{:.1}k", count as f64 / 1000.0)
+    } else {
+        format!("{:.1}M", count as f64 / 1_000_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const PROGRESS: Progress = Progress {
+        expanded: 1_234_567,
+        frontier: 35_100,
+        best: Some(9),
+    };
+
+    #[test]
+    fn test_render_with_timeout() {
+        assert_eq!(
+            "day 19 [#####---------------] 2.50s of 10.00s, 1.2M expanded, 35.1k queued, best 9",
+            render(
+                "day 19",
+                &PROGRESS,
+                Duration::from_millis(2500),
+                Some(Duration::from_secs(10)),
+                0
+            )
+        );
+        assert!(render(
+            "day 19",
+            &PROGRESS,
+            Duration::from_secs(11),
+            Some(Duration::from_secs(10)),
+            0
+        )
+        .contains(&format!("[{}]", "#".repeat(BAR_WIDTH))));
+    }
+
+    #[test]
+    fn test_render_without_timeout() {
+        let progress = Progress {
+            expanded: 950,
+            frontier: 0,
+            best: None,
+        };
+
+        assert_eq!(
+            "day 16 [ #                  ] 850µs, 950 expanded, 0 queued, best none",
+            render("day 16", &progress, Duration::from_micros(850), None, 1)
+        );
+        assert!(render("day 16", &progress, Duration::ZERO, None, BAR_WIDTH)
+            .starts_with(&format!("day 16 [{}# ]", " ".repeat(BAR_WIDTH - 2))));
+    }
+}
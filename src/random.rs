@@ -0,0 +1,144 @@
+/// A small seeded pseudo-random number generator (SplitMix64) for generating puzzle inputs.
+///
+/// It is implemented here rather than taken from a crate so that a seed produces the same input on
+/// every platform and with every version of the dependencies.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `low` to `high`, inclusive.
+    pub fn between(&mut self, low: i64, high: i64) -> i64 {
+        assert!(low <= high, "empty range {}..={}", low, high);
+
+        let span = (high as i128 - low as i128 + 1) as u128;
+        (low as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    /// A number from 0 up to, but not including, `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0, "empty range 0..0");
+
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Returns true with the given probability.
+    pub fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// A word of lowercase letters, with a length from `min_len` to `max_len`.
+    pub fn word(&mut self, min_len: usize, max_len: usize) -> String {
+        let len = self.between(min_len as i64, max_len as i64);
+
+        (0..len)
+            .map(|_| (b'a' + self.below(26) as u8) as char)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use crate::get_days;
+    use crate::solution::Part;
+
+    #[test]
+    fn test_same_seed_same_numbers() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        let mut c = Rng::new(8);
+
+        let a: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+        let c: Vec<u64> = (0..5).map(|_| c.next_u64()).collect();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_between() {
+        let mut rng = Rng::new(1);
+        let values: Vec<i64> = (0..1000).map(|_| rng.between(-2, 2)).collect();
+
+        assert!(values.iter().all(|v| (-2..=2).contains(v)));
+        for v in -2..=2 {
+            assert!(values.contains(&v));
+        }
+        assert_eq!(i64::MAX, rng.between(i64::MAX, i64::MAX));
+    }
+
+    #[test]
+    fn test_shuffle() {
+        let mut rng = Rng::new(3);
+        let mut items: Vec<i32> = (0..20).collect();
+
+        rng.shuffle(&mut items);
+        assert_ne!((0..20).collect::<Vec<i32>>(), items);
+
+        items.sort_unstable();
+        assert_eq!((0..20).collect::<Vec<i32>>(), items);
+    }
+
+    #[test]
+    fn test_generated_inputs_parse() {
+        for day in get_days() {
+            for seed in 0..5 {
+                for size in [1, 5, 30] {
+                    let input = day
+                        .generate(seed, size)
+                        .unwrap_or_else(|| panic!("day {} has no generator", day.number));
+
+                    assert_eq!(Some(input.clone()), day.generate(seed, size));
+                    if let Err(e) = day.parse(&mut input.as_bytes()) {
+                        panic!("seed {} size {}: {}\n{}", seed, size, e, input);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_inputs_solve() {
+        // Day 19 takes seconds for even a single blueprint, so it is only checked to parse
+        for day in get_days().into_iter().filter(|d| d.number != 19) {
+            // Day 16's search grows quickly with the number of valves
+            let size = if day.number == 16 { 5 } else { 10 };
+
+            for seed in 0..3 {
+                let input = day.generate(seed, size).unwrap();
+
+                for part in Part::all() {
+                    day.run(part, &mut input.as_bytes()).unwrap();
+                }
+            }
+        }
+    }
+}
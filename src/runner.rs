@@ -0,0 +1,209 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::ParseError;
+use crate::solution::{Day, Part, SolveError};
+
+/// The outcome of running one part of a day against one input file.
+#[derive(Debug)]
+pub struct RunResult {
+    pub day: u32,
+    pub part: Part,
+    pub input: PathBuf,
+    pub outcome: Result<Answer, RunError>,
+    pub elapsed: Duration,
+}
+
+impl RunResult {
+    /// The name used for the input in `answers.txt`, i.e. its file name without the extension.
+    pub fn input_name(&self) -> String {
+        input_name(&self.input)
+    }
+
+    /// The result as a JSON object, with either an `answer`, its `answer_type` and whether it is
+    /// `complete`, or an `error`.
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({
+            "day": self.day,
+            "part": self.part.name(),
+            "input": self.input.display().to_string(),
+            "time_ms": self.elapsed.as_secs_f64() * 1000.0,
+        });
+
+        match &self.outcome {
+            Ok(answer) => {
+                value["answer"] = answer.to_json();
+                value["answer_type"] = json!(answer.type_name());
+                value["complete"] = json!(answer.is_complete());
+            }
+            Err(e) => value["error"] = json!(e.to_string()),
+        }
+
+        value
+    }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    MissingInput(io::Error),
+    Parse(ParseError),
+    Solve(SolveError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::MissingInput(e) => write!(f, "could not open input: {}", e),
+            RunError::Parse(e) => write!(f, "could not parse input: {}", e),
+            RunError::Solve(e) => write!(f, "could not solve: {}", e),
+        }
+    }
+}
+
+pub fn input_name(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Whether the input is one of the examples from a puzzle description, such as
+/// `inputs/nine_example_2.txt`, which some days need different parameters for.
+pub fn is_example(path: &Path) -> bool {
+    input_name(path).contains("_example")
+}
+
+/// Runs one part of a day against the given input file with the default parameters for it, timing
+/// how long it takes to parse and solve.
+pub fn run_file(day: &Day, part: Part, path: &Path) -> RunResult {
+    run_file_with_params(day, part, path, &day.default_params(is_example(path)))
+}
+
+pub fn run_file_with_params(day: &Day, part: Part, path: &Path, params: &Params) -> RunResult {
+    match File::open(path) {
+        Ok(mut f) => run_reader(day, part, path, &mut f, params),
+        Err(e) => RunResult {
+            day: day.number,
+            part,
+            input: path.to_path_buf(),
+            outcome: Err(RunError::MissingInput(e)),
+            elapsed: Duration::ZERO,
+        },
+    }
+}
+
+/// Runs one part of a day against an already opened input, such as stdin, labelling the result
+/// with the given path.
+pub fn run_reader(
+    day: &Day,
+    part: Part,
+    path: &Path,
+    reader: &mut dyn Read,
+    params: &Params,
+) -> RunResult {
+    let start = Instant::now();
+    let outcome = day
+        .parse(reader)
+        .map_err(RunError::Parse)
+        .and_then(|input| {
+            day.solve(part, input.as_ref(), params)
+                .map_err(RunError::Solve)
+        });
+    let elapsed = start.elapsed();
+
+    RunResult {
+        day: day.number,
+        part,
+        input: path.to_path_buf(),
+        outcome,
+        elapsed,
+    }
+}
+
+/// Formats a duration with a unit suited to its size, e.g. "850µs", "12.3ms" or "4.56s".
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_secs_f64() * 1_000_000.0;
+
+    if micros < 1000.0 {
+        format!("{:.0}µs", micros)
+    } else if micros < 1_000_000.0 {
+        format!("{:.1}ms", micros / 1000.0)
+    } else {
+        format!("{:.2}s", micros / 1_000_000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use crate::get_days;
+
+    #[test]
+    fn test_run_file() {
+        let days = get_days();
+        let day = days.iter().find(|d| d.number == 2).unwrap();
+
+        let result = run_file(day, Part::Two, &day.example_path());
+
+        assert_eq!("two_example", result.input_name());
+        assert_eq!(Answer::Integer(12), result.outcome.unwrap());
+    }
+
+    #[test]
+    fn test_run_file_missing_input() {
+        let days = get_days();
+        let day = days.iter().find(|d| d.number == 2).unwrap();
+
+        let result = run_file(day, Part::One, Path::new("inputs/does_not_exist.txt"));
+
+        assert!(matches!(result.outcome, Err(RunError::MissingInput(_))));
+    }
+
+    #[test]
+    fn test_to_json() {
+        let days = get_days();
+        let day = days.iter().find(|d| d.number == 2).unwrap();
+
+        let json = run_file(day, Part::Two, &day.example_path()).to_json();
+        assert_eq!(2, json["day"]);
+        assert_eq!("two", json["part"]);
+        assert_eq!(12, json["answer"]);
+        assert_eq!("integer", json["answer_type"]);
+        assert_eq!(true, json["complete"]);
+        assert!(json["error"].is_null());
+
+        let json = run_file(day, Part::One, Path::new("inputs/does_not_exist.txt")).to_json();
+        assert!(json["answer"].is_null());
+        assert!(json["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("could not open input"));
+    }
+
+    #[test]
+    fn test_example_params() {
+        let days = get_days();
+        let day = days.iter().find(|d| d.number == 15).unwrap();
+
+        let result = run_file(day, Part::One, &day.example_path());
+
+        assert!(is_example(&day.example_path()));
+        assert!(!is_example(&day.input_path()));
+        assert_eq!(Answer::Integer(26), result.outcome.unwrap());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("850µs", format_duration(Duration::from_micros(850)));
+        assert_eq!("12.3ms", format_duration(Duration::from_micros(12_340)));
+        assert_eq!("4.56s", format_duration(Duration::from_millis(4_560)));
+    }
+}
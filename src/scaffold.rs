@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The module written for a new day, with `__TYPE__` filled in. Its answers are tested by adding
+/// them to answers.txt, which generates a golden test for each.
+const TEMPLATE: &str = r#"use std::io::prelude::*;
+use std::io::BufReader;
+
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{read_lines, ParseError};
+use crate::solution::{Solution, SolveError};
+
+pub struct Day__TYPE__;
+
+impl Solution for Day__TYPE__ {
+    type Input = Vec<String>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(part_one(input).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(part_two(input).into())
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<String>, ParseError> {
+    read_lines(reader)
+}
+
+fn part_one(_lines: &[String]) -> i64 {
+    todo!()
+}
+
+fn part_two(_lines: &[String]) -> i64 {
+    todo!()
+}
+"#;
+
+const ONES: [&str; 10] = [
+    "", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+];
+
+const TEENS: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+/// The module and type names of a day, e.g. `("twentyone", "TwentyOne")` for day 21.
+pub fn day_names(number: u32) -> Result<(String, String), String> {
+    let words: Vec<&str> = match number {
+        1..=9 => vec![ONES[number as usize]],
+        10..=19 => vec![TEENS[number as usize - 10]],
+        20 => vec!["twenty"],
+        21..=25 => vec!["twenty", ONES[number as usize - 20]],
+        _ => return Err(format!("Invalid day {}, expected 1 to 25", number)),
+    };
+
+    let name = words.concat();
+    let type_name = words
+        .iter()
+        .map(|w| w[..1].to_uppercase() + &w[1..])
+        .collect();
+
+    Ok((name, type_name))
+}
+
+pub fn render_module(number: u32) -> Result<String, String> {
+    let (_, type_name) = day_names(number)?;
+
+    Ok(TEMPLATE.replace("__TYPE__", &type_name))
+}
+
+/// Adds a day to the source of `lib.rs`: its module to the declarations in alphabetical order, and
+/// its solution to the `days!` registry in order of day.
+pub fn register_day(lib_source: &str, number: u32) -> Result<String, String> {
+    let (name, type_name) = day_names(number)?;
+
+    let lines: Vec<&str> = lib_source.lines().collect();
+    let declaration = format!("pub mod {};", name);
+    if lines.contains(&declaration.as_str()) {
+        return Err(format!("Module {} is already declared", name));
+    }
+
+    let start = lines
+        .iter()
+        .position(|l| *l == "days! {")
+        .ok_or("Could not find the days! registry in lib.rs")?;
+    let end = start
+        + lines[start..]
+            .iter()
+            .position(|l| *l == "}")
+            .ok_or("Could not find the end of the days! registry in lib.rs")?;
+
+    let mut insert_at = end;
+    for (i, line) in lines.iter().enumerate().take(end).skip(start + 1) {
+        let existing = line
+            .trim()
+            .split(' ')
+            .next()
+            .and_then(|n| n.parse::<u32>().ok())
+            .ok_or_else(|| format!("Unexpected line in the days! registry: \"{}\"", line))?;
+
+        if existing == number {
+            return Err(format!("Day {} is already registered", number));
+        }
+        if existing > number {
+            insert_at = i;
+            break;
+        }
+    }
+
+    let entry = format!("    {} => {}::Day{},", number, name, type_name);
+
+    // Before the first module that comes after it, or after the last module
+    let module_name = |line: &str| {
+        line.strip_prefix("pub mod ")
+            .and_then(|rest| rest.strip_suffix(';'))
+            .map(|rest| rest.to_string())
+    };
+    let modules: Vec<(usize, String)> = lines[..start]
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| module_name(line).map(|m| (i, m)))
+        .collect();
+    let declare_at = match modules.iter().find(|(_, m)| *m > name) {
+        Some((i, _)) => *i,
+        None => modules.last().map(|(i, _)| i + 1).unwrap_or(0),
+    };
+
+    let mut result: Vec<&str> = lines[..declare_at].to_vec();
+    result.push(&declaration);
+    result.extend_from_slice(&lines[declare_at..insert_at]);
+    result.push(&entry);
+    result.extend_from_slice(&lines[insert_at..]);
+
+    Ok(result.join("\n") + "\n")
+}
+
+/// Writes the module and an empty example input for a new day under `root`, and registers the day
+/// in `lib.rs`. Returns the paths of the files that were written.
+pub fn new_day(root: &Path, number: u32) -> Result<Vec<PathBuf>, String> {
+    let (name, _) = day_names(number)?;
+
+    let module_path = root.join("src").join(format!("{}.rs", name));
+    let example_path = root.join("inputs").join(format!("{}_example.txt", name));
+    let lib_path = root.join("src").join("lib.rs");
+
+    for path in [&module_path, &example_path] {
+        if path.exists() {
+            return Err(format!("{} already exists", path.display()));
+        }
+    }
+
+    let lib_source = fs::read_to_string(&lib_path)
+        .map_err(|e| format!("Could not read {}: {}", lib_path.display(), e))?;
+    let lib_source = register_day(&lib_source, number)?;
+
+    let write = |path: &Path, contents: &str| {
+        fs::write(path, contents).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    };
+    write(&module_path, &render_module(number)?)?;
+    write(&example_path, "")?;
+    write(&lib_path, &lib_source)?;
+
+    Ok(vec![module_path, example_path, lib_path])
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use crate::get_days;
+
+    #[test]
+    fn test_day_names() {
+        for day in get_days() {
+            let (name, _) = day_names(day.number).unwrap();
+            assert_eq!(day.name, name);
+        }
+
+        assert_eq!(
+            ("twentyfive".to_string(), "TwentyFive".to_string()),
+            day_names(25).unwrap()
+        );
+        assert!(day_names(26).is_err());
+    }
+
+    #[test]
+    fn test_register_day() {
+        let lib = "pub mod answer;\npub mod one;\npub mod three;\n\ndays! {\n    1 => one::DayOne,\n    3 => three::DayThree,\n}\n";
+
+        assert_eq!(
+            "pub mod answer;\npub mod one;\npub mod three;\npub mod two;\n\ndays! {\n    1 => one::DayOne,\n    2 => two::DayTwo,\n    3 => three::DayThree,\n}\n",
+            register_day(lib, 2).unwrap()
+        );
+        assert_eq!(
+            "pub mod answer;\npub mod four;\npub mod one;\npub mod three;\n\ndays! {\n    1 => one::DayOne,\n    3 => three::DayThree,\n    4 => four::DayFour,\n}\n",
+            register_day(lib, 4).unwrap()
+        );
+        assert!(register_day(lib, 3).is_err());
+    }
+
+    #[test]
+    fn test_render_module() {
+        let module = render_module(24).unwrap();
+
+        assert!(module.contains("pub struct DayTwentyFour;"));
+        assert!(!module.contains("mod tests"));
+        assert!(!module.contains("__"));
+    }
+}
@@ -0,0 +1,520 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Add;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often, in expanded states, a search reports its progress.
+const PROGRESS_INTERVAL: usize = 1000;
+
+/// A route found by a search, from a start state to a goal state.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Path<S, C> {
+    /// Every state along the route, including the start and the goal.
+    pub states: Vec<S>,
+    pub cost: C,
+}
+
+impl<S, C> Path<S, C> {
+    pub fn goal(&self) -> &S {
+        self.states.last().expect("a path has at least one state")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult<S, C> {
+    /// The cheapest path to a goal, or `None` if no goal could be reached.
+    pub path: Option<Path<S, C>>,
+    /// The number of states whose neighbours were generated.
+    pub expanded: usize,
+}
+
+/// Breadth-first search from any of the start states to the nearest goal, where every step costs
+/// one.
+pub fn bfs<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> SearchResult<S, usize>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut visited: HashSet<S> = HashSet::new();
+    let mut parents: HashMap<S, S> = HashMap::new();
+    let mut to_visit: VecDeque<(S, usize)> = VecDeque::new();
+
+    for start in starts {
+        if visited.insert(start.clone()) {
+            to_visit.push_back((start, 0));
+        }
+    }
+
+    let mut expanded = 0;
+    while let Some((state, steps)) = to_visit.pop_front() {
+        if is_goal(&state) {
+            return SearchResult {
+                path: Some(Path {
+                    states: reconstruct_path(&parents, state),
+                    cost: steps,
+                }),
+                expanded,
+            };
+        }
+
+        expanded += 1;
+        for neighbor in neighbors(&state) {
+            if visited.insert(neighbor.clone()) {
+                parents.insert(neighbor.clone(), state.clone());
+                to_visit.push_back((neighbor, steps + 1));
+            }
+        }
+    }
+
+    SearchResult {
+        path: None,
+        expanded,
+    }
+}
+
+/// Dijkstra's algorithm, for finding the cheapest path when steps have different (non-negative)
+/// costs.
+pub fn dijkstra<S, C, I>(
+    start: S,
+    neighbors: impl FnMut(&S) -> I,
+    is_goal: impl FnMut(&S) -> bool,
+) -> SearchResult<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    astar(start, neighbors, |_| C::default(), is_goal)
+}
+
+/// A* search, where `heuristic` estimates the remaining cost to a goal. The heuristic must never
+/// overestimate for the path found to be the cheapest.
+pub fn astar<S, C, I>(
+    start: S,
+    mut neighbors: impl FnMut(&S) -> I,
+    mut heuristic: impl FnMut(&S) -> C,
+    mut is_goal: impl FnMut(&S) -> bool,
+) -> SearchResult<S, C>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (S, C)>,
+{
+    let mut costs: HashMap<S, C> = HashMap::new();
+    let mut parents: HashMap<S, S> = HashMap::new();
+
+    // States are kept out of the queue so that they don't need to be ordered.
+    let mut states: Vec<S> = vec![];
+    let mut to_visit: BinaryHeap<Reverse<(C, C, usize)>> = BinaryHeap::new();
+
+    costs.insert(start.clone(), C::default());
+    to_visit.push(Reverse((heuristic(&start), C::default(), 0)));
+    states.push(start);
+
+    let mut expanded = 0;
+    while let Some(Reverse((_, cost, index))) = to_visit.pop() {
+        let state = states[index].clone();
+
+        // Skip states that were reached more cheaply after this entry was queued
+        if costs.get(&state).is_some_and(|best| *best < cost) {
+            continue;
+        }
+
+        if is_goal(&state) {
+            return SearchResult {
+                path: Some(Path {
+                    states: reconstruct_path(&parents, state),
+                    cost,
+                }),
+                expanded,
+            };
+        }
+
+        expanded += 1;
+        for (neighbor, step_cost) in neighbors(&state) {
+            let neighbor_cost = cost + step_cost;
+            if costs
+                .get(&neighbor)
+                .is_none_or(|best| neighbor_cost < *best)
+            {
+                costs.insert(neighbor.clone(), neighbor_cost);
+                parents.insert(neighbor.clone(), state.clone());
+
+                to_visit.push(Reverse((
+                    neighbor_cost + heuristic(&neighbor),
+                    neighbor_cost,
+                    states.len(),
+                )));
+                states.push(neighbor);
+            }
+        }
+    }
+
+    SearchResult {
+        path: None,
+        expanded,
+    }
+}
+
+/// Follows the parents of each state back from the goal to find the path to it.
+fn reconstruct_path<S: Clone + Eq + Hash>(parents: &HashMap<S, S>, goal: S) -> Vec<S> {
+    let mut path = vec![goal];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+
+    path
+}
+
+/// How far a long running search has got, as passed to the callback given to
+/// `Limits::with_progress`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Progress {
+    /// The number of states whose children were generated.
+    pub expanded: usize,
+    /// The number of states waiting to be explored.
+    pub frontier: usize,
+    /// The score of the best state found so far.
+    pub best: Option<i64>,
+}
+
+/// A callback given the progress of a search, see `Limits::with_progress`.
+pub type ProgressCallback = Arc<dyn Fn(&Progress) + Send + Sync>;
+
+/// How much work a search may do before it gives up and returns the best it has found so far, and
+/// where to report its progress. The default has no limits.
+///
+/// Clones share the same cancellation flag, so that a search can be stopped from another thread
+/// (or a Ctrl-C handler).
+#[derive(Clone, Default)]
+pub struct Limits {
+    /// When to stop, shared by every search run with these limits.
+    pub deadline: Option<Instant>,
+    /// The most states that each search may expand.
+    pub max_expanded: Option<usize>,
+    cancelled: Arc<AtomicBool>,
+    progress: Option<ProgressCallback>,
+}
+
+impl Limits {
+    /// Stops searching once `timeout` has passed from now.
+    pub fn with_timeout(mut self, timeout: Duration) -> Limits {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    pub fn with_max_expanded(mut self, max_expanded: usize) -> Limits {
+        self.max_expanded = Some(max_expanded);
+        self
+    }
+
+    pub fn with_progress(mut self, progress: impl Fn(&Progress) + Send + Sync + 'static) -> Limits {
+        self.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// Stops every search using these limits, or a clone of them, the next time it checks.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn exceeded(&self, expanded: usize) -> bool {
+        self.is_cancelled()
+            || self.max_expanded.is_some_and(|max| expanded >= max)
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    fn report(&self, progress: Progress) {
+        if let Some(report) = &self.progress {
+            report(&progress);
+        }
+    }
+}
+
+impl fmt::Debug for Limits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Limits")
+            .field("deadline", &self.deadline)
+            .field("max_expanded", &self.max_expanded)
+            .field("cancelled", &self.is_cancelled())
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// A value worked out by one or more searches, which is only known to be the best possible if none
+/// of them were stopped early by their `Limits`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Best<T> {
+    pub value: T,
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BranchAndBoundResult<S, C> {
+    /// The highest scoring state found and its score.
+    pub best: Option<(S, C)>,
+    /// The number of states whose children were generated.
+    pub expanded: usize,
+    /// False if the search was stopped by its limits before it could rule out every other state,
+    /// in which case `best` may not be the highest scoring state.
+    pub complete: bool,
+}
+
+/// Finds the highest scoring state reachable from `start`, exploring the states with the highest
+/// upper bounds first.
+///
+/// `upper_bound` must never be lower than the score of the state or of anything reachable from it,
+/// so that states that can't beat the best score found so far can be skipped.
+pub fn branch_and_bound<S, C, I>(
+    start: S,
+    children: impl FnMut(&S) -> I,
+    score: impl FnMut(&S) -> C,
+    upper_bound: impl FnMut(&S) -> C,
+) -> BranchAndBoundResult<S, C>
+where
+    S: Clone + Eq + Hash + Ord,
+    C: Copy + Ord + Into<i64>,
+    I: IntoIterator<Item = S>,
+{
+    branch_and_bound_with_limits(start, children, score, upper_bound, &Limits::default())
+}
+
+/// `branch_and_bound`, but stopping early with the best state found so far once the limits are
+/// reached, and reporting its progress as it goes.
+pub fn branch_and_bound_with_limits<S, C, I>(
+    start: S,
+    mut children: impl FnMut(&S) -> I,
+    mut score: impl FnMut(&S) -> C,
+    mut upper_bound: impl FnMut(&S) -> C,
+    limits: &Limits,
+) -> BranchAndBoundResult<S, C>
+where
+    S: Clone + Eq + Hash + Ord,
+    C: Copy + Ord + Into<i64>,
+    I: IntoIterator<Item = S>,
+{
+    let mut best: Option<(S, C)> = None;
+    let could_improve = |best: &Option<(S, C)>, bound: C| match best {
+        Some((_, best_score)) => bound > *best_score,
+        None => true,
+    };
+
+    let mut visited: HashSet<S> = HashSet::new();
+    let mut to_visit: BinaryHeap<(C, S)> = BinaryHeap::new();
+    to_visit.push((upper_bound(&start), start));
+
+    let progress = |best: &Option<(S, C)>, expanded: usize, frontier: usize| Progress {
+        expanded,
+        frontier,
+        best: best.as_ref().map(|(_, score)| (*score).into()),
+    };
+
+    let mut expanded = 0;
+    let mut complete = true;
+    while let Some((bound, state)) = to_visit.pop() {
+        if !visited.insert(state.clone()) || !could_improve(&best, bound) {
+            continue;
+        }
+
+        // The first state is always scored, so that there is something to return
+        if expanded > 0 && limits.exceeded(expanded) {
+            complete = false;
+            break;
+        }
+        if expanded % PROGRESS_INTERVAL == 0 {
+            limits.report(progress(&best, expanded, to_visit.len()));
+        }
+
+        let state_score = score(&state);
+        if could_improve(&best, state_score) {
+            best = Some((state.clone(), state_score));
+        }
+
+        expanded += 1;
+        for child in children(&state) {
+            if visited.contains(&child) {
+                continue;
+            }
+
+            let child_bound = upper_bound(&child);
+            if could_improve(&best, child_bound) {
+                to_visit.push((child_bound, child));
+            }
+        }
+    }
+
+    limits.report(progress(&best, expanded, to_visit.len()));
+
+    BranchAndBoundResult {
+        best,
+        expanded,
+        complete,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use crate::grid::{Grid, Position};
+
+    fn maze() -> Grid<char> {
+        let lines = ["S.#.....", "..#.##.#", "..#..#..", "....#..E"];
+
+        Grid::parse(&lines, "a maze", Some).unwrap()
+    }
+
+    fn open_neighbors(maze: &Grid<char>, position: &Position) -> Vec<Position> {
+        maze.neighbors4(position)
+            .filter(|p| maze.get(p) != Some(&'#'))
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let maze = maze();
+        let end = Position::new(3, 7);
+
+        let result = bfs(
+            vec![Position::new(0, 0)],
+            |p| open_neighbors(&maze, p),
+            |p| *p == end,
+        );
+        let path = result.path.unwrap();
+
+        assert_eq!(16, path.cost);
+        assert_eq!(17, path.states.len());
+        assert_eq!(Position::new(0, 0), path.states[0]);
+        assert_eq!(end, *path.goal());
+        assert!(path
+            .states
+            .windows(2)
+            .all(|w| w[0].manhattan_distance(&w[1]) == 1));
+        assert!(result.expanded > 0);
+
+        let unreachable = bfs(
+            vec![Position::new(0, 0)],
+            |p| open_neighbors(&maze, p),
+            |p| *p == Position::new(0, 2),
+        );
+        assert!(unreachable.path.is_none());
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar() {
+        // Stepping onto a '.' costs 1, but the maze is slower to walk through in the bottom row
+        let maze = maze();
+        let end = Position::new(3, 7);
+        let neighbors = |p: &Position| -> Vec<(Position, i32)> {
+            open_neighbors(&maze, p)
+                .into_iter()
+                .map(|n| (n, if n.row == 3 { 5 } else { 1 }))
+                .collect()
+        };
+
+        let result = dijkstra(Position::new(0, 0), neighbors, |p| *p == end);
+        let path = result.path.unwrap();
+        assert_eq!(32, path.cost);
+
+        let guided = astar(
+            Position::new(0, 0),
+            neighbors,
+            |p| p.manhattan_distance(&end),
+            |p| *p == end,
+        );
+        assert_eq!(32, guided.path.unwrap().cost);
+        assert!(guided.expanded <= result.expanded);
+    }
+
+    /// Picks items with (weight, value) to get the most value within a weight limit of 10.
+    fn knapsack(limits: &Limits) -> BranchAndBoundResult<Vec<usize>, i32> {
+        let items = [(5, 10), (4, 40), (6, 30), (3, 50)];
+        let weight = |chosen: &Vec<usize>| chosen.iter().map(|i| items[*i].0).sum::<i32>();
+        let value = |chosen: &Vec<usize>| chosen.iter().map(|i| items[*i].1).sum::<i32>();
+
+        branch_and_bound_with_limits(
+            vec![],
+            |chosen: &Vec<usize>| {
+                let next = chosen.last().map(|i| i + 1).unwrap_or(0);
+                (next..items.len())
+                    .map(|i| {
+                        let mut child = chosen.clone();
+                        child.push(i);
+                        child
+                    })
+                    .filter(|child| weight(child) <= 10)
+                    .collect::<Vec<_>>()
+            },
+            value,
+            |chosen| {
+                let next = chosen.last().map(|i| i + 1).unwrap_or(0);
+                value(chosen) + items[next..].iter().map(|item| item.1).sum::<i32>()
+            },
+            limits,
+        )
+    }
+
+    #[test]
+    fn test_branch_and_bound() {
+        let result = knapsack(&Limits::default());
+
+        assert_eq!(Some((vec![1, 3], 90)), result.best);
+        assert!(result.complete);
+    }
+
+    #[test]
+    fn test_branch_and_bound_limits() {
+        let result = knapsack(&Limits::default().with_max_expanded(2));
+        assert_eq!(2, result.expanded);
+        assert!(!result.complete);
+        assert!(result.best.is_some());
+
+        let cancelled = Limits::default();
+        cancelled.clone().cancel();
+        let result = knapsack(&cancelled);
+        assert_eq!(Some((vec![], 0)), result.best);
+        assert!(!result.complete);
+
+        let result = knapsack(&Limits::default().with_timeout(Duration::ZERO));
+        assert!(!result.complete);
+    }
+
+    #[test]
+    fn test_branch_and_bound_progress() {
+        let reports = Arc::new(std::sync::Mutex::new(vec![]));
+        let limits = {
+            let reports = reports.clone();
+            Limits::default().with_progress(move |p| reports.lock().unwrap().push(*p))
+        };
+
+        let result = knapsack(&limits);
+        let reports = reports.lock().unwrap();
+
+        assert_eq!(0, reports[0].expanded);
+        assert_eq!(
+            Progress {
+                expanded: result.expanded,
+                frontier: 0,
+                best: Some(90),
+            },
+            *reports.last().unwrap()
+        );
+    }
+}
@@ -1,22 +1,36 @@
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_total_size(&values);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DaySeven;
+
+impl Solution for DaySeven {
+    type Input = Vec<Command>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_smallest_to_remove(&values);
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_total_size(input).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_smallest_to_remove(input).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
 #[derive(Debug)]
-enum Command {
+pub enum Command {
     CdUp(),
     CdRoot(),
     Cd(String),
@@ -24,50 +38,59 @@ enum Command {
 }
 
 impl Command {
-    fn from_lines(lines: &[String]) -> Command {
-        match lines.first().unwrap().as_str() {
-            "$ cd /" => return Command::CdRoot(),
-            "$ cd .." => return Command::CdUp(),
-            _ => (),
+    /// Parses a command and its output, which start on line `first_line` of the input.
+    fn from_lines(lines: &[String], first_line: usize) -> Result<Command, ParseError> {
+        let command = lines.first().map(|l| l.as_str()).unwrap_or("");
+        match command {
+            "$ cd /" => return Ok(Command::CdRoot()),
+            "$ cd .." => return Ok(Command::CdUp()),
+            "$ ls" => (),
+            _ if command.starts_with("$ cd ") => {
+                return Ok(Command::Cd(command["$ cd ".len()..].to_string()))
+            }
+            _ => {
+                return Err(
+                    ParseError::new(command, "a command (\"$ cd <dir>\" or \"$ ls\")")
+                        .on_line(first_line),
+                )
+            }
         }
 
-        if lines.first().unwrap().starts_with("$ cd ") {
-            let line = lines.first().unwrap();
-
-            return Command::Cd(line.split("cd ").last().unwrap().to_string());
+        let mut objects = vec![];
+        for (i, line) in lines.iter().enumerate().skip(1) {
+            objects.push(Object::from_line(line).map_err(|e| e.on_line(first_line + i))?);
         }
 
-        Command::Ls(lines.iter().skip(1).map(|l| Object::from_line(l)).collect())
+        Ok(Command::Ls(objects))
     }
 }
 
 #[derive(Debug)]
-enum Object {
+pub enum Object {
     File(String, i32),
     Directory(String),
 }
 
 impl Object {
-    fn from_line(line: &str) -> Object {
-        if line.starts_with("dir") {
-            Object::Directory(
-                line.split(' ')
-                    .collect::<Vec<&str>>()
-                    .get(1)
-                    .unwrap()
-                    .to_string(),
+    fn from_line(line: &str) -> Result<Object, ParseError> {
+        let (first, name) = line.split_once(' ').ok_or_else(|| {
+            ParseError::new(
+                line,
+                "a directory or file, such as \"dir a\" or \"14848514 b.txt\"",
             )
-        } else {
-            let parts = line.split(' ').collect::<Vec<&str>>();
+        })?;
 
-            let name = parts.last().unwrap().to_string();
-            let size = parts.first().unwrap().parse().unwrap();
+        if first == "dir" {
+            Ok(Object::Directory(name.to_string()))
+        } else {
+            let size = parse_number(first, "a file size")?;
 
-            Object::File(name, size)
+            Ok(Object::File(name.to_string(), size))
         }
     }
 }
 
+#[allow(dead_code)] // File names are only kept for debug output
 #[derive(Debug)]
 enum Structure {
     File(String, i32),
@@ -169,37 +192,39 @@ impl Structure {
     }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Command> {
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Command>, ParseError> {
     let mut commands: Vec<Command> = Vec::new();
     let mut buffer: Vec<String> = Vec::new();
 
     let mut seen_any = false;
-    for line in reader.lines() {
-        let line = line.unwrap();
+    let mut buffer_start = 1;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
 
         if line.starts_with('$') && seen_any {
-            commands.push(Command::from_lines(&buffer));
+            commands.push(Command::from_lines(&buffer, buffer_start)?);
             buffer.clear();
+            buffer_start = i + 1;
         }
 
         buffer.push(line.to_string());
         seen_any = true;
     }
 
-    commands.push(Command::from_lines(&buffer));
+    commands.push(Command::from_lines(&buffer, buffer_start)?);
 
-    commands
+    Ok(commands)
 }
 
-fn find_total_size(commands: &[Command]) -> i32 {
+/// The total size of the directories with a size of less than 100000.
+pub fn find_total_size(commands: &[Command]) -> i32 {
     let structure = Structure::from_commands(commands);
 
     let mut dirs_to_check = Vec::new();
     dirs_to_check.push(&structure);
 
     let mut total = 0;
-    while !dirs_to_check.is_empty() {
-        let dir = dirs_to_check.pop().unwrap();
+    while let Some(dir) = dirs_to_check.pop() {
         let size = dir.get_size();
 
         if size < 100000 {
@@ -215,15 +240,15 @@ fn find_total_size(commands: &[Command]) -> i32 {
     total
 }
 
-fn find_smallest_to_remove(commands: &[Command]) -> i32 {
+/// The size of the smallest directory that frees up enough space for the update.
+pub fn find_smallest_to_remove(commands: &[Command]) -> i32 {
     let structure = Structure::from_commands(commands);
 
     let mut dirs_to_check = Vec::new();
     dirs_to_check.push(&structure);
 
     let mut smallest_to_remove = 999999999;
-    while !dirs_to_check.is_empty() {
-        let dir = dirs_to_check.pop().unwrap();
+    while let Some(dir) = dirs_to_check.pop() {
         let size = dir.get_size();
 
         if size >= (structure.get_size() - 40000000) && size < smallest_to_remove {
@@ -239,56 +264,88 @@ fn find_smallest_to_remove(commands: &[Command]) -> i32 {
     smallest_to_remove
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/seven_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+/// Generates a terminal session exploring a filesystem of `size` files and directories.
+///
+/// File sizes are spread over several orders of magnitude, then scaled so that the disk is between
+/// 41000000 and 69000000 full, leaving part two something to free up.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    struct Dir {
+        dirs: Vec<(String, Dir)>,
+        files: Vec<(String, i64)>,
+    }
 
-        let expected = 95437;
-        let actual = find_total_size(&values);
+    impl Dir {
+        fn count(&self) -> usize {
+            self.files.len() + self.dirs.iter().map(|(_, d)| 1 + d.count()).sum::<usize>()
+        }
 
-        assert_eq!(expected, actual)
-    }
+        fn total_size(&self) -> i64 {
+            self.files.iter().map(|(_, s)| s).sum::<i64>()
+                + self.dirs.iter().map(|(_, d)| d.total_size()).sum::<i64>()
+        }
 
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/seven.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+        fn has_name(&self, name: &str) -> bool {
+            self.dirs.iter().any(|(n, _)| n == name) || self.files.iter().any(|(n, _)| n == name)
+        }
 
-        let expected = 1447046;
-        let actual = find_total_size(&values);
+        /// Adds an entry to this directory or one of its subdirectories.
+        fn add(&mut self, rng: &mut Rng) {
+            if !self.dirs.is_empty() && rng.chance(0.6) {
+                let i = rng.below(self.dirs.len());
+                return self.dirs[i].1.add(rng);
+            }
 
-        assert_eq!(expected, actual)
-    }
+            let mut name = rng.word(1, 8);
+            if rng.chance(0.5) {
+                name = format!("{}.{}", name, rng.word(1, 3));
+            }
+            if self.has_name(&name) {
+                return;
+            }
 
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/seven_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+            if rng.chance(0.3) {
+                let dir = Dir {
+                    dirs: vec![],
+                    files: vec![],
+                };
+                self.dirs.push((name, dir));
+            } else {
+                let magnitude = 10i64.pow(rng.between(1, 5) as u32);
+                self.files.push((name, rng.between(1, magnitude)));
+            }
+        }
 
-        let expected = 24933642;
-        let actual = find_smallest_to_remove(&values);
+        /// Writes the commands that explore this directory, with file sizes multiplied by `scale`.
+        fn write(&self, session: &mut String, scale: f64) {
+            session.push_str("$ ls\n");
+            for (name, _) in self.dirs.iter() {
+                session.push_str(&format!("dir {}\n", name));
+            }
+            for (name, file_size) in self.files.iter() {
+                let file_size = ((*file_size as f64 * scale) as i64).max(1);
+                session.push_str(&format!("{} {}\n", file_size, name));
+            }
 
-        assert_eq!(expected, actual)
+            for (name, dir) in self.dirs.iter() {
+                session.push_str(&format!("$ cd {}\n", name));
+                dir.write(session, scale);
+                session.push_str("$ cd ..\n");
+            }
+        }
     }
 
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/seven.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    let mut root = Dir {
+        dirs: vec![],
+        files: vec![],
+    };
+    while root.count() < size.max(1) {
+        root.add(rng);
+    }
 
-        let expected = 578710;
-        let actual = find_smallest_to_remove(&values);
+    let used = rng.between(41000000, 69000000) as f64;
+    let scale = used / root.total_size().max(1) as f64;
 
-        assert_eq!(expected, actual)
-    }
+    let mut session = "$ cd /\n".to_string();
+    root.write(&mut session, scale);
+    session
 }
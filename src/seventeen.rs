@@ -1,27 +1,75 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeSet, HashSet};
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+use std::iter;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_tower_height(&values, 2022);
+use log::trace;
 
-    println!("{}", answer);
-}
+use crate::answer::Answer;
+use crate::cycle;
+use crate::grid::{self, Direction};
+use crate::params::{Param, Params};
+use crate::parse::{read_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Part, Solution, SolveError};
+use crate::visualize::{Frame, Frames};
+
+pub struct DaySeventeen;
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_tower_height(&values, 1000000000000);
+impl Solution for DaySeventeen {
+    type Input = JetPattern;
 
-    println!("{}", answer);
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::integer("part_one_rocks", 2022, "Rocks to drop in part one")
+                .with_range(0..=i64::MAX),
+            Param::integer("part_two_rocks", 1000000000000, "Rocks to drop in part two")
+                .with_range(0..=i64::MAX),
+        ]
+    }
+
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_tower_height(input, params.integer("part_one_rocks")).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_tower_height(input, params.integer("part_two_rocks")).into())
+    }
+
+    fn frames(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        let num_rocks = match part {
+            Part::One => params.integer("part_one_rocks"),
+            Part::Two => params.integer("part_two_rocks"),
+        };
+
+        Ok(Some(tower_frames(input, num_rocks)))
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> JetPattern {
-    let line = reader.lines().next().unwrap().unwrap();
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<JetPattern, ParseError> {
+    let lines = read_lines(reader)?;
+    let line = lines.first().map(|l| l.as_str()).unwrap_or("");
 
-    JetPattern::from_str(&line)
+    JetPattern::from_str(line).map_err(|e| e.on_line(1))
 }
 
+/// How far below the top of the tower a fingerprint looks. Anything deeper is taken to no longer
+/// affect where rocks come to rest.
+const FINGERPRINT_ROWS: i64 = 100;
+
 #[derive(Hash, Eq, PartialEq, Debug, Clone)]
 struct Fingerprint {
     pattern_modulo: i64,
@@ -30,44 +78,43 @@ struct Fingerprint {
 }
 
 impl Fingerprint {
-    fn new(grid: &Grid, j: i64, jet_pattern: &JetPattern) -> Fingerprint {
+    fn new(tower: &Tower, jet_pattern: &JetPattern) -> Fingerprint {
         Fingerprint {
-            pattern_modulo: j % 5,
-            direction_modulo: j % jet_pattern.directions.len() as i64,
-            tiles_pattern: grid.get_hashable_chunk(),
+            pattern_modulo: tower.num_rocks % 5,
+            direction_modulo: tower.num_jets % jet_pattern.directions.len() as i64,
+            tiles_pattern: tower.grid.get_hashable_chunk(),
         }
     }
 }
 
-#[derive(Debug, Clone)]
-struct Occurance {
-    i: i64,
-    j: i64,
-    height: i64,
+struct Tower {
+    grid: Grid,
+    num_rocks: i64,
+    num_jets: i64,
 }
 
-fn find_tower_height(jet_pattern: &JetPattern, num_rocks: i64) -> i64 {
-    let mut grid = Grid::new();
-
-    let mut fingerprints: HashMap<Fingerprint, Vec<Occurance>> = HashMap::new();
-
-    let mut calculated_offset = 0;
+impl Tower {
+    fn new() -> Tower {
+        Tower {
+            grid: Grid::new(),
+            num_rocks: 0,
+            num_jets: 0,
+        }
+    }
 
-    let mut j = 0;
-    let mut i = -1;
-    while i < num_rocks - 1 {
-        // Weird check so that we can adjust i as needed in loop
-        i += 1; // maybe less error prone than making sure to increment at end
+    /// Drops the next rock, returning it and the position it came to rest at.
+    fn drop_rock(&mut self, jet_pattern: &JetPattern) -> (RockPattern, Position) {
+        let grid = &mut self.grid;
         let mut position = Position::new(grid.get_heighest_row() + 4, 4);
-        let rock_pattern = RockPattern::from_round_number(i);
+        let rock_pattern = RockPattern::from_round_number(self.num_rocks);
 
-        //grid.print(&rock_pattern, &position);
-        //println!("");
+        trace!("\n{}", grid.render(&rock_pattern, &position));
 
         loop {
-            let direction = jet_pattern.get_direction(j);
-            let position_lr = position.moved(0, direction.to_column_offset());
+            let direction = jet_pattern.get_direction(self.num_jets);
+            self.num_jets += 1;
 
+            let position_lr = position.step(direction, 1);
             if grid.hits_anything(&rock_pattern, &position_lr).is_none() {
                 position = position_lr;
             }
@@ -77,43 +124,68 @@ fn find_tower_height(jet_pattern: &JetPattern, num_rocks: i64) -> i64 {
                 for p in rock_pattern.all_positions(&position) {
                     grid.set(&p);
                 }
-                j += 1;
                 break;
             }
 
             position = position_down;
 
-            //grid.print(&rock_pattern, &position);
-            //println!("");
-
-            j += 1;
+            trace!("\n{}", grid.render(&rock_pattern, &position));
         }
 
-        let fingerprint = Fingerprint::new(&grid, j, jet_pattern);
-        let height = grid.get_heighest_row();
-        let occurance = Occurance { i, j, height };
-        if fingerprints.contains_key(&fingerprint) {
-            let prev_occurance = fingerprints.get(&fingerprint).unwrap()[0].clone();
+        self.num_rocks += 1;
 
-            let i_diff = occurance.i - prev_occurance.i;
-            let j_diff = occurance.j - prev_occurance.j;
-            let height_diff = occurance.height - prev_occurance.height;
+        (rock_pattern, position)
+    }
+}
 
-            let remaining_rocks = (num_rocks - 1) - i;
-            let num_remaining_recurrances = remaining_rocks / i_diff;
+/// The height of the tower once `num_rocks` rocks have come to rest.
+pub fn find_tower_height(jet_pattern: &JetPattern, num_rocks: i64) -> i64 {
+    let tower = Tower::new();
+
+    // The tower grows by the same amount each time the top of it and the next rock and jet repeat
+    cycle::extrapolate(
+        tower,
+        num_rocks as u64,
+        |mut tower, _| {
+            tower.drop_rock(jet_pattern);
+            tower
+        },
+        |tower| Fingerprint::new(tower, jet_pattern),
+        |tower| tower.grid.get_heighest_row(),
+    )
+}
 
-            i += num_remaining_recurrances * i_diff;
-            j += num_remaining_recurrances * j_diff;
+/// The number of rows from the top of the tower that are drawn in each frame.
+const FRAME_ROWS: usize = 40;
 
-            calculated_offset += height_diff * num_remaining_recurrances;
-        } else {
-            fingerprints.insert(fingerprint.clone(), vec![]);
+/// Drops the rocks one at a time, drawing the top of the tower with the rock that just landed.
+fn tower_frames(jet_pattern: &JetPattern, num_rocks: i64) -> Frames {
+    let jet_pattern = jet_pattern.clone();
+    let mut tower = Tower::new();
+
+    Box::new(iter::from_fn(move || {
+        if tower.num_rocks >= num_rocks {
+            return None;
         }
 
-        fingerprints.get_mut(&fingerprint).unwrap().push(occurance);
-    }
+        let (rock_pattern, position) = tower.drop_rock(&jet_pattern);
+        let picture = tower
+            .grid
+            .render(&rock_pattern, &position)
+            .lines()
+            .take(FRAME_ROWS)
+            .map(|line| line.to_string() + "\n")
+            .collect();
 
-    grid.get_heighest_row() + calculated_offset
+        Some(Frame::new(
+            format!(
+                "Rock {} landed, the tower is {} high",
+                tower.num_rocks,
+                tower.grid.get_heighest_row()
+            ),
+            picture,
+        ))
+    }))
 }
 
 struct RockPattern {
@@ -213,14 +285,17 @@ impl RockPattern {
 }
 
 #[derive(Eq, PartialEq, Hash, Debug, Clone)]
-struct JetPattern {
-    directions: Vec<Direction>,
+pub struct JetPattern {
+    pub directions: Vec<Direction>,
 }
 
 impl JetPattern {
-    fn from_str(s: &str) -> JetPattern {
-        JetPattern {
-            directions: s.chars().map(Direction::from_char).collect(),
+    fn from_str(s: &str) -> Result<JetPattern, ParseError> {
+        let directions: Option<Vec<Direction>> = s.chars().map(parse_jet).collect();
+
+        match directions {
+            Some(directions) if !directions.is_empty() => Ok(JetPattern { directions }),
+            _ => Err(ParseError::new(s, "a pattern of jets (< and >)")),
         }
     }
 
@@ -229,47 +304,16 @@ impl JetPattern {
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Debug, Clone, Copy)]
-enum Direction {
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn from_char(c: char) -> Direction {
-        match c {
-            '<' => Direction::Left,
-            '>' => Direction::Right,
-            _ => panic!(),
-        }
-    }
-
-    fn to_column_offset(self) -> i64 {
-        match self {
-            Direction::Left => -1,
-            Direction::Right => 1,
-        }
+fn parse_jet(c: char) -> Option<Direction> {
+    match c {
+        '<' => Some(Direction::West),
+        '>' => Some(Direction::East),
+        _ => None,
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Debug, Clone, Ord, PartialOrd)]
-struct Position {
-    row: i64,
-    column: i64,
-}
-
-impl Position {
-    fn new(r: i64, c: i64) -> Position {
-        Position { row: r, column: c }
-    }
-
-    fn moved(&self, row_change: i64, column_change: i64) -> Position {
-        let new_row = row_change + self.row;
-        let new_column = column_change + self.column;
-
-        Position::new(new_row, new_column)
-    }
-}
+/// Rows count upwards from the floor in this puzzle, so that the tower grows to higher rows.
+type Position = grid::Position<i64>;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, Hash)]
 enum HitType {
@@ -309,7 +353,7 @@ impl Grid {
     }
 
     fn set(&mut self, position: &Position) {
-        self.tiles.insert(position.clone());
+        self.tiles.insert(*position);
     }
 
     fn is_in_wall(&self, position: &Position) -> bool {
@@ -372,13 +416,16 @@ impl Grid {
 
     fn get_hashable_chunk(&self) -> BTreeSet<Position> {
         let highest_row = self.get_heighest_row();
+        // A column that never gets filled, such as when every jet pushes the same way, would
+        // otherwise make the chunk the whole tower, which never repeats
+        let deepest_row = (highest_row - FINGERPRINT_ROWS).max(self.bottom_row);
 
         let mut lower_row = highest_row;
         for column in self.left_wall + 1..self.right_wall {
             let mut row = highest_row;
             loop {
                 let position = Position::new(row, column);
-                if row == self.bottom_row || self.get(&position) == TileState::Filled {
+                if row == deepest_row || self.get(&position) == TileState::Filled {
                     break;
                 }
 
@@ -395,35 +442,39 @@ impl Grid {
             .collect()
     }
 
-    /*fn print(&self, rock_pattern: &RockPattern, position: &Position) {
+    /// Draws the tower with the falling rock, from the top down to the floor.
+    fn render(&self, rock_pattern: &RockPattern, position: &Position) -> String {
         let all_pattern_positions = rock_pattern.all_positions(position);
 
+        let mut rendered = String::new();
         for row in (self.bottom_row + 1..self.get_heighest_row() + 3 + 4 + 1).rev() {
-            print!("|");
+            rendered.push('|');
             for column in self.left_wall + 1..self.right_wall {
                 let p = Position::new(row, column);
-                print!(
-                    "{}",
-                    match self.tiles.get(&p) {
-                        Some(_) => '#',
-                        None => {
-                            if all_pattern_positions.contains(&p) {
-                                '@'
-                            } else {
-                                '.'
-                            }
-                        }
-                    }
-                );
+                rendered.push(if all_pattern_positions.contains(&p) {
+                    '@'
+                } else if self.tiles.contains(&p) {
+                    '#'
+                } else {
+                    '.'
+                });
             }
-            println!("|");
+            rendered.push_str("|\n");
         }
 
         for _ in self.left_wall..self.right_wall + 1 {
-            print!("-");
+            rendered.push('-');
         }
-        println!();
-    }*/
+
+        rendered
+    }
+}
+
+/// Generates a jet pattern of `size` pushes.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut jets: String = (0..size.max(1)).map(|_| *rng.choose(&['<', '>'])).collect();
+    jets.push('\n');
+    jets
 }
 
 #[cfg(test)]
@@ -431,51 +482,38 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/seventeen_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3068;
-        let actual = find_tower_height(&values, 2022);
+    /// Drops every rock, returning the height of the tower after each one.
+    fn simulate_tower_heights(jet_pattern: &JetPattern, num_rocks: i64) -> Vec<i64> {
+        let mut tower = Tower::new();
 
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/seventeen.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3177;
-        let actual = find_tower_height(&values, 2022);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/seventeen_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 1514285714288;
-        let actual = find_tower_height(&values, 1000000000000);
-
-        assert_eq!(expected, actual)
+        (0..num_rocks)
+            .map(|_| {
+                tower.drop_rock(jet_pattern);
+                tower.grid.get_heighest_row()
+            })
+            .collect()
     }
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/seventeen.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 1565517241382;
-        let actual = find_tower_height(&values, 1000000000000);
-
-        assert_eq!(expected, actual)
+    fn test_tower_height_matches_simulation() {
+        for seed in 0..10 {
+            let mut rng = Rng::new(seed);
+            // Short patterns repeat within a few hundred rocks, so the extrapolation gets used
+            let size = rng.between(1, 30) as usize;
+            let input = generate(&mut rng, size);
+            let jet_pattern = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
+
+            let heights = simulate_tower_heights(&jet_pattern, 1000);
+            for num_rocks in [1, rng.between(2, 999), 1000] {
+                let expected = heights[num_rocks as usize - 1];
+                let actual = find_tower_height(&jet_pattern, num_rocks);
+
+                assert_eq!(
+                    expected, actual,
+                    "seed {}, {} rocks: {}",
+                    seed, num_rocks, input
+                );
+            }
+        }
     }
 }
@@ -1,31 +1,57 @@
 use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_start_marker(&values[0]);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{read_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DaySix;
+
+impl Solution for DaySix {
+    type Input = Vec<String>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_start_marker_2(&values[0]);
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_start_marker(&input[0])
+            .expect("read_input checks for a start-of-packet marker")
+            .into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        find_start_marker_2(&input[0])
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::new("The datastream has no start-of-message marker"))
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<String> {
-    let mut numbers: Vec<String> = Vec::new();
-    for line in reader.lines() {
-        numbers.push(line.unwrap().to_string());
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<String>, ParseError> {
+    let lines = read_lines(reader)?;
+    if lines.is_empty() {
+        return Err(ParseError::new("", "a datastream buffer"));
+    }
+    if find_start_marker(&lines[0]).is_none() {
+        return Err(ParseError::new(
+            &lines[0],
+            "a datastream buffer with a start-of-packet marker",
+        )
+        .on_line(1));
     }
 
-    numbers
+    Ok(lines)
 }
 
-fn find_start_marker(values: &str) -> i32 {
+/// Where the first start-of-packet marker (four different characters) ends, if there is one.
+pub fn find_start_marker(values: &str) -> Option<i32> {
     let mut buffer = Vec::new();
     for (i, c) in values.chars().enumerate() {
         if buffer.len() == 4 {
@@ -38,14 +64,15 @@ fn find_start_marker(values: &str) -> i32 {
 
         let unique_chars: HashSet<char> = buffer.iter().cloned().collect();
         if unique_chars.len() == 4 {
-            return i as i32 + 1;
+            return Some(i as i32 + 1);
         }
     }
 
-    panic!()
+    None
 }
 
-fn find_start_marker_2(values: &str) -> i32 {
+/// Where the first start-of-message marker (fourteen different characters) ends, if there is one.
+pub fn find_start_marker_2(values: &str) -> Option<i32> {
     let mut buffer = Vec::new();
     for (i, c) in values.chars().enumerate() {
         if buffer.len() == 14 {
@@ -58,63 +85,46 @@ fn find_start_marker_2(values: &str) -> i32 {
 
         let unique_chars: HashSet<char> = buffer.iter().cloned().collect();
         if unique_chars.len() == 14 {
-            return i as i32 + 1;
+            return Some(i as i32 + 1);
         }
     }
 
-    panic!()
+    None
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
+/// Generates a datastream of `size` characters (at least 14), which has a start-of-message marker
+/// somewhere in it.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let len = size.max(14);
+    // A small alphabet makes markers rare, so that they aren't always found straight away
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    let common = &alphabet[..rng.between(3, 10) as usize];
 
-        assert_eq!(expected, actual)
-    }
+    let mut datastream: Vec<char> = (0..len).map(|_| *rng.choose(common)).collect();
 
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    let mut marker = alphabet;
+    rng.shuffle(&mut marker);
+    let start = rng.below(len - 13);
+    datastream[start..start + 14].copy_from_slice(&marker[..14]);
 
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
+    let mut datastream: String = datastream.into_iter().collect();
+    datastream.push('\n');
+    datastream
+}
 
-        assert_eq!(expected, actual)
-    }
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
-
-        assert_eq!(expected, actual)
+    fn test_no_marker() {
+        let error = read_input(&mut BufReader::new("abcabc\n".as_bytes())).unwrap_err();
+        assert_eq!(Some(1), error.line);
+
+        let input = read_input(&mut BufReader::new("abcd\n".as_bytes())).unwrap();
+        let params = Params::defaults(&[], false);
+        assert_eq!(Ok(Answer::Integer(4)), DaySix.part_one(&input, &params));
+        assert!(DaySix.part_two(&input, &params).is_err());
     }
 }
@@ -1,294 +1,201 @@
-use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_max_pressure(&values);
+use log::{debug, trace};
 
-    println!("{}", answer);
-}
+use crate::answer::Answer;
+use crate::params::{Param, Params};
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::search::{self, Best, Limits};
+use crate::solution::{Solution, SolveError};
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_max_pressure_2(&values);
+pub struct DaySixteen;
 
-    println!("{}", answer);
-}
+impl Solution for DaySixteen {
+    type Input = HashMap<String, Valve>;
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> HashMap<String, Valve> {
-    let mut valves: HashMap<String, Valve> = HashMap::new();
-    for line in reader.lines() {
-        let valve = Valve::from_str(&line.unwrap());
-        //println!("{valve:?}");
-        valves.insert(valve.name.clone(), valve);
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
     }
 
-    valves
-}
-
-fn find_max_pressure_2(valves: &HashMap<String, Valve>) -> i32 {
-    let meta_graph = MetaGraph::from_valves(valves);
-    println!("{meta_graph:?}");
-
-    let start = "AA".to_string();
-    let all_nodes: Vec<String> = valves.keys().cloned().collect();
-    let start_time = 26;
-
-    let starting_state = SearchState::new(2);
-
-    let mut states_to_try: BinaryHeap<SearchStateWrapper> = BinaryHeap::new();
-    states_to_try.push(SearchStateWrapper::from_state(
-        &starting_state,
-        &start,
-        start_time,
-        &meta_graph,
-        valves,
-    ));
-
-    let mut tried_states: BTreeSet<SearchState> = BTreeSet::new();
-
-    let mut elimination_times: BTreeMap<i32, i32> = BTreeMap::new();
-
-    let mut max_score = 0;
-    let mut i: i32 = 0;
-    let _max_changed = false;
-    while !states_to_try.is_empty() {
-        i += 1;
-
-        /*if i % 1000 == 0 {
-            println!("Cleaned!")
-            max_changed = false;
-        }*/
-
-        //let state = states_to_try.iter().next().unwrap().clone();
-        let state = states_to_try.pop().unwrap().search_state;
-
-        //println!("trying: {state:?}");
-
-        if tried_states.contains(&state) {
-            //println!("already_visited");
-            continue;
-        }
-        tried_states.insert(state.clone());
-
-        let (state_result, actions) = state.to_game_state(&start, start_time, &meta_graph, valves);
-        //println!("\t{state_result:?}");
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::text("start", "AA", "Valve to start at"),
+            Param::integer(
+                "part_one_minutes",
+                30,
+                "Minutes until the volcano erupts in part one",
+            )
+            .with_range(0..=i32::MAX as i64),
+            Param::integer(
+                "part_two_minutes",
+                26,
+                "Minutes left after teaching the elephant in part two",
+            )
+            .with_range(0..=i32::MAX as i64),
+        ]
+    }
 
-        if state_result.time_remaining[0] < 0 || state_result.time_remaining[1] < 0 {
-            //println!("too long");
-            continue;
-        }
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        let start = check_start(input, params.text("start"))?;
 
-        if state_result.best_case_score(valves) < max_score {
-            //println!("best score is too low");
-            //elimination_times.get_mut()
-            *elimination_times
-                .entry(state_result.time_remaining[0] + state_result.time_remaining[1])
-                .or_default() += 1;
-            continue;
-        }
-
-        if state_result.score > max_score {
-            println!("-------------------");
-            println!("{state:?}");
-            println!("{actions:?}");
-            println!("{state_result:?}");
-            println!("{i}");
-
-            //max_changed = true;
-            states_to_try = states_to_try
-                .into_iter()
-                .filter(|s| s.base_case_score >= max_score)
-                .collect();
-
-            for (time_sum, count) in elimination_times.iter() {
-                println!("{time_sum}\t{count}");
-            }
+        Ok(find_max_pressure(
+            input,
+            start,
+            params.integer("part_one_minutes") as i32,
+            params.limits(),
+        )
+        .into())
+    }
 
-            max_score = state_result.score;
-        }
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        let start = check_start(input, params.text("start"))?;
 
-        for node in state.get_remaining_nodes(&all_nodes) {
-            /*let mut actors_in_heuristic_order: Vec<(usize, i32)> = vec![0, 1]
-                .iter()
-                .map(|actor| {
-                    (
-                        *actor,
-                        meta_graph
-                            .edges
-                            .get(&(state_result.locations[*actor].clone(), node.clone()))
-                            .unwrap()
-                            .as_ref()
-                            .unwrap()
-                            .len() as i32,
-                    )
-                })
-                .collect();
-            actors_in_heuristic_order.sort_by(|a, b| a.1.cmp(&b.1));
-            //actors_in_heuristic_order.reverse();
-
-            for (actor, _) in actors_in_heuristic_order {*/
-            for actor in [0, 1] {
-                let new_state = state.appended(&node, actor);
-                let new_state_wrapper = SearchStateWrapper::from_state(
-                    &new_state,
-                    &start,
-                    start_time,
-                    &meta_graph,
-                    valves,
-                );
-                if !tried_states.contains(&new_state)
-                    && new_state_wrapper.base_case_score > max_score
-                {
-                    states_to_try.push(new_state_wrapper);
-                }
-            }
-        }
+        Ok(find_max_pressure_2(
+            input,
+            start,
+            params.integer("part_two_minutes") as i32,
+            params.limits(),
+        )
+        .into())
     }
 
-    max_score
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn find_max_pressure(valves: &HashMap<String, Valve>) -> i32 {
-    /*
-    let starting_valve = valves.get("AA").unwrap();
-    let total_time = 30;
-
-    let starting_state = State::new(&starting_valve.name, total_time, 0, BTreeMap::new());
-
-    let mut state = starting_state.apply_action(&Action::Move("DD".to_string()), valves);
-    //println!("{state:?}");
-    state = state.apply_action(&Action::Open, valves);
-    //println!("{state:?}");
-
-    //panic!();
-
-    let mut states_to_try: BTreeSet<State> = BTreeSet::new();
-    states_to_try.insert(starting_state);
-
-    let mut tried_states: BTreeSet<State> = BTreeSet::new();
-
-    let mut max_score = 0;
-    while !states_to_try.is_empty() {
-        let state = states_to_try.iter().next().unwrap().clone();
-        states_to_try.remove(&state);
-
-        if tried_states.contains(&state) {
-            continue;
-        }
-        tried_states.insert(state.clone());
-
-        if state.best_case_score(valves) < max_score {
-            continue;
-        }
-
-        if state.score > max_score {
-            println!("{state:?}");
-            max_score = state.score;
-        }
+/// The `start` param names a valve, which has to be in the input, so it can only be checked once
+/// the input has been read.
+fn check_start<'a>(valves: &HashMap<String, Valve>, start: &'a str) -> Result<&'a str, SolveError> {
+    if valves.contains_key(start) {
+        Ok(start)
+    } else {
+        Err(SolveError::new(&format!(
+            "Invalid value for parameter start: expected a valve in the input, found \"{}\"",
+            start
+        )))
+    }
+}
 
-        if state.time_remaining == 0 {
-            continue;
-        }
+fn read_input<T: std::io::Read>(
+    reader: &mut BufReader<T>,
+) -> Result<HashMap<String, Valve>, ParseError> {
+    let mut valves: HashMap<String, Valve> = HashMap::new();
+    for valve in parse_lines(reader, Valve::from_str)? {
+        trace!("{valve:?}");
+        valves.insert(valve.name.clone(), valve);
+    }
 
-        for action in state.get_possible_actions(valves) {
-            let new_state = state.apply_action(&action, valves);
-            if !tried_states.contains(&new_state) {
-                states_to_try.insert(new_state);
-            }
+    for valve in valves.values() {
+        if let Some(unknown) = valve.leads_to.iter().find(|v| !valves.contains_key(*v)) {
+            return Err(ParseError::new(
+                unknown,
+                &format!("a valve that exists, in the tunnels from {}", valve.name),
+            ));
         }
     }
 
-    max_score*/
+    Ok(valves)
+}
 
+/// The most pressure that two actors working together can release, or the most found before
+/// reaching the limits.
+pub fn find_max_pressure_2(
+    valves: &HashMap<String, Valve>,
+    start: &str,
+    start_time: i32,
+    limits: &Limits,
+) -> Best<i32> {
     let meta_graph = MetaGraph::from_valves(valves);
-    println!("{meta_graph:?}");
-
-    let start = "AA".to_string();
-    let all_nodes: Vec<String> = valves.keys().cloned().collect();
-
-    let starting_state = SearchState::new(1);
-
-    let mut states_to_try: BTreeSet<SearchState> = BTreeSet::new();
-    states_to_try.insert(starting_state);
-
-    let mut tried_states: BTreeSet<SearchState> = BTreeSet::new();
-
-    let mut max_score = 0;
-    while !states_to_try.is_empty() {
-        let state = states_to_try.iter().next().unwrap().clone();
-        states_to_try.remove(&state);
-
-        //println!("trying: {state:?}");
-
-        if tried_states.contains(&state) {
-            //println!("already_visited");
-            continue;
-        }
-        tried_states.insert(state.clone());
-
-        let (state_result, actions) = state.to_game_state(&start, 30, &meta_graph, valves);
-        //println!("\t{state_result:?}");
-
-        if state_result.time_remaining[0] < 0 {
-            //println!("too long");
-            continue;
-        }
-
-        if state_result.best_case_score(valves) < max_score {
-            //println!("best score is too low");
-            continue;
-        }
-
-        if state_result.score > max_score {
-            println!("-------------------");
-            println!("{state:?}");
-            println!("{actions:?}");
-            println!("{state_result:?}");
-            max_score = state_result.score;
-        }
+    debug!("{meta_graph:?}");
 
-        for node in state.get_remaining_nodes(&all_nodes) {
-            let new_state = state.appended(&node, 0);
-            if !tried_states.contains(&new_state) {
-                states_to_try.insert(new_state);
-            }
-        }
-    }
+    search_max_pressure(
+        2,
+        &start.to_string(),
+        start_time,
+        &meta_graph,
+        valves,
+        limits,
+    )
+}
 
+/// The most pressure that one actor can release, or the most found before reaching the limits.
+pub fn find_max_pressure(
+    valves: &HashMap<String, Valve>,
+    start: &str,
+    total_time: i32,
+    limits: &Limits,
+) -> Best<i32> {
+    let meta_graph = MetaGraph::from_valves(valves);
+    debug!("{meta_graph:?}");
     for (entry, value) in meta_graph.edges.iter() {
-        println!("{entry:?} => {value:?}");
+        trace!("{entry:?} => {value:?}");
     }
 
-    max_score
+    search_max_pressure(
+        1,
+        &start.to_string(),
+        total_time,
+        &meta_graph,
+        valves,
+        limits,
+    )
 }
 
-#[derive(Eq, PartialEq, Debug, Ord, PartialOrd, Clone)]
-struct SearchStateWrapper {
-    base_case_score: i32,
-    search_state: SearchState,
-}
+/// Finds the most pressure that the actors can release by choosing the order that each of them
+/// opens valves in, or the most found before reaching the limits.
+fn search_max_pressure(
+    num_actors: usize,
+    start: &String,
+    start_time: i32,
+    meta_graph: &MetaGraph,
+    valves: &HashMap<String, Valve>,
+    limits: &Limits,
+) -> Best<i32> {
+    let all_nodes: Vec<String> = valves.keys().cloned().collect();
 
-impl SearchStateWrapper {
-    fn from_state(
-        search_state: &SearchState,
-        start: &String,
-        start_time: i32,
-        meta_graph: &MetaGraph,
-        valves: &HashMap<String, Valve>,
-    ) -> SearchStateWrapper {
-        SearchStateWrapper {
-            base_case_score: search_state
-                .to_game_state(start, start_time, meta_graph, valves)
-                .0
-                .best_case_score(valves),
-            /*base_case_score: search_state
-            .to_game_state(start, start_time, meta_graph, valves)
-            .0
-            .score,*/
-            search_state: search_state.clone(),
+    // Orders that take longer than the time available can't be followed
+    let evaluate = |state: &SearchState| -> Option<State> {
+        let (game_state, _) = state.to_game_state(start, start_time, meta_graph, valves);
+
+        if game_state.time_remaining.iter().any(|t| *t < 0) {
+            None
+        } else {
+            Some(game_state)
         }
+    };
+
+    let result = search::branch_and_bound_with_limits(
+        SearchState::new(num_actors),
+        |state| {
+            state
+                .get_remaining_nodes(&all_nodes)
+                .iter()
+                .flat_map(|node| (0..num_actors).map(move |actor| state.appended(node, actor)))
+                .collect::<Vec<_>>()
+        },
+        |state| evaluate(state).map_or(i32::MIN, |s| s.score),
+        |state| evaluate(state).map_or(i32::MIN, |s| s.best_case_score(valves)),
+        limits,
+    );
+
+    debug!(
+        "Expanded {} orders{}, the best was {:?}",
+        result.expanded,
+        if result.complete {
+            ""
+        } else {
+            " before stopping early"
+        },
+        result.best
+    );
+
+    Best {
+        value: result.best.map_or(0, |(_, score)| score),
+        complete: result.complete,
     }
 }
 
@@ -388,7 +295,7 @@ impl State {
             .filter(|v| !*self.valve_states.get(&v.name).unwrap_or(&false))
             .collect();
 
-        off_valves.sort_by(|a, b| a.flow_rate.cmp(&b.flow_rate));
+        off_valves.sort_by_key(|a| a.flow_rate);
         off_valves.reverse();
 
         let mut best_score = self.score;
@@ -418,7 +325,7 @@ impl State {
     }
 }
 
-#[derive(Eq, PartialEq, Debug, Ord, PartialOrd, Clone)]
+#[derive(Eq, PartialEq, Debug, Ord, PartialOrd, Clone, Hash)]
 struct SearchState {
     visited_nodes: Vec<Vec<String>>,
 }
@@ -496,17 +403,6 @@ struct Path {
 }
 
 impl Path {
-    fn new() -> Path {
-        Path { valves: vec![] }
-    }
-
-    fn appended(&self, node: &str) -> Path {
-        let mut valves = self.valves.clone();
-        valves.push(node.to_string());
-
-        Path { valves }
-    }
-
     fn to_actions(&self) -> Vec<Action> {
         let mut actions: Vec<Action> = self
             .valves
@@ -540,33 +436,15 @@ impl MetaGraph {
     }
 
     fn bfs(source: &String, destination: &String, valves: &HashMap<String, Valve>) -> Option<Path> {
-        let mut visited: HashSet<String> = HashSet::new();
-        // \/ \/ \/ Comment out to allow self revisit
-        //visited.insert(source.clone());
-
-        let mut to_visit: Vec<(String, Path)> = vec![];
-        for neighbor in valves.get(source).unwrap().leads_to.iter() {
-            to_visit.push((neighbor.clone(), Path::new().appended(neighbor)));
-        }
-
-        while !to_visit.is_empty() {
-            let (current, path) = to_visit.remove(0);
-
-            if visited.contains(&current) {
-                continue;
-            }
-            visited.insert(current.clone());
-
-            if current == *destination {
-                return Some(path);
-            }
-
-            for neighbor in valves.get(&current).unwrap().leads_to.iter() {
-                to_visit.push((neighbor.clone(), path.appended(neighbor)));
-            }
-        }
-
-        None
+        let leads_to = |valve: &String| valves.get(valve).unwrap().leads_to.clone();
+
+        // Searching from the tunnels out of the source means that a path from a valve back to
+        // itself goes around a loop
+        search::bfs(leads_to(source), leads_to, |valve| valve == destination)
+            .path
+            .map(|path| Path {
+                valves: path.states,
+            })
     }
 }
 
@@ -577,47 +455,90 @@ enum Action {
 }
 
 #[derive(Debug)]
-struct Valve {
-    name: String,
-    flow_rate: i32,
-    leads_to: Vec<String>,
+pub struct Valve {
+    pub name: String,
+    pub flow_rate: i32,
+    pub leads_to: Vec<String>,
 }
 
 impl Valve {
-    fn from_str(s: &str) -> Valve {
-        let parts: Vec<&str> = s.split("; ").collect();
+    fn from_str(s: &str) -> Result<Valve, ParseError> {
+        let invalid = || {
+            ParseError::new(
+                s,
+                "\"Valve <name> has flow rate=<rate>; tunnels lead to valves <name>, ...\"",
+            )
+        };
+
+        let (name, rest) = s
+            .strip_prefix("Valve ")
+            .and_then(|rest| rest.split_once(" has flow rate="))
+            .ok_or_else(invalid)?;
+        let (flow_rate, tunnels) = rest.split_once("; ").ok_or_else(invalid)?;
+        let tunnels = tunnels
+            .strip_prefix("tunnels lead to valves ")
+            .or_else(|| tunnels.strip_prefix("tunnel leads to valve "))
+            .ok_or_else(invalid)?;
+
+        Ok(Valve {
+            name: name.to_string(),
+            flow_rate: parse_number(flow_rate, "a flow rate")?,
+            leads_to: tunnels.split(", ").map(|v| v.to_string()).collect(),
+        })
+    }
+}
 
-        let name = parts[0]
-            .split(" has")
-            .next()
-            .unwrap()
-            .split("Valve ")
-            .last()
-            .unwrap()
-            .to_string();
-        let flow_rate: i32 = parts[0].split("rate=").last().unwrap().parse().unwrap();
-
-        let leads_to: Vec<String> = parts[1]
-            .split(", ")
-            .enumerate()
-            .map(|(i, v)| {
-                if i == 0 {
-                    v.split(' ').last().unwrap().to_string()
-                } else {
-                    v.to_string()
-                }
-            })
+/// Generates a connected graph of `size` valves (at least two) starting from `AA`, with about half of
+/// them jammed shut with a flow rate of zero.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let num_valves = size.clamp(2, 26 * 26);
+
+    let mut names = vec!["AA".to_string()];
+    while names.len() < num_valves {
+        let name: String = (0..2)
+            .map(|_| (b'A' + rng.below(26) as u8) as char)
             .collect();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
 
-        Valve {
-            name,
-            flow_rate,
-            leads_to,
+    // A random spanning tree keeps every valve reachable, and a few more tunnels add loops
+    let mut tunnels: Vec<Vec<usize>> = vec![vec![]; num_valves];
+    let mut connect = |a: usize, b: usize| {
+        if a != b && !tunnels[a].contains(&b) {
+            tunnels[a].push(b);
+            tunnels[b].push(a);
         }
+    };
+    for i in 1..num_valves {
+        connect(i, rng.below(i));
     }
+    for _ in 0..num_valves / 2 {
+        connect(rng.below(num_valves), rng.below(num_valves));
+    }
+
+    (0..num_valves)
+        .map(|i| {
+            let flow_rate = if i == 0 || rng.chance(0.5) {
+                0
+            } else {
+                rng.between(1, 25)
+            };
+            let leads_to: Vec<&str> = tunnels[i].iter().map(|&t| names[t].as_str()).collect();
+            let tunnels = match leads_to.as_slice() {
+                [valve] => format!("tunnel leads to valve {}", valve),
+                _ => format!("tunnels lead to valves {}", leads_to.join(", ")),
+            };
+
+            format!(
+                "Valve {} has flow rate={}; {}\n",
+                names[i], flow_rate, tunnels
+            )
+        })
+        .collect()
 }
 
-/*
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -626,49 +547,33 @@ mod tests {
     use std::fs::File;
 
     #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    fn test_part_2_stops_at_limit() {
+        let f = File::open("inputs/sixteen_example.txt").unwrap();
+        let valves = read_input(&mut BufReader::new(f)).unwrap();
 
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
+        let limits = Limits::default().with_max_expanded(1000);
+        let best = find_max_pressure_2(&valves, "AA", 26, &limits);
 
-        assert_eq!(expected, actual)
+        assert!(!best.complete);
+        assert!(best.value <= 1707);
     }
 
     #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
+    fn test_start_valve() {
+        let input = "Valve BB has flow rate=0; tunnels lead to valves CC\n\
+                     Valve CC has flow rate=5; tunnel leads to valve BB\n";
+        let valves = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
+        let mut params = Params::defaults(&DaySixteen.params(), false);
+
+        assert_eq!(
+            "Invalid value for parameter start: expected a valve in the input, found \"AA\"",
+            DaySixteen.part_one(&valves, &params).unwrap_err().message
+        );
 
-        assert_eq!(expected, actual)
+        params.set("start", "BB").unwrap();
+        assert_eq!(
+            Ok(Answer::Integer(5 * 28)),
+            DaySixteen.part_one(&valves, &params)
+        );
     }
 }
-*/
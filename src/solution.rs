@@ -0,0 +1,410 @@
+use std::any::Any;
+use std::error;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::answer::Answer;
+use crate::image::Image;
+use crate::params::{Param, Params};
+use crate::parse::ParseError;
+use crate::random::Rng;
+use crate::visualize::Frames;
+use crate::Error;
+
+/// A single day's puzzle: how to read its input and how to solve each of its two parts.
+pub trait Solution {
+    type Input;
+
+    /// The puzzle's tunable constants, which are passed to both parts.
+    fn params(&self) -> Vec<Param> {
+        vec![]
+    }
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError>;
+
+    fn part_one(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError>;
+
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError>;
+
+    /// Pictures of the simulation behind a part, for the days that have something to watch.
+    fn frames(
+        &self,
+        _input: &Self::Input,
+        _part: Part,
+        _params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        Ok(None)
+    }
+
+    /// A picture of the final state of a part, for the days that solve the puzzle on a grid.
+    fn image(
+        &self,
+        _input: &Self::Input,
+        _part: Part,
+        _params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        Ok(None)
+    }
+
+    /// A random input that this day can solve, with `size` controlling how long it is (e.g. its
+    /// number of lines).
+    fn generate(&self, _rng: &mut Rng, _size: usize) -> Option<String> {
+        None
+    }
+}
+
+/// Type-erased form of `Solution`, so that days with different input types can live side by side in
+/// the registry.
+pub trait ErasedSolution: Sync {
+    fn params(&self) -> Vec<Param>;
+
+    fn parse(&self, reader: &mut dyn Read) -> Result<Box<dyn Any>, ParseError>;
+
+    fn solve(&self, part: Part, input: &dyn Any, params: &Params) -> Result<Answer, SolveError>;
+
+    fn frames(
+        &self,
+        part: Part,
+        input: &dyn Any,
+        params: &Params,
+    ) -> Result<Option<Frames>, SolveError>;
+
+    fn image(
+        &self,
+        part: Part,
+        input: &dyn Any,
+        params: &Params,
+    ) -> Result<Option<Image>, SolveError>;
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String>;
+}
+
+impl<S> ErasedSolution for S
+where
+    S: Solution + Sync,
+    S::Input: 'static,
+{
+    fn params(&self) -> Vec<Param> {
+        Solution::params(self)
+    }
+
+    fn parse(&self, reader: &mut dyn Read) -> Result<Box<dyn Any>, ParseError> {
+        let input = self.read_input(&mut BufReader::new(reader))?;
+
+        Ok(Box::new(input))
+    }
+
+    fn solve(&self, part: Part, input: &dyn Any, params: &Params) -> Result<Answer, SolveError> {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .expect("input was parsed by a different solution");
+
+        match part {
+            Part::One => self.part_one(input, params),
+            Part::Two => self.part_two(input, params),
+        }
+    }
+
+    fn frames(
+        &self,
+        part: Part,
+        input: &dyn Any,
+        params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .expect("input was parsed by a different solution");
+
+        Solution::frames(self, input, part, params)
+    }
+
+    fn image(
+        &self,
+        part: Part,
+        input: &dyn Any,
+        params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .expect("input was parsed by a different solution");
+
+        Solution::image(self, input, part, params)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Solution::generate(self, rng, size)
+    }
+}
+
+/// Why a part has no answer for an input that parsed, such as a maze with no way through it, or a
+/// parameter that doesn't fit the input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SolveError {
+    pub day: Option<u32>,
+    pub message: String,
+}
+
+impl SolveError {
+    pub fn new(message: &str) -> SolveError {
+        SolveError {
+            day: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn for_day(mut self, day: u32) -> SolveError {
+        self.day = Some(day);
+        self
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}: ", day)?;
+        }
+
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for SolveError {}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub fn all() -> [Part; 2] {
+        [Part::One, Part::Two]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Part::One => "one",
+            Part::Two => "two",
+        }
+    }
+}
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Part, String> {
+        match s {
+            "one" | "1" => Ok(Part::One),
+            "two" | "2" => Ok(Part::Two),
+            p => Err(format!("Unknown part: {}", p)),
+        }
+    }
+}
+
+/// An entry in the registry of days, see `get_days`.
+pub struct Day {
+    pub number: u32,
+    pub name: &'static str,
+    pub solution: &'static dyn ErasedSolution,
+}
+
+impl Day {
+    /// The puzzle input for this day, `inputs/<name>.txt`.
+    pub fn input_path(&self) -> PathBuf {
+        PathBuf::from(format!("inputs/{}.txt", self.name))
+    }
+
+    /// The example input from the puzzle description, `inputs/<name>_example.txt`.
+    pub fn example_path(&self) -> PathBuf {
+        PathBuf::from(format!("inputs/{}_example.txt", self.name))
+    }
+
+    /// The default values of this day's parameters, for either the real or the example input.
+    pub fn default_params(&self, example: bool) -> Params {
+        Params::defaults(&self.solution.params(), example)
+    }
+
+    /// Parses the input with this day's solution, attributing any parse error to this day.
+    pub fn parse(&self, reader: &mut dyn Read) -> Result<Box<dyn Any>, ParseError> {
+        self.solution
+            .parse(reader)
+            .map_err(|e| e.for_day(self.number))
+    }
+
+    /// Reads all of the input before parsing it, so that failing to read it is told apart from it
+    /// not making sense, such as not being UTF-8.
+    fn read_and_parse(&self, reader: &mut dyn Read) -> Result<Box<dyn Any>, Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes)?;
+
+        Ok(self.parse(&mut bytes.as_slice())?)
+    }
+
+    /// Solves one part for an input from `parse`, attributing any error to this day.
+    pub fn solve(
+        &self,
+        part: Part,
+        input: &dyn Any,
+        params: &Params,
+    ) -> Result<Answer, SolveError> {
+        self.solution
+            .solve(part, input, params)
+            .map_err(|e| e.for_day(self.number))
+    }
+
+    /// Runs one part against the real input's default parameters.
+    pub fn run(&self, part: Part, reader: &mut dyn Read) -> Result<Answer, Error> {
+        self.run_with_params(part, reader, &self.default_params(false))
+    }
+
+    pub fn run_with_params(
+        &self,
+        part: Part,
+        reader: &mut dyn Read,
+        params: &Params,
+    ) -> Result<Answer, Error> {
+        let input = self.read_and_parse(reader)?;
+
+        Ok(self.solve(part, input.as_ref(), params)?)
+    }
+
+    /// Parses the input and starts the simulation behind one part, or returns `Ok(None)` if this
+    /// day can't be visualized.
+    pub fn frames(
+        &self,
+        part: Part,
+        reader: &mut dyn Read,
+        params: &Params,
+    ) -> Result<Option<Frames>, Error> {
+        let input = self.read_and_parse(reader)?;
+
+        Ok(self
+            .solution
+            .frames(part, input.as_ref(), params)
+            .map_err(|e| e.for_day(self.number))?)
+    }
+
+    /// Parses the input and draws the final state of one part, or returns `Ok(None)` if this day
+    /// has nothing to draw.
+    pub fn image(
+        &self,
+        part: Part,
+        reader: &mut dyn Read,
+        params: &Params,
+    ) -> Result<Option<Image>, Error> {
+        let input = self.read_and_parse(reader)?;
+
+        Ok(self
+            .solution
+            .image(part, input.as_ref(), params)
+            .map_err(|e| e.for_day(self.number))?)
+    }
+
+    /// Generates a random input from a seed, or returns `None` if this day has no generator.
+    pub fn generate(&self, seed: u64, size: usize) -> Option<String> {
+        self.solution.generate(&mut Rng::new(seed), size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use std::fs::File;
+
+    use crate::get_days;
+
+    #[test]
+    fn test_days_are_unique_and_ordered() {
+        let days = get_days();
+
+        for (a, b) in days.iter().zip(days.iter().skip(1)) {
+            assert!(a.number < b.number);
+            assert_ne!(a.name, b.name);
+        }
+    }
+
+    #[test]
+    fn test_default_params_are_allowed() {
+        for day in get_days() {
+            for example in [false, true] {
+                let mut params = day.default_params(example);
+                let defaults: Vec<(String, String)> = params
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.to_string()))
+                    .collect();
+
+                for (name, value) in defaults {
+                    if let Err(e) = params.set(&name, &value) {
+                        panic!("day {}: {}", day.number, e);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_from_registry() {
+        let day = get_days().into_iter().find(|d| d.number == 1).unwrap();
+        let mut f = File::open(day.example_path()).unwrap();
+
+        let expected = Answer::Integer(24000);
+        let actual = day.run(Part::One, &mut f).unwrap();
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_run_malformed_input() {
+        let day = get_days().into_iter().find(|d| d.number == 1).unwrap();
+
+        let error = day
+            .run(Part::One, &mut "1000\n2000\nlots\n".as_bytes())
+            .unwrap_err();
+
+        assert_eq!(
+            "day 1, line 3: expected a number of calories, found \"lots\"",
+            error.to_string()
+        );
+    }
+
+    /// A day whose first part never has an answer.
+    struct Unsolvable;
+
+    impl Solution for Unsolvable {
+        type Input = ();
+
+        fn read_input<R: Read>(&self, _reader: &mut BufReader<R>) -> Result<(), ParseError> {
+            Ok(())
+        }
+
+        fn part_one(&self, _input: &(), _params: &Params) -> Result<Answer, SolveError> {
+            Err(SolveError::new("There is no answer"))
+        }
+
+        fn part_two(&self, _input: &(), _params: &Params) -> Result<Answer, SolveError> {
+            Ok(Answer::Integer(2))
+        }
+    }
+
+    #[test]
+    fn test_run_unsolvable() {
+        let day = Day {
+            number: 26,
+            name: "unsolvable",
+            solution: &Unsolvable,
+        };
+
+        let error = day.run(Part::One, &mut "".as_bytes()).unwrap_err();
+
+        assert!(matches!(error, Error::Solve(_)));
+        assert_eq!("day 26: There is no answer", error.to_string());
+        assert_eq!(
+            Answer::Integer(2),
+            day.run(Part::Two, &mut "".as_bytes()).unwrap()
+        );
+    }
+}
@@ -1,28 +1,37 @@
 use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = calc_sum_signal_strengths(&values);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayTen;
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = build_image(&values);
+impl Solution for DayTen {
+    type Input = Vec<Instruction>;
 
-    println!("{}", answer);
-}
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(calc_sum_signal_strengths(input).into())
+    }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Instruction> {
-    let mut instructions: Vec<Instruction> = Vec::new();
-    for line in reader.lines() {
-        instructions.push(Instruction::from_str(&line.unwrap()));
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(Answer::image(&build_image(input)))
     }
 
-    instructions
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Instruction>, ParseError> {
+    parse_lines(reader, Instruction::from_str)
 }
 
 struct Registers {
@@ -43,17 +52,22 @@ impl Registers {
 }
 
 #[derive(Clone, Debug)]
-enum Instruction {
+pub enum Instruction {
     Noop,
     Addx(i32),
 }
 
 impl Instruction {
-    fn from_str(s: &str) -> Instruction {
+    fn from_str(s: &str) -> Result<Instruction, ParseError> {
         if s == "noop" {
-            Instruction::Noop
+            Ok(Instruction::Noop)
+        } else if let Some(value) = s.strip_prefix("addx ") {
+            Ok(Instruction::Addx(parse_number(value, "a value to add")?))
         } else {
-            Instruction::Addx(s.split(' ').last().unwrap().parse().unwrap())
+            Err(ParseError::new(
+                s,
+                "an instruction (\"noop\" or \"addx <value>\")",
+            ))
         }
     }
 
@@ -65,10 +79,11 @@ impl Instruction {
     }
 }
 
-fn calc_sum_signal_strengths(instructions: &[Instruction]) -> i32 {
+/// The sum of the signal strengths during the 20th, 60th, 100th, 140th, 180th and 220th cycles.
+pub fn calc_sum_signal_strengths(instructions: &[Instruction]) -> i32 {
     let mut registers = Registers::new();
 
-    let important_cycles: HashSet<i32> = vec![20, 60, 100, 140, 180, 220].iter().cloned().collect();
+    let important_cycles: HashSet<i32> = [20, 60, 100, 140, 180, 220].iter().cloned().collect();
 
     let mut signals_sum = 0;
 
@@ -98,7 +113,8 @@ fn calc_sum_signal_strengths(instructions: &[Instruction]) -> i32 {
     signals_sum
 }
 
-fn build_image(instructions: &[Instruction]) -> String {
+/// The image drawn on the CRT, one line per row of pixels.
+pub fn build_image(instructions: &[Instruction]) -> String {
     let mut registers = Registers::new();
 
     let mut image_chars: Vec<char> = vec![];
@@ -135,70 +151,28 @@ fn build_image(instructions: &[Instruction]) -> String {
     image_chars.iter().collect()
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/ten_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+/// Generates `size` instructions, adding more if needed to last the 240 cycles drawn in part two.
+///
+/// The register stays within the 40 pixel wide screen, give or take the sprite's width.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut program = String::new();
+    let (mut x, mut cycles) = (1, 0);
 
-        let expected = 13140;
-        let actual = calc_sum_signal_strengths(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/ten.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 14920;
-        let actual = calc_sum_signal_strengths(&values);
-
-        assert_eq!(expected, actual)
-    }
+    for i in 0.. {
+        if i >= size && cycles >= 240 {
+            break;
+        }
 
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/ten_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = concat!(
-            "##..##..##..##..##..##..##..##..##..##..\n",
-            "###...###...###...###...###...###...###.\n",
-            "####....####....####....####....####....\n",
-            "#####.....#####.....#####.....#####.....\n",
-            "######......######......######......####\n",
-            "#######.......#######.......#######.....\n",
-        );
-        let actual = build_image(&values);
-
-        assert_eq!(expected, actual)
+        if rng.chance(0.3) {
+            program.push_str("noop\n");
+            cycles += 1;
+        } else {
+            let value = rng.between(-1 - x, 40 - x);
+            program.push_str(&format!("addx {}\n", value));
+            x += value;
+            cycles += 2;
+        }
     }
 
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/ten.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = concat!(
-            "###..#..#..##...##...##..###..#..#.####.\n",
-            "#..#.#..#.#..#.#..#.#..#.#..#.#..#....#.\n",
-            "###..#..#.#....#..#.#....###..#..#...#..\n",
-            "#..#.#..#.#....####.#....#..#.#..#..#...\n",
-            "#..#.#..#.#..#.#..#.#..#.#..#.#..#.#....\n",
-            "###...##...##..#..#..##..###...##..####.\n",
-        );
-        let actual = build_image(&values);
-
-        assert_eq!(expected, actual)
-    }
+    program
 }
@@ -1,45 +1,79 @@
 use std::cmp::Ordering;
 use std::fmt;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 use std::iter::Peekable;
 use std::str::Chars;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = sum_indicies_in_correct_order(&values);
+pub struct DayThirteen;
 
-    println!("{}", answer);
-}
+impl Solution for DayThirteen {
+    type Input = Vec<Vec<Element>>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(sum_indicies_in_correct_order(input).into())
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_decoder_key(&values);
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_decoder_key(input).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<Vec<Element>> {
+fn read_input<T: std::io::Read>(
+    reader: &mut BufReader<T>,
+) -> Result<Vec<Vec<Element>>, ParseError> {
     let mut entries: Vec<Vec<Element>> = Vec::new();
     let mut entry: Vec<Element> = Vec::new();
-    for line in reader.lines() {
-        let line = line.unwrap();
+    let mut num_lines = 0;
+    for (i, line) in reader.lines().enumerate() {
+        let line = line?;
+        num_lines = i + 1;
         if line.is_empty() {
-            entries.push(entry.clone());
-            entry = vec![];
+            if entry.len() != 2 {
+                return Err(ParseError::new(&line, "a pair of packets").on_line(i + 1));
+            }
+
+            entries.push(std::mem::take(&mut entry));
             continue;
         }
 
-        entry.push(Element::from_str(&line));
+        if entry.len() == 2 {
+            return Err(
+                ParseError::new(&line, "a blank line after each pair of packets").on_line(i + 1),
+            );
+        }
+
+        entry.push(Element::from_str(&line).map_err(|e| e.on_line(i + 1))?);
     }
 
-    entries.push(entry.clone());
+    // The last pair needn't be followed by a blank line
+    if !entry.is_empty() || entries.is_empty() {
+        if entry.len() != 2 {
+            return Err(ParseError::new("", "a pair of packets").on_line(num_lines + 1));
+        }
+
+        entries.push(entry);
+    }
 
-    entries
+    Ok(entries)
 }
 
 #[derive(Clone, PartialEq, Eq)]
-enum Element {
+pub enum Element {
     List(Vec<Element>),
     Value(i32),
 }
@@ -63,7 +97,8 @@ impl std::fmt::Debug for Element {
 }
 
 impl Element {
-    fn from_str(s: &str) -> Element {
+    fn from_str(s: &str) -> Result<Element, ParseError> {
+        let invalid = || ParseError::new(s, "a packet, such as \"[1,[2,3]]\"");
         let mut stack = vec![Element::List(vec![])];
 
         let mut char_iter = s.chars().peekable();
@@ -77,19 +112,23 @@ impl Element {
             let token = token.unwrap();
             match token.as_str() {
                 "[" => stack.push(Element::List(vec![])),
-                "]" => {
+                "]" if stack.len() > 1 => {
                     let e = stack.pop().unwrap();
                     stack.last_mut().unwrap().push(e)
                 }
+                "]" => return Err(invalid()),
                 "," => (),
                 value => {
-                    let v = Element::Value(value.parse().unwrap());
+                    let v = Element::Value(parse_number(value, "a number or a list")?);
                     stack.last_mut().unwrap().push(v);
                 }
             }
         }
 
-        stack.pop().unwrap().get_list()[0].clone()
+        match stack.pop().unwrap().get_list().as_slice() {
+            [packet @ Element::List(_)] if stack.is_empty() => Ok(packet.clone()),
+            _ => Err(invalid()),
+        }
     }
 
     fn get_token(char_iter: &mut Peekable<Chars>) -> Option<String> {
@@ -152,7 +191,7 @@ impl Element {
         let s = self.get_list();
         let o = other.get_list();
 
-        for i in 0..*vec![s.len(), o.len()].iter().max().unwrap() {
+        for i in 0..*[s.len(), o.len()].iter().max().unwrap() {
             if i >= s.len() {
                 return Ordering::Less;
             }
@@ -193,7 +232,8 @@ impl PartialOrd for Element {
     }
 }
 
-fn sum_indicies_in_correct_order(values: &[Vec<Element>]) -> i32 {
+/// The sum of the indices, counting from 1, of the pairs that are in the right order.
+pub fn sum_indicies_in_correct_order(values: &[Vec<Element>]) -> i32 {
     values
         .iter()
         .enumerate()
@@ -203,10 +243,14 @@ fn sum_indicies_in_correct_order(values: &[Vec<Element>]) -> i32 {
         .sum::<usize>() as i32
 }
 
-fn find_decoder_key(values: &[Vec<Element>]) -> i32 {
+/// The product of where the two divider packets end up once all of the packets are sorted.
+pub fn find_decoder_key(values: &[Vec<Element>]) -> i32 {
     let mut packets: Vec<Element> = values.iter().flatten().cloned().collect();
 
-    let divider_packets = vec![Element::from_str("[[2]]"), Element::from_str("[[6]]")];
+    let divider_packets = [
+        Element::from_str("[[2]]").unwrap(),
+        Element::from_str("[[6]]").unwrap(),
+    ];
 
     divider_packets
         .iter()
@@ -221,56 +265,48 @@ fn find_decoder_key(values: &[Vec<Element>]) -> i32 {
         .product::<usize>() as i32
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/thirteen_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 13;
-        let actual = sum_indicies_in_correct_order(&values);
+/// Generates `size` pairs of packets, nested up to four lists deep.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    fn packet(rng: &mut Rng, depth: usize) -> String {
+        let elements: Vec<String> = (0..rng.between(0, 4))
+            .map(|_| {
+                if depth < 4 && rng.chance(0.3) {
+                    packet(rng, depth + 1)
+                } else {
+                    rng.between(0, 10).to_string()
+                }
+            })
+            .collect();
 
-        assert_eq!(expected, actual)
+        format!("[{}]", elements.join(","))
     }
 
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/thirteen.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 5003;
-        let actual = sum_indicies_in_correct_order(&values);
+    let pairs: Vec<String> = (0..size.max(1))
+        .map(|_| format!("{}\n{}\n", packet(rng, 0), packet(rng, 0)))
+        .collect();
 
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/thirteen_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 140;
-        let actual = find_decoder_key(&values);
+    pairs.join("\n")
+}
 
-        assert_eq!(expected, actual)
-    }
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/thirteen.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 20280;
-        let actual = find_decoder_key(&values);
+    fn test_incomplete_pairs() {
+        for (input, line) in [
+            ("", 1),
+            ("\n", 1),
+            ("[1]\n[2]\n\n[3]\n", 5),
+            ("[1]\n[2]\n[3]\n", 3),
+        ] {
+            let error = read_input(&mut BufReader::new(input.as_bytes())).unwrap_err();
+
+            assert_eq!(Some(line), error.line, "{:?}", input);
+        }
 
-        assert_eq!(expected, actual)
+        let input = read_input(&mut BufReader::new("[1]\n[2]\n\n".as_bytes())).unwrap();
+        assert_eq!(1, input.len());
     }
 }
@@ -1,31 +1,58 @@
 use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = sum_priorities(&values);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayThree;
 
-pub fn part_two() {
-    let numbers = read_input(&mut BufReader::new(io::stdin()));
-    let answer = sum_priorities_of_badges(&numbers);
+impl Solution for DayThree {
+    type Input = Vec<String>;
 
-    println!("{}", answer);
-}
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<String> {
-    let mut numbers: Vec<String> = Vec::new();
-    for line in reader.lines() {
-        numbers.push(line.unwrap().to_string());
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(sum_priorities(input).into())
     }
 
-    numbers
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(sum_priorities_of_badges(input)?.into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<String>, ParseError> {
+    parse_lines(reader, |line| {
+        if line.len() % 2 != 0 || !line.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(ParseError::new(
+                line,
+                "a rucksack of an even number of items (a-z, A-Z)",
+            ));
+        }
+
+        let (left, right) = line.split_at(line.len() / 2);
+        if !left.chars().any(|c| right.contains(c)) {
+            return Err(ParseError::new(
+                line,
+                "a rucksack with an item type in both compartments",
+            ));
+        }
+
+        Ok(line.to_string())
+    })
 }
 
-fn sum_priorities(values: &[String]) -> i32 {
+/// The sum of the priorities of the item type in both compartments of each rucksack.
+pub fn sum_priorities(values: &[String]) -> i32 {
     let mut total = 0;
 
     for value in values.iter() {
@@ -34,7 +61,10 @@ fn sum_priorities(values: &[String]) -> i32 {
         let set_a: HashSet<char> = parts.0.chars().collect();
         let set_b: HashSet<char> = parts.1.chars().collect();
 
-        let intersection = set_a.intersection(&set_b).next().unwrap();
+        let intersection = set_a
+            .intersection(&set_b)
+            .next()
+            .expect("read_input checks for an item type in both compartments");
 
         total += get_priority(*intersection);
     }
@@ -42,7 +72,7 @@ fn sum_priorities(values: &[String]) -> i32 {
     total
 }
 
-fn split_rucksack(value: &String) -> (String, String) {
+fn split_rucksack(value: &str) -> (String, String) {
     let split_index = value.len() / 2;
 
     (
@@ -59,7 +89,8 @@ fn get_priority(c: char) -> i32 {
     }
 }
 
-fn sum_priorities_of_badges(values: &[String]) -> i32 {
+/// The sum of the priorities of the item type that each group of three rucksacks has in common.
+pub fn sum_priorities_of_badges(values: &[String]) -> Result<i32, SolveError> {
     let mut total = 0;
 
     for i in 0..(values.len() / 3) {
@@ -75,80 +106,70 @@ fn sum_priorities_of_badges(values: &[String]) -> i32 {
         let intersection_abc: HashSet<char> =
             intersection_ab.intersection(&set_c).cloned().collect();
 
-        let intersection = intersection_abc.iter().next().unwrap();
+        let intersection = intersection_abc.iter().next().ok_or_else(|| {
+            SolveError::new(&format!(
+                "The rucksacks on lines {} to {} have no item type in common",
+                a + 1,
+                c + 1
+            ))
+        })?;
 
         total += get_priority(*intersection);
     }
 
-    total
+    Ok(total)
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/three_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        for v in values.iter() {
-            println!("{}", v);
+/// Generates `size` rucksacks, rounded up to a whole number of groups of three.
+///
+/// Each rucksack has exactly one item type in both compartments, and each group has exactly one
+/// item type in all three of its rucksacks, as the puzzle promises.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut rucksacks = String::new();
+
+    for _ in 0..size.max(1).div_ceil(3) {
+        let mut items: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+        rng.shuffle(&mut items);
+
+        // Split the remaining items into a left and right pool for each rucksack, so that nothing
+        // else is shared between compartments or rucksacks
+        let (badge, shared, rest) = (items[0], &items[1..4], &items[4..]);
+        let pools: Vec<&[char]> = rest.chunks(rest.len() / 6).collect();
+
+        for (i, shared) in shared.iter().enumerate() {
+            let (left_pool, right_pool) = (pools[i * 2], pools[i * 2 + 1]);
+            let len = rng.between(3, 16) as usize;
+
+            let mut left: Vec<char> = (0..len - 1).map(|_| *rng.choose(left_pool)).collect();
+            let mut right: Vec<char> = (0..len - 2).map(|_| *rng.choose(right_pool)).collect();
+            left.push(*shared);
+            right.push(*shared);
+            right.push(badge);
+            if rng.chance(0.5) {
+                std::mem::swap(&mut left, &mut right);
+            }
+            rng.shuffle(&mut left);
+            rng.shuffle(&mut right);
+
+            rucksacks.extend(left.iter().chain(right.iter()));
+            rucksacks.push('\n');
         }
-
-        let expected = 157;
-        let actual = sum_priorities(&values);
-
-        assert_eq!(expected, actual);
     }
 
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/three.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        for v in values.iter() {
-            println!("{}", v);
-        }
-
-        let expected = 7674;
-        let actual = sum_priorities(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/three_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        for v in values.iter() {
-            println!("{}", v);
-        }
-
-        let expected = 70;
-        let actual = sum_priorities_of_badges(&values);
+    rucksacks
+}
 
-        assert_eq!(expected, actual);
-    }
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/three.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        for v in values.iter() {
-            println!("{}", v);
-        }
-
-        let expected = 2805;
-        let actual = sum_priorities_of_badges(&values);
+    fn test_nothing_in_common() {
+        let error = read_input(&mut BufReader::new("aA\nabab\n".as_bytes())).unwrap_err();
+        assert_eq!(Some(1), error.line);
 
-        assert_eq!(expected, actual);
+        let input = read_input(&mut BufReader::new("aa\nbb\ncc\n".as_bytes())).unwrap();
+        assert!(sum_priorities_of_badges(&input).is_err());
     }
 }
@@ -1,157 +1,114 @@
 use std::collections::HashSet;
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_shortest_path(&values);
+use crate::answer::Answer;
+use crate::grid::{Grid, Position};
+use crate::image::{Color, Image};
+use crate::params::Params;
+use crate::parse::{read_lines, ParseError};
+use crate::random::Rng;
+use crate::search;
+use crate::solution::{Part, Solution, SolveError};
 
-    println!("{}", answer);
-}
-
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_shortest_path_2(&values);
-
-    println!("{}", answer);
-}
+pub struct DayTwelve;
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Graph {
-    Graph::from_lines(&reader.lines().map(|l| l.unwrap()).collect::<Vec<String>>())
-}
+impl Solution for DayTwelve {
+    type Input = Graph;
 
-#[derive(Debug, Eq, PartialEq, Hash, Clone)]
-struct Position {
-    row: i32,
-    column: i32,
-}
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-impl Position {
-    fn new(r: i32, c: i32) -> Position {
-        Position { row: r, column: c }
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        find_shortest_path(input)
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::new("There is no way up from the start to the end"))
     }
 
-    fn moved(&self, row_diff: i32, column_diff: i32) -> Position {
-        Position::new(self.row + row_diff, self.column + column_diff)
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        find_shortest_path_2(input)
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::new("There is no way up from any lowest square to the end"))
     }
-}
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-struct Path {
-    tiles: Vec<Position>,
-}
+    fn image(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        let starts = match part {
+            Part::One => vec![input.start],
+            Part::Two => input.get_positions_with_height(0),
+        };
 
-impl Path {
-    fn new() -> Path {
-        Path { tiles: vec![] }
+        Ok(Some(path_image(input, starts)))
     }
 
-    fn appended(&self, position: &Position) -> Path {
-        let mut new_path = self.clone();
-        new_path.tiles.push(position.clone());
-
-        new_path
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
     }
 }
 
-struct Graph {
-    tiles: Vec<Vec<i32>>,
-    start: Position,
-    end: Position,
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Graph, ParseError> {
+    Graph::from_lines(&read_lines(reader)?)
 }
 
-impl Graph {
-    fn from_lines(lines: &[String]) -> Graph {
-        let mut tiles = vec![];
-        let mut start = None;
-        let mut end = None;
-        for (r, line) in lines.iter().enumerate() {
-            let mut row = vec![];
-            for (c, char) in line.chars().enumerate() {
-                if char == 'S' {
-                    start = Some(Position::new(r as i32, c as i32));
-                    row.push(0);
-                } else if char == 'E' {
-                    end = Some(Position::new(r as i32, c as i32));
-                    row.push(25);
-                } else {
-                    row.push(char as i32 - 'a' as i32);
-                }
-            }
-
-            tiles.push(row);
-        }
-
-        let start = start.unwrap();
-        let end = end.unwrap();
-
-        Graph { tiles, start, end }
-    }
-
-    fn get_height(&self) -> usize {
-        self.tiles.len()
-    }
-
-    fn get_width(&self) -> usize {
-        self.tiles[0].len()
-    }
-
-    fn is_in_bounds(&self, position: &Position) -> bool {
-        position.row >= 0
-            && position.column >= 0
-            && position.row < self.get_height() as i32
-            && position.column < self.get_width() as i32
-    }
-
-    fn get_neighbors(&self, position: &Position) -> Vec<Position> {
-        vec![
-            position.moved(1, 0),
-            position.moved(-1, 0),
-            position.moved(0, 1),
-            position.moved(0, -1),
-        ]
-        .iter()
-        .filter(|p| self.is_in_bounds(p))
-        .cloned()
-        .collect()
-    }
-
-    fn bfs(&self, source: &Position, destination: &Position) -> Option<i32> {
-        let mut visited: HashSet<Position> = HashSet::new();
-        visited.insert(source.clone());
-
-        let mut to_visit = vec![];
-        for neighbor in self.get_neighbors(source) {
-            if self.is_stepable(source, &neighbor) {
-                to_visit.push((neighbor.clone(), Path::new().appended(&neighbor)));
-            }
-        }
-
-        while !to_visit.is_empty() {
-            let (current, cur_path) = to_visit[0].clone();
-            to_visit.remove(0);
-
-            if visited.contains(&current) {
-                continue;
-            }
-            visited.insert(current.clone());
-
-            if current == *destination {
-                return Some(cur_path.tiles.len() as i32);
-            }
-
-            for neighbor in self.get_neighbors(&current) {
-                if !visited.contains(&neighbor) && self.is_stepable(&current, &neighbor) {
-                    to_visit.push((neighbor.clone(), cur_path.appended(&neighbor)));
-                }
-            }
-        }
+pub struct Graph {
+    pub heights: Grid<i32>,
+    pub start: Position,
+    pub end: Position,
+}
 
-        None
+impl Graph {
+    fn from_lines(lines: &[String]) -> Result<Graph, ParseError> {
+        let heights = Grid::parse(
+            lines,
+            "a row of heights (a-z), with the start (S) and end (E)",
+            |c| match c {
+                'S' => Some(0),
+                'E' => Some(25),
+                'a'..='z' => Some(c as i32 - 'a' as i32),
+                _ => None,
+            },
+        )?;
+
+        let find = |marker: char| {
+            lines.iter().enumerate().find_map(|(r, line)| {
+                line.chars()
+                    .position(|c| c == marker)
+                    .map(|c| Position::new(r as i32, c as i32))
+            })
+        };
+
+        let start = find('S').ok_or_else(|| ParseError::new("", "a start position (S)"))?;
+        let end = find('E').ok_or_else(|| ParseError::new("", "an end position (E)"))?;
+
+        Ok(Graph {
+            heights,
+            start,
+            end,
+        })
+    }
+
+    /// The shortest path up to the end from whichever of the starting positions is closest.
+    fn shortest_path(&self, starts: Vec<Position>) -> Option<search::Path<Position, usize>> {
+        search::bfs(
+            starts,
+            |position| {
+                self.heights
+                    .neighbors4(position)
+                    .filter(|neighbor| self.is_stepable(position, neighbor))
+                    .collect::<Vec<_>>()
+            },
+            |position| *position == self.end,
+        )
+        .path
     }
 
     fn get_value(&self, position: &Position) -> i32 {
-        self.tiles[position.row as usize][position.column as usize]
+        *self.heights.get(position).unwrap()
     }
 
     fn is_stepable(&self, src: &Position, dest: &Position) -> bool {
@@ -162,31 +119,76 @@ impl Graph {
     }
 
     fn get_positions_with_height(&self, height: i32) -> Vec<Position> {
-        let mut matches = vec![];
-        for row in 0..self.get_height() {
-            for column in 0..self.get_width() {
-                let position = Position::new(row as i32, column as i32);
-                if self.get_value(&position) == height {
-                    matches.push(position)
-                }
-            }
+        self.heights
+            .iter()
+            .filter(|(_, h)| **h == height)
+            .map(|(p, _)| p)
+            .collect()
+    }
+}
+
+/// Draws the heights from green valleys to white peaks, with the shortest path in red.
+fn path_image(graph: &Graph, starts: Vec<Position>) -> Image {
+    let path: HashSet<Position> = graph
+        .shortest_path(starts)
+        .map(|path| path.states.into_iter().collect())
+        .unwrap_or_default();
+
+    let low = Color::new(30, 110, 40);
+    let high = Color::new(240, 240, 240);
+    Image::from_bounds(&graph.heights.bounds(), |p| {
+        if *p == graph.end {
+            Color::new(255, 220, 0)
+        } else if path.contains(p) {
+            Color::new(220, 30, 30)
+        } else {
+            low.mix(&high, graph.get_value(p) as f64 / 25.0)
         }
+    })
+}
 
-        matches
-    }
+/// The fewest steps from the start to the end, if the end can be reached.
+pub fn find_shortest_path(graph: &Graph) -> Option<i32> {
+    Some(graph.shortest_path(vec![graph.start])?.cost as i32)
 }
 
-fn find_shortest_path(graph: &Graph) -> i32 {
-    graph.bfs(&graph.start, &graph.end).unwrap()
+/// The fewest steps from any of the lowest squares to the end, if the end can be reached.
+pub fn find_shortest_path_2(graph: &Graph) -> Option<i32> {
+    // Searching from every lowest square at once finds the path from the closest of them
+    let starts = graph.get_positions_with_height(0);
+
+    Some(graph.shortest_path(starts)?.cost as i32)
 }
 
-fn find_shortest_path_2(graph: &Graph) -> i32 {
-    graph
-        .get_positions_with_height(0)
-        .iter()
-        .filter_map(|pos| graph.bfs(pos, &graph.end))
-        .min()
-        .unwrap()
+/// Generates a heightmap with `size` rows that climbs from `S` on the left to `E` on the right.
+///
+/// The height only rises by one from each column to the next, and some squares dip a level below
+/// their column, but never on the start's row, so there is always a way up.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let rows = size.max(1);
+    let columns = (size * 2).max(26);
+    let (start_row, end_row) = (rng.below(rows), rng.below(rows));
+
+    (0..rows)
+        .map(|row| {
+            let mut line: String = (0..columns)
+                .map(|column| {
+                    let mut height = (column * 26 / columns) as u8;
+                    if row != start_row && height > 0 && rng.chance(0.3) {
+                        height -= 1;
+                    }
+
+                    match (row, column) {
+                        (r, 0) if r == start_row => 'S',
+                        (r, c) if r == end_row && c == columns - 1 => 'E',
+                        _ => (b'a' + height) as char,
+                    }
+                })
+                .collect();
+            line.push('\n');
+            line
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -194,51 +196,13 @@ mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
     #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/twelve_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 31;
-        let actual = find_shortest_path(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/twelve.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 383;
-        let actual = find_shortest_path(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/twelve_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 29;
-        let actual = find_shortest_path_2(&values);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/twelve.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 377;
-        let actual = find_shortest_path_2(&values);
+    fn test_no_way_up() {
+        let graph = read_input(&mut BufReader::new("SbcE\n".as_bytes())).unwrap();
+        let params = Params::defaults(&[], false);
 
-        assert_eq!(expected, actual)
+        assert!(DayTwelve.part_one(&graph, &params).is_err());
+        assert!(DayTwelve.part_two(&graph, &params).is_err());
+        assert!(DayTwelve.image(&graph, Part::One, &params).is_ok());
     }
 }
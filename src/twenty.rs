@@ -0,0 +1,296 @@
+use std::io::prelude::*;
+use std::io::BufReader;
+
+use log::debug;
+
+use crate::answer::Answer;
+use crate::params::{Param, Params};
+use crate::parse::{parse_lines, parse_number, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
+
+pub struct DayTwenty;
+
+impl Solution for DayTwenty {
+    type Input = Vec<i64>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn params(&self) -> Vec<Param> {
+        vec![
+            Param::integer(
+                "decryption_key",
+                811589153,
+                "Number that every number is multiplied by in part two",
+            ),
+            Param::integer(
+                "part_two_rounds",
+                10,
+                "Times the numbers are mixed in part two",
+            )
+            .with_range(0..=i32::MAX as i64),
+        ]
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(sum_grove_coordinates(&mix(input, 1))?.into())
+    }
+
+    fn part_two(&self, input: &Self::Input, params: &Params) -> Result<Answer, SolveError> {
+        let key = params.integer("decryption_key");
+        let numbers: Vec<i64> = input
+            .iter()
+            .map(|n| n.checked_mul(key))
+            .collect::<Option<_>>()
+            .ok_or_else(|| {
+                SolveError::new(&format!(
+                    "The numbers are too large to multiply by the decryption key {}",
+                    key
+                ))
+            })?;
+
+        Ok(
+            sum_grove_coordinates(&mix(&numbers, params.integer("part_two_rounds") as usize))?
+                .into(),
+        )
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<i64>, ParseError> {
+    let numbers = parse_lines(reader, |line| parse_number(line, "a number"))?;
+
+    if numbers.iter().filter(|n| **n == 0).count() != 1 {
+        return Err(ParseError::new("", "exactly one 0 in the list"));
+    }
+
+    Ok(numbers)
+}
+
+/// Moves each number, in the order they were given, forward or back around the circle by its
+/// value, repeating for the given number of rounds. Returns the numbers in their final order.
+pub fn mix(numbers: &[i64], rounds: usize) -> Vec<i64> {
+    let mut ring = Ring::new(numbers.len());
+
+    // A number that goes all the way around, past the other n - 1, ends up where it started
+    let num_others = numbers.len() as i64 - 1;
+    if num_others > 0 {
+        for round in 0..rounds {
+            for (id, value) in numbers.iter().enumerate() {
+                let from = ring.remove(id);
+                let to = (from as i64 + value.rem_euclid(num_others)) % num_others;
+                let to = to as usize;
+                ring.insert(to, id);
+            }
+
+            debug!("Mixed round {}", round + 1);
+        }
+    }
+
+    ring.order().map(|id| numbers[id]).collect()
+}
+
+/// The sum of the numbers 1000, 2000 and 3000 places after the 0.
+pub fn sum_grove_coordinates(mixed: &[i64]) -> Result<i64, SolveError> {
+    let zero = mixed.iter().position(|n| *n == 0).unwrap();
+
+    [1000, 2000, 3000]
+        .iter()
+        .try_fold(0i64, |sum, offset| {
+            sum.checked_add(mixed[(zero + offset) % mixed.len()])
+        })
+        .ok_or_else(|| SolveError::new("The grove coordinates add up to too large a number"))
+}
+
+/// The order of the numbers in the circle, split into blocks of about √n, so that moving a number
+/// costs O(√n) instead of the O(n) of shifting everything along in a single `Vec`.
+///
+/// Numbers are identified by where they were in the original list.
+struct Ring {
+    blocks: Vec<Vec<usize>>,
+    /// The block that each number is in.
+    block_of: Vec<usize>,
+    block_size: usize,
+    /// Moves made since the blocks were last evened out.
+    moves: usize,
+}
+
+impl Ring {
+    fn new(len: usize) -> Ring {
+        let block_size = ((len as f64).sqrt() as usize).max(1);
+        let mut ring = Ring {
+            blocks: vec![(0..len).collect()],
+            block_of: vec![0; len],
+            block_size,
+            moves: 0,
+        };
+        ring.rebalance();
+
+        ring
+    }
+
+    /// Splits the numbers into blocks of `block_size` again, as moving them around leaves some
+    /// blocks bigger than others.
+    fn rebalance(&mut self) {
+        let order: Vec<usize> = self.order().collect();
+
+        self.blocks = order
+            .chunks(self.block_size)
+            .map(|chunk| chunk.to_vec())
+            .collect();
+        for (i, block) in self.blocks.iter().enumerate() {
+            for id in block.iter() {
+                self.block_of[*id] = i;
+            }
+        }
+        self.moves = 0;
+    }
+
+    /// Takes a number out of the circle, returning the position that it was at.
+    fn remove(&mut self, id: usize) -> usize {
+        let block = self.block_of[id];
+        let offset = self.blocks[block].iter().position(|i| *i == id).unwrap();
+        self.blocks[block].remove(offset);
+
+        self.blocks[..block].iter().map(|b| b.len()).sum::<usize>() + offset
+    }
+
+    /// Puts a number into the circle, so that `position` numbers come before it.
+    fn insert(&mut self, position: usize, id: usize) {
+        let mut remaining = position;
+        let mut block = 0;
+        while block + 1 < self.blocks.len() && remaining > self.blocks[block].len() {
+            remaining -= self.blocks[block].len();
+            block += 1;
+        }
+
+        self.blocks[block].insert(remaining, id);
+        self.block_of[id] = block;
+
+        self.moves += 1;
+        if self.moves >= self.block_size {
+            self.rebalance();
+        }
+    }
+
+    fn order(&self) -> impl Iterator<Item = usize> + '_ {
+        self.blocks.iter().flatten().copied()
+    }
+}
+
+/// Generates `size` numbers, one of them 0, in the same range as the real input and with repeats
+/// like it has.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut numbers: Vec<i64> = (1..size.max(1))
+        .map(|_| match rng.between(-10000, 9999) {
+            0 => 10000,
+            n => n,
+        })
+        .collect();
+    numbers.insert(rng.below(numbers.len() + 1), 0);
+
+    numbers.iter().map(|n| format!("{}\n", n)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    /// Mixes the numbers by moving them one place at a time along a `Vec`, as the puzzle describes.
+    fn mix_one_step_at_a_time(numbers: &[i64], rounds: usize) -> Vec<i64> {
+        let n = numbers.len();
+        let mut order: Vec<usize> = (0..n).collect();
+
+        for _ in 0..rounds {
+            for (id, value) in numbers.iter().enumerate() {
+                if n < 2 {
+                    continue;
+                }
+
+                let mut position = order.iter().position(|i| *i == id).unwrap();
+                let steps = value.rem_euclid(n as i64 - 1);
+                for _ in 0..steps {
+                    let next = (position + 1) % n;
+                    order.swap(position, next);
+                    position = next;
+                }
+            }
+        }
+
+        order.iter().map(|id| numbers[*id]).collect()
+    }
+
+    /// The same circle of numbers read from the 0, as where the circle starts doesn't matter.
+    fn from_zero(numbers: &[i64]) -> Vec<i64> {
+        let zero = numbers.iter().position(|n| *n == 0).unwrap();
+
+        numbers[zero..]
+            .iter()
+            .chain(numbers[..zero].iter())
+            .copied()
+            .collect()
+    }
+
+    #[test]
+    fn test_mix_example() {
+        let numbers = vec![1, 2, -3, 3, -2, 0, 4];
+
+        assert_eq!(vec![0, 3, -2, 1, 2, -3, 4], from_zero(&mix(&numbers, 1)));
+    }
+
+    #[test]
+    fn test_mix_matches_moving_one_step_at_a_time() {
+        for seed in 0..30 {
+            let mut rng = Rng::new(seed);
+            let size = rng.between(1, 60) as usize;
+            let numbers = read_input(&mut BufReader::new(generate(&mut rng, size).as_bytes()))
+                .unwrap()
+                .iter()
+                // Smaller, to keep the reference quick, but without making another 0
+                .map(|n| n.signum() * (1 + n.abs() % 200))
+                .collect::<Vec<i64>>();
+            let rounds = rng.between(1, 3) as usize;
+
+            assert_eq!(
+                from_zero(&mix_one_step_at_a_time(&numbers, rounds)),
+                from_zero(&mix(&numbers, rounds)),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_one_zero() {
+        assert!(read_input(&mut BufReader::new("1\n2\n".as_bytes())).is_err());
+        assert!(read_input(&mut BufReader::new("0\n2\n0\n".as_bytes())).is_err());
+        assert_eq!(
+            vec![0],
+            mix(
+                &read_input(&mut BufReader::new("0\n".as_bytes())).unwrap(),
+                10
+            )
+        );
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        let input = read_input(&mut BufReader::new(
+            "0\n-2\n9223372036854775807\n".as_bytes(),
+        ))
+        .unwrap();
+        let mut params = Params::defaults(&DayTwenty.params(), false);
+
+        assert!(DayTwenty.part_one(&input, &params).is_ok());
+        assert!(DayTwenty.part_two(&input, &params).is_err());
+
+        params.set("decryption_key", "1").unwrap();
+        assert!(DayTwenty.part_two(&input, &params).is_ok());
+    }
+}
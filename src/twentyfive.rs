@@ -0,0 +1,271 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::iter::Sum;
+use std::ops::Add;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_traits::{ToPrimitive, Zero};
+
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
+
+const DIGITS: [char; 5] = ['=', '-', '0', '1', '2'];
+
+pub struct DayTwentyFive;
+
+impl Solution for DayTwentyFive {
+    type Input = Vec<Snafu>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(input.iter().sum::<Snafu>().to_string().into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        // There is no second puzzle on the last day, so this gives the sum in decimal instead
+        Ok(BigInt::from(&input.iter().sum::<Snafu>()).into())
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Vec<Snafu>, ParseError> {
+    parse_lines(reader, |line| line.parse())
+}
+
+/// A number in SNAFU, which is base 5 with digits from -2 to 2, written `=`, `-`, `0`, `1` and
+/// `2`. Negative numbers need no sign, as they start with a negative digit.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Snafu {
+    /// Each digit, from the least significant, with no zeros at the most significant end, so that
+    /// zero has no digits at all.
+    pub digits: Vec<i8>,
+}
+
+impl Snafu {
+    fn from_digits(mut digits: Vec<i8>) -> Snafu {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+
+        Snafu { digits }
+    }
+}
+
+impl FromStr for Snafu {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Snafu, ParseError> {
+        if s.is_empty() {
+            return Err(ParseError::new(s, "a SNAFU number, such as \"1=-0-2\""));
+        }
+
+        let digits: Option<Vec<i8>> = s
+            .chars()
+            .rev()
+            .map(|c| DIGITS.iter().position(|d| *d == c).map(|i| i as i8 - 2))
+            .collect();
+
+        digits.map(Snafu::from_digits).ok_or_else(|| {
+            ParseError::new(s, "a SNAFU number, made of the digits =, -, 0, 1 and 2")
+        })
+    }
+}
+
+impl fmt::Display for Snafu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return f.write_str("0");
+        }
+
+        let s: String = self
+            .digits
+            .iter()
+            .rev()
+            .map(|d| DIGITS[(d + 2) as usize])
+            .collect();
+        f.write_str(&s)
+    }
+}
+
+/// Adds the digits one at a time like on paper, carrying -1 or 1 when a column adds up to more
+/// than a digit can hold.
+impl<'a> Add<&'a Snafu> for &'a Snafu {
+    type Output = Snafu;
+
+    fn add(self, other: &Snafu) -> Snafu {
+        let len = self.digits.len().max(other.digits.len());
+        let mut digits = Vec::with_capacity(len + 1);
+
+        let mut carry = 0;
+        for i in 0..len {
+            let digit = |n: &Snafu| n.digits.get(i).copied().unwrap_or(0);
+            let column = digit(self) + digit(other) + carry;
+            carry = match column {
+                3..=5 => 1,
+                -5..=-3 => -1,
+                _ => 0,
+            };
+            digits.push(column - carry * 5);
+        }
+        digits.push(carry);
+
+        Snafu::from_digits(digits)
+    }
+}
+
+impl Add for Snafu {
+    type Output = Snafu;
+
+    fn add(self, other: Snafu) -> Snafu {
+        &self + &other
+    }
+}
+
+impl<'a> Sum<&'a Snafu> for Snafu {
+    fn sum<I: Iterator<Item = &'a Snafu>>(iter: I) -> Snafu {
+        iter.fold(Snafu::default(), |total, n| &total + n)
+    }
+}
+
+impl Sum for Snafu {
+    fn sum<I: Iterator<Item = Snafu>>(iter: I) -> Snafu {
+        iter.fold(Snafu::default(), |total, n| total + n)
+    }
+}
+
+impl From<&BigInt> for Snafu {
+    fn from(n: &BigInt) -> Snafu {
+        let mut n = n.clone();
+        let mut digits = vec![];
+        while !n.is_zero() {
+            // The remainder can be negative, so it is moved into 0..5 first, then into -2..=2
+            let remainder = ((&n % 5i32).to_i8().unwrap() + 5) % 5;
+            let digit = if remainder > 2 {
+                remainder - 5
+            } else {
+                remainder
+            };
+
+            digits.push(digit);
+            n = (n - digit) / 5i32;
+        }
+
+        Snafu::from_digits(digits)
+    }
+}
+
+impl From<i64> for Snafu {
+    fn from(n: i64) -> Snafu {
+        Snafu::from(&BigInt::from(n))
+    }
+}
+
+impl From<&Snafu> for BigInt {
+    fn from(snafu: &Snafu) -> BigInt {
+        snafu
+            .digits
+            .iter()
+            .rev()
+            .fold(BigInt::zero(), |n, d| n * 5i32 + *d)
+    }
+}
+
+impl TryFrom<&Snafu> for i64 {
+    type Error = String;
+
+    fn try_from(snafu: &Snafu) -> Result<i64, String> {
+        BigInt::from(snafu)
+            .to_i64()
+            .ok_or_else(|| format!("{} is too big for an i64", snafu))
+    }
+}
+
+/// Generates `size` fuel requirements, positive and up to 20 digits long like the real input.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let len = rng.between(1, 20) as usize;
+            let mut line: String = rng.choose(&["1", "2"]).to_string();
+            line.extend((1..len).map(|_| *rng.choose(&DIGITS)));
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        // From the puzzle, with a couple of negative numbers
+        let examples = [
+            (0, "0"),
+            (1, "1"),
+            (2, "2"),
+            (3, "1="),
+            (4, "1-"),
+            (5, "10"),
+            (8, "2="),
+            (10, "20"),
+            (15, "1=0"),
+            (20, "1-0"),
+            (2022, "1=11-2"),
+            (12345, "1-0---0"),
+            (314159265, "1121-1110-1=0"),
+            (-1, "-"),
+            (-3, "-2"),
+            (-2022, "-2--1="),
+        ];
+
+        for (decimal, snafu) in examples.iter() {
+            assert_eq!(*snafu, Snafu::from(*decimal).to_string());
+            assert_eq!(
+                Ok(*decimal),
+                i64::try_from(&snafu.parse::<Snafu>().unwrap())
+            );
+        }
+
+        assert_eq!(Snafu::default(), "000".parse().unwrap());
+        assert!("".parse::<Snafu>().is_err());
+        assert!("1=3".parse::<Snafu>().is_err());
+    }
+
+    #[test]
+    fn test_add_matches_big_integers() {
+        let mut rng = Rng::new(25);
+        let random = |rng: &mut Rng| -> Snafu {
+            let len = rng.between(1, 40);
+            (0..len)
+                .map(|_| *rng.choose(&DIGITS))
+                .collect::<String>()
+                .parse()
+                .unwrap()
+        };
+
+        for _ in 0..200 {
+            let (a, b) = (random(&mut rng), random(&mut rng));
+            let sum = &a + &b;
+
+            assert_eq!(BigInt::from(&a) + BigInt::from(&b), BigInt::from(&sum));
+            assert_eq!(sum, Snafu::from(&BigInt::from(&sum)));
+        }
+
+        let big = Snafu::from(&"2".repeat(40).parse::<BigInt>().unwrap());
+        assert!(i64::try_from(&big).is_err());
+    }
+}
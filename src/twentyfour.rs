@@ -0,0 +1,370 @@
+use std::io::prelude::*;
+use std::io::BufReader;
+
+use log::debug;
+
+use crate::answer::Answer;
+use crate::grid::{Direction, Grid, Position};
+use crate::params::Params;
+use crate::parse::{read_lines, ParseError};
+use crate::random::Rng;
+use crate::search;
+use crate::solution::{Solution, SolveError};
+
+pub struct DayTwentyFour;
+
+impl Solution for DayTwentyFour {
+    type Input = Valley;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        trips_answer(input, 1)
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        // There, back for the snacks, and there again
+        trips_answer(input, 3)
+    }
+
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
+}
+
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<Valley, ParseError> {
+    Valley::from_lines(&read_lines(reader)?)
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Tile {
+    Wall,
+    Ground,
+    /// Ground with a blizzard on it at the start, blowing in the given direction.
+    Blizzard(Direction),
+}
+
+pub struct Valley {
+    pub tiles: Grid<Tile>,
+    pub start: Position,
+    pub end: Position,
+    /// Where there are blizzards in each minute, until they are all back where they started.
+    pub blizzards: Vec<Grid<bool>>,
+}
+
+impl Valley {
+    fn from_lines(lines: &[String]) -> Result<Valley, ParseError> {
+        let tiles = Grid::parse(
+            lines,
+            "a row of the valley, made of walls (#), ground (.) and blizzards (^, >, v or <)",
+            |c| match c {
+                '#' => Some(Tile::Wall),
+                '.' => Some(Tile::Ground),
+                '^' => Some(Tile::Blizzard(Direction::North)),
+                '>' => Some(Tile::Blizzard(Direction::East)),
+                'v' => Some(Tile::Blizzard(Direction::South)),
+                '<' => Some(Tile::Blizzard(Direction::West)),
+                _ => None,
+            },
+        )?;
+
+        let (width, height) = (tiles.width() as i32, tiles.height() as i32);
+        if width < 3 || height < 3 {
+            return Err(ParseError::new("", "a valley with ground inside its walls"));
+        }
+
+        // The only gaps in the walls are one at the top and one at the bottom
+        let gap_in = |row: i32| -> Result<Position, ParseError> {
+            let line = &lines[row as usize];
+            let mut gaps = (0..width)
+                .map(|column| Position::new(row, column))
+                .filter(|p| tiles.get(p) != Some(&Tile::Wall));
+
+            let expected = match (gaps.next(), gaps.next()) {
+                (Some(gap), None) if gap.column > 0 && gap.column < width - 1 => {
+                    if tiles.get(&gap) == Some(&Tile::Ground) {
+                        return Ok(gap);
+                    }
+                    "no blizzards in the walls"
+                }
+                _ => "a wall with a single gap in it",
+            };
+
+            Err(ParseError::new(line, expected).on_line(row as usize + 1))
+        };
+        let start = gap_in(0)?;
+        let end = gap_in(height - 1)?;
+
+        for row in 1..height - 1 {
+            let line = &lines[row as usize];
+            for column in 0..width {
+                let is_wall = tiles.get(&Position::new(row, column)) == Some(&Tile::Wall);
+                if is_wall != (column == 0 || column == width - 1) {
+                    return Err(ParseError::new(line, "a row of ground between two walls")
+                        .on_line(row as usize + 1));
+                }
+            }
+        }
+
+        // Blizzards blowing up or down the start or end column would leave the valley
+        for (position, tile) in tiles.iter() {
+            if let Tile::Blizzard(direction @ (Direction::North | Direction::South)) = tile {
+                if position.column == start.column || position.column == end.column {
+                    return Err(ParseError::new(
+                        &lines[position.row as usize],
+                        &format!(
+                            "no blizzard blowing {:?} through the gap in the wall",
+                            direction
+                        ),
+                    )
+                    .on_line(position.row as usize + 1));
+                }
+            }
+        }
+
+        let blizzards = simulate_blizzards(&tiles);
+
+        Ok(Valley {
+            tiles,
+            start,
+            end,
+            blizzards,
+        })
+    }
+
+    /// The number of minutes after which every blizzard is back where it started.
+    fn period(&self) -> usize {
+        self.blizzards.len()
+    }
+
+    /// Whether there is a blizzard at a position, `time` minutes after the start.
+    fn has_blizzard(&self, position: &Position, time: usize) -> bool {
+        self.blizzards[time % self.period()].get(position) == Some(&true)
+    }
+
+    /// Whether the expedition can be at a position `time` minutes after the start.
+    fn is_clear(&self, position: &Position, time: usize) -> bool {
+        match self.tiles.get(position) {
+            None | Some(Tile::Wall) => false,
+            _ => {
+                *position == self.start
+                    || *position == self.end
+                    || !self.has_blizzard(position, time)
+            }
+        }
+    }
+
+    /// The fewest minutes to get from one position to another, setting off at `start_time`, or
+    /// `None` if the blizzards always block the way.
+    fn crossing_time(&self, from: Position, to: Position, start_time: usize) -> Option<usize> {
+        // The blizzards repeat, so being in the same place at the same point in their cycle is
+        // the same state, however many minutes have passed
+        let start = Expedition {
+            position: from,
+            phase: start_time % self.period(),
+        };
+
+        search::bfs(
+            vec![start],
+            |expedition| {
+                let phase = (expedition.phase + 1) % self.period();
+
+                // Waiting where it is, or moving in one of the four directions
+                std::iter::once(expedition.position)
+                    .chain(expedition.position.neighbors4())
+                    .filter(|p| self.is_clear(p, phase))
+                    .map(|position| Expedition { position, phase })
+                    .collect::<Vec<_>>()
+            },
+            |expedition| expedition.position == to,
+        )
+        .path
+        .map(|path| path.cost)
+    }
+}
+
+/// Moves the blizzards a minute at a time, marking where they are each minute, for as many minutes
+/// as it takes for them all to be back where they started. Each blizzard wraps around its row or
+/// column inside the walls, so that is the lowest common multiple of the inside's width and height.
+fn simulate_blizzards(tiles: &Grid<Tile>) -> Vec<Grid<bool>> {
+    let (width, height) = (tiles.width(), tiles.height());
+    let period = lcm(width - 2, height - 2);
+
+    let mut blizzards: Vec<(Position, Direction)> = tiles
+        .iter()
+        .filter_map(|(position, tile)| match tile {
+            Tile::Blizzard(direction) => Some((position, *direction)),
+            _ => None,
+        })
+        .collect();
+
+    (0..period)
+        .map(|_| {
+            let mut occupied = Grid::new(width, height, false);
+            for (position, direction) in blizzards.iter_mut() {
+                occupied.set(position, true);
+
+                let next = position.step(*direction, 1);
+                *position = Position::new(
+                    (next.row - 1).rem_euclid(height as i32 - 2) + 1,
+                    (next.column - 1).rem_euclid(width as i32 - 2) + 1,
+                );
+            }
+
+            occupied
+        })
+        .collect()
+}
+
+/// Where the expedition is, and the minute within the cycle of the blizzards.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+struct Expedition {
+    position: Position,
+    phase: usize,
+}
+
+/// The fewest minutes to cross the valley `num_trips` times, going back and forth, or `None` if
+/// one of the crossings can't be made.
+pub fn find_trips_time(valley: &Valley, num_trips: usize) -> Option<usize> {
+    let mut time = 0;
+    let (mut from, mut to) = (valley.start, valley.end);
+
+    for trip in 0..num_trips {
+        let minutes = valley.crossing_time(from, to, time)?;
+        time += minutes;
+        debug!(
+            "Trip {} took {} minutes, arriving at minute {}",
+            trip + 1,
+            minutes,
+            time
+        );
+
+        std::mem::swap(&mut from, &mut to);
+    }
+
+    Some(time)
+}
+
+fn trips_answer(valley: &Valley, num_trips: usize) -> Result<Answer, SolveError> {
+    match find_trips_time(valley, num_trips) {
+        Some(minutes) => Ok((minutes as i64).into()),
+        None => Err(SolveError::new("There is no way across the valley")),
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    a / gcd(a, b) * b
+}
+
+/// Generates a valley `size` wide and about a quarter as deep inside its walls, like the real
+/// input, with the gaps at the top left and bottom right. Valleys that can't be crossed there and
+/// back again are thrown away.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let width = size.max(1);
+    let height = size.div_ceil(4).max(1);
+
+    loop {
+        let wall = |gap: usize| -> String {
+            (0..width + 2)
+                .map(|c| if c == gap { '.' } else { '#' })
+                .collect::<String>()
+        };
+
+        let mut lines = vec![wall(1)];
+        for _ in 0..height {
+            let row: String = (1..=width)
+                .map(|column| {
+                    if !rng.chance(0.3) {
+                        return '.';
+                    }
+
+                    if column == 1 || column == width {
+                        *rng.choose(&['>', '<'])
+                    } else {
+                        *rng.choose(&['^', '>', 'v', '<'])
+                    }
+                })
+                .collect();
+            lines.push(format!("#{}#", row));
+        }
+        lines.push(wall(width));
+
+        let valley = Valley::from_lines(&lines).expect("generated valley is valid");
+        if find_trips_time(&valley, 3).is_some() {
+            return lines.iter().map(|line| format!("{}\n", line)).collect();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn example() -> Valley {
+        read_input(&mut BufReader::new(
+            std::fs::File::open("inputs/twentyfour_example.txt").unwrap(),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_blizzards_after_one_minute() {
+        let valley = example();
+        // From the puzzle, where a digit is where that many blizzards are
+        let after_one_minute = [
+            "#.######", "#.>3.<.#", "#<..<<.#", "#>2.22.#", "#>v..^<#", "######.#",
+        ];
+
+        assert_eq!(12, valley.period());
+        for position in valley.tiles.positions() {
+            let tile = after_one_minute[position.row as usize].as_bytes()[position.column as usize];
+
+            assert_eq!(
+                tile != b'#' && tile != b'.',
+                valley.has_blizzard(&position, 1),
+                "{:?}",
+                position
+            );
+            assert_eq!(
+                valley.has_blizzard(&position, 0),
+                valley.has_blizzard(&position, valley.period())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_valleys() {
+        let parse = |valley: &str| read_input(&mut BufReader::new(valley.as_bytes()));
+
+        assert!(parse("#.###\n#...#\n###.#\n").is_ok());
+        assert!(parse("#..##\n#...#\n###.#\n").is_err());
+        assert!(parse("#.###\n#...#\n#####\n").is_err());
+        assert!(parse("#.###\n#..#.\n###.#\n").is_err());
+        assert!(parse("#.###\n#.^.#\n###.#\n").is_ok());
+        assert!(parse("#.###\n#^..#\n###.#\n").is_err());
+        assert!(parse("#>###\n#...#\n###.#\n").is_err());
+    }
+
+    #[test]
+    fn test_no_way_across() {
+        // The blizzard blows round and round the only tile inside the walls
+        let valley = read_input(&mut BufReader::new("#.#\n#>#\n#.#\n".as_bytes())).unwrap();
+
+        assert_eq!(
+            Err(SolveError::new("There is no way across the valley")),
+            DayTwentyFour.part_one(&valley, &Params::defaults(&[], false))
+        );
+        assert_eq!(Ok(Answer::Integer(18)), trips_answer(&example(), 1));
+    }
+}
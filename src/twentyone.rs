@@ -1,49 +1,80 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_root_value(&values);
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
-    println!("{}", answer);
-}
+pub struct DayTwentyOne;
+
+impl Solution for DayTwentyOne {
+    type Input = HashMap<MonkeyName, Formula>;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = calc_human_value(&values, &"root".to_string(), &"humn".to_string());
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_root_value(input).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(calc_human_value(input, &"root".to_string(), &"humn".to_string()).into())
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> HashMap<MonkeyName, Formula> {
-    let mut monkeys = HashMap::new();
-    for line in reader.lines() {
-        let monkey = parse_monkey(&line.unwrap());
-        monkeys.insert(monkey.0, monkey.1);
+fn read_input<T: std::io::Read>(
+    reader: &mut BufReader<T>,
+) -> Result<HashMap<MonkeyName, Formula>, ParseError> {
+    let monkeys: HashMap<MonkeyName, Formula> =
+        parse_lines(reader, parse_monkey)?.into_iter().collect();
+
+    if !monkeys.contains_key("root") {
+        return Err(ParseError::new("", "a monkey named root"));
+    }
+    for (name, formula) in monkeys.iter() {
+        if let Some((left, right)) = formula.get_args() {
+            for arg in [left, right] {
+                if !monkeys.contains_key(&arg) {
+                    return Err(ParseError::new(
+                        &arg,
+                        &format!("a monkey that exists, in the formula for {}", name),
+                    ));
+                }
+            }
+        }
     }
 
-    monkeys
+    Ok(monkeys)
 }
 
 type Monkey = (String, Formula);
-type MonkeyName = String;
+pub type MonkeyName = String;
 
-fn parse_monkey(line: &str) -> Monkey {
-    let mut parts = line.split(": ");
-    let name = parts.next().unwrap();
-    let formula = Formula::from_str(parts.next().unwrap());
+fn parse_monkey(line: &str) -> Result<Monkey, ParseError> {
+    let (name, formula) = line
+        .split_once(": ")
+        .ok_or_else(|| ParseError::new(line, "a monkey, such as \"root: pppw + sjmn\""))?;
 
-    (name.to_string(), formula)
+    Ok((name.to_string(), Formula::from_str(formula)?))
 }
 
-fn find_root_value(monkeys: &HashMap<MonkeyName, Formula>) -> i64 {
+/// The number that the monkey named root yells.
+pub fn find_root_value(monkeys: &HashMap<MonkeyName, Formula>) -> i64 {
     let mut cache = MonkeyCache::from_monkeys(monkeys);
 
     cache.get(&"root".to_string())
 }
 
-fn calc_human_value(
+/// The number for `human_name` to yell so that both sides of `root_name`'s formula are equal.
+pub fn calc_human_value(
     monkeys: &HashMap<MonkeyName, Formula>,
     root_name: &MonkeyName,
     human_name: &MonkeyName,
@@ -119,23 +150,34 @@ impl MonkeyCache {
 }
 
 #[derive(Debug, Clone)]
-enum Formula {
+pub enum Formula {
     Constant(i64),
     Calculation(MonkeyName, Operation, MonkeyName),
 }
 
 impl Formula {
-    fn from_str(s: &str) -> Formula {
+    fn from_str(s: &str) -> Result<Formula, ParseError> {
         if let Ok(number) = s.parse::<i64>() {
-            return Formula::Constant(number);
+            return Ok(Formula::Constant(number));
         }
 
-        let mut parts = s.split(' ');
-        let monkey_1 = parts.next().unwrap();
-        let operation = Operation::from_char(parts.next().unwrap().chars().next().unwrap());
-        let monkey_2 = parts.next().unwrap();
-
-        Formula::Calculation(monkey_1.to_string(), operation, monkey_2.to_string())
+        let parts: Vec<&str> = s.split(' ').collect();
+        match parts[..] {
+            [monkey_1, operation, monkey_2] if operation.len() == 1 => {
+                let operation = Operation::from_char(operation.chars().next().unwrap())
+                    .ok_or_else(|| ParseError::new(operation, "an operation (+, -, * or /)"))?;
+
+                Ok(Formula::Calculation(
+                    monkey_1.to_string(),
+                    operation,
+                    monkey_2.to_string(),
+                ))
+            }
+            _ => Err(ParseError::new(
+                s,
+                "a number or a calculation, such as \"pppw + sjmn\"",
+            )),
+        }
     }
 
     fn get_args(&self) -> Option<(MonkeyName, MonkeyName)> {
@@ -213,7 +255,7 @@ impl Formula2 {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Operation {
+pub enum Operation {
     Addition,
     Subtraction,
     Multiplication,
@@ -221,13 +263,13 @@ enum Operation {
 }
 
 impl Operation {
-    fn from_char(c: char) -> Operation {
+    fn from_char(c: char) -> Option<Operation> {
         match c {
-            '+' => Operation::Addition,
-            '-' => Operation::Subtraction,
-            '*' => Operation::Multiplication,
-            '/' => Operation::Division,
-            _ => panic!(),
+            '+' => Some(Operation::Addition),
+            '-' => Some(Operation::Subtraction),
+            '*' => Some(Operation::Multiplication),
+            '/' => Some(Operation::Division),
+            _ => None,
         }
     }
 
@@ -243,58 +285,156 @@ impl Operation {
     }
 }
 
-/*
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
+/// Generates `size` monkeys (at least three) whose numbers are worked out in a tree under `root`.
+///
+/// The tree is built from the top down by picking each monkey's number and then choosing an
+/// operation and operands that give it, so every division is exact. Both of root's operands start
+/// out equal, which makes `humn`'s own number the answer to part two.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    struct Tree<'a> {
+        rng: &'a mut Rng,
+        names: HashSet<String>,
+        jobs: Vec<String>,
+    }
 
-    use std::fs::File;
+    impl Tree<'_> {
+        fn new_name(&mut self, is_human: bool) -> String {
+            if is_human {
+                return "humn".to_string();
+            }
 
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+            loop {
+                let name = self.rng.word(4, 4);
+                if name != "root" && name != "humn" && self.names.insert(name.clone()) {
+                    return name;
+                }
+            }
+        }
 
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
+        /// Adds a monkey, plus `size - 1` monkeys under it, that yells `number`.
+        fn add(&mut self, name: &str, number: i64, size: usize, has_human: bool) {
+            if size <= 1 {
+                self.jobs.push(format!("{}: {}", name, number));
+                return;
+            }
 
-        assert_eq!(expected, actual)
-    }
+            let (left, operation, right) = loop {
+                match self.rng.below(4) {
+                    0 if number >= 2 => {
+                        let left = self.rng.between(1, number - 1);
+                        break (left, '+', number - left);
+                    }
+                    1 => {
+                        let right = self.rng.between(1, 20);
+                        break (number + right, '-', right);
+                    }
+                    2 => {
+                        let divisors: Vec<i64> =
+                            (1..=number.min(1000)).filter(|d| number % d == 0).collect();
+                        let left = *self.rng.choose(&divisors);
+                        break (left, '*', number / left);
+                    }
+                    3 if number < 1_000_000 => {
+                        let right = self.rng.between(2, 9);
+                        break (number * right, '/', right);
+                    }
+                    _ => continue,
+                }
+            };
 
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+            let left_size = self.rng.between(1, (size as i64 - 2).max(1)) as usize;
+            let right_size = (size - 1 - left_size).max(1);
+            let human_on_left = self.rng.chance(0.5);
 
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
+            let left_name = self.new_name(has_human && human_on_left && left_size == 1);
+            let right_name = self.new_name(has_human && !human_on_left && right_size == 1);
+            self.jobs.push(format!(
+                "{}: {} {} {}",
+                name, left_name, operation, right_name
+            ));
 
-        assert_eq!(expected, actual)
+            self.add(&left_name, left, left_size, has_human && human_on_left);
+            self.add(&right_name, right, right_size, has_human && !human_on_left);
+        }
     }
 
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    let size = size.max(3);
+    let number = rng.between(1, 1000);
+    let operation = *rng.choose(&['+', '-', '*', '/']);
+    let mut tree = Tree {
+        rng: &mut *rng,
+        names: HashSet::new(),
+        jobs: vec![],
+    };
 
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
+    let left_size = (size - 1) / 2;
+    let right_size = size - 1 - left_size;
+    let left_name = tree.new_name(left_size == 1);
+    let right_name = tree.new_name(false);
+    tree.jobs
+        .push(format!("root: {} {} {}", left_name, operation, right_name));
+    tree.add(&left_name, number, left_size, true);
+    tree.add(&right_name, number, right_size, false);
+
+    let mut jobs = tree.jobs;
+    rng.shuffle(&mut jobs);
+    jobs.iter().map(|job| format!("{}\n", job)).collect()
+}
 
-        assert_eq!(expected, actual)
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    /// Works out what a monkey yells when the human yells `human`, or `None` if one of the
+    /// divisions along the way leaves a remainder.
+    fn evaluate(monkeys: &HashMap<MonkeyName, Formula>, name: &str, human: i64) -> Option<i64> {
+        if name == "humn" {
+            return Some(human);
+        }
+
+        match &monkeys[name] {
+            Formula::Constant(value) => Some(*value),
+            Formula::Calculation(left, operation, right) => {
+                let left = evaluate(monkeys, left, human)?;
+                let right = evaluate(monkeys, right, human)?;
+
+                match operation {
+                    Operation::Division if right == 0 || left % right != 0 => None,
+                    _ => Some(operation.apply(left, right)),
+                }
+            }
+        }
     }
 
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    /// Tries each number from 0 to `max` for the human to yell, until root's two numbers match.
+    fn try_human_values(monkeys: &HashMap<MonkeyName, Formula>, max: i64) -> Option<i64> {
+        let (left, right) = monkeys["root"].get_args().unwrap();
+
+        (0..=max).find(|&human| {
+            let left = evaluate(monkeys, &left, human);
+            left.is_some() && left == evaluate(monkeys, &right, human)
+        })
+    }
 
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
+    #[test]
+    fn test_human_value_matches_trying_values() {
+        let mut num_checked = 0;
+        for seed in 0..40 {
+            let mut rng = Rng::new(seed);
+            let size = rng.between(3, 15) as usize;
+            let input = generate(&mut rng, size);
+            let monkeys = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
+
+            // Some trees need the human to yell a number too big to reach by counting up
+            if let Some(expected) = try_human_values(&monkeys, 10000) {
+                let actual = calc_human_value(&monkeys, &"root".to_string(), &"humn".to_string());
+
+                assert_eq!(expected, actual, "seed {}: {}", seed, input);
+                num_checked += 1;
+            }
+        }
 
-        assert_eq!(expected, actual)
+        assert!(num_checked >= 20, "only {} trees were checked", num_checked);
     }
 }
-*/
@@ -1,119 +1,171 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::prelude::*;
-use std::io::{self, BufReader};
-
-type Direction = Position;
-
-const NORTH: Direction = Position::new(-1, 0);
-const SOUTH: Direction = Position::new(1, 0);
-const EAST: Direction = Position::new(0, 1);
-const WEST: Direction = Position::new(0, -1);
-const NORTH_EAST: Direction = Position::new(-1, 1);
-const NORTH_WEST: Direction = Position::new(-1, -1);
-const SOUTH_EAST: Direction = Position::new(1, 1);
-const SOUTH_WEST: Direction = Position::new(1, -1);
-
-const NORTH_RULE: MoveRule = MoveRule::new([NORTH, NORTH_EAST, NORTH_WEST], &NORTH);
-const SOUTH_RULE: MoveRule = MoveRule::new([SOUTH, SOUTH_EAST, SOUTH_WEST], &SOUTH);
-const EAST_RULE: MoveRule = MoveRule::new([EAST, NORTH_EAST, SOUTH_EAST], &EAST);
-const WEST_RULE: MoveRule = MoveRule::new([WEST, NORTH_WEST, SOUTH_WEST], &WEST);
+use std::io::BufReader;
+use std::iter;
+
+use log::trace;
+
+use crate::answer::Answer;
+use crate::cycle;
+use crate::grid::{Bounds, Direction8, Position};
+use crate::params::Params;
+use crate::parse::{read_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Part, Solution, SolveError};
+use crate::visualize::{Frame, Frames};
+
+const NORTH_RULE: MoveRule = MoveRule::new(
+    [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::NorthWest,
+    ],
+    Direction8::North,
+);
+const SOUTH_RULE: MoveRule = MoveRule::new(
+    [
+        Direction8::South,
+        Direction8::SouthEast,
+        Direction8::SouthWest,
+    ],
+    Direction8::South,
+);
+const EAST_RULE: MoveRule = MoveRule::new(
+    [
+        Direction8::East,
+        Direction8::NorthEast,
+        Direction8::SouthEast,
+    ],
+    Direction8::East,
+);
+const WEST_RULE: MoveRule = MoveRule::new(
+    [
+        Direction8::West,
+        Direction8::NorthWest,
+        Direction8::SouthWest,
+    ],
+    Direction8::West,
+);
+
+pub struct DayTwentyThree;
+
+impl Solution for DayTwentyThree {
+    type Input = State;
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_num_empty_after_rounds(&values, 10);
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_num_empty_after_rounds(input, 10).into())
+    }
 
-    println!("{}", answer);
-}
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        find_first_round_no_move(input)
+            .map(Answer::from)
+            .ok_or_else(|| SolveError::new("The elves never stop moving"))
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_first_round_no_move(&values);
+    fn frames(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        let num_rounds = match part {
+            Part::One => Some(10),
+            Part::Two => None,
+        };
+
+        Ok(Some(spreading_frames(input, num_rounds)))
+    }
 
-    println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> State {
-    State::from_lines(&reader.lines().map(|l| l.unwrap()).collect::<Vec<String>>())
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<State, ParseError> {
+    State::from_lines(&read_lines(reader)?)
 }
 
-fn find_first_round_no_move(initial_state: &State) -> i32 {
-    let mut state = initial_state.clone();
-    let mut prev_state = state.clone();
-    let mut round = 0;
-    loop {
-        state = state.advance(round);
-
-        if round != 0 && state == prev_state {
-            return round + 1;
-        }
+/// The first round in which no elf moves, or `None` if the elves fall into a pattern that repeats
+/// forever with some of them always moving.
+pub fn find_first_round_no_move(initial_state: &State) -> Option<i32> {
+    // The positions alone can repeat a couple of rounds apart while the elves are still moving, so
+    // the state includes which direction the elves look in first. Once they stop, that repeats
+    // four rounds later.
+    let mut first_round_no_move = None;
+    cycle::find_cycle(
+        (initial_state.clone(), 0),
+        |(state, round), _| {
+            let next = state.advance(round);
+            if next == state && first_round_no_move.is_none() {
+                first_round_no_move = Some(round + 1);
+            }
 
-        prev_state = state.clone();
-        round += 1;
-    }
+            (next, round + 1)
+        },
+        |(state, round)| {
+            (
+                state.elf_positions.values().copied().collect::<Vec<_>>(),
+                round % 4,
+            )
+        },
+    );
+
+    first_round_no_move
 }
 
-fn find_num_empty_after_rounds(initial_state: &State, num_rounds: i32) -> i32 {
+/// How many empty tiles are in the smallest rectangle around the elves after `num_rounds`.
+pub fn find_num_empty_after_rounds(initial_state: &State, num_rounds: i32) -> i32 {
     let mut state = initial_state.clone();
-    //state.print();
-    //println!("===============================================================");
+    trace!("Initial state:\n{}", state.render());
     for round in 0..num_rounds {
         state = state.advance(round);
 
-        //state.print();
-        //println!("===============================================================");
+        trace!("After round {}:\n{}", round + 1, state.render());
     }
 
     let bounds = state.get_bounds();
 
-    bounds.num_tiles() - state.elf_positions.len() as i32
+    bounds.area() - state.elf_positions.len() as i32
 }
 
-type ElfId = i32;
-
-#[derive(Debug, Clone)]
-struct Bounds2D {
-    top_left: Position,
-    bottom_right: Position,
-}
-
-impl Bounds2D {
-    fn from_positions(positions: &[Position]) -> Bounds2D {
-        let top = positions.iter().map(|pos| pos.row).min().unwrap();
-        let bottom = positions.iter().map(|pos| pos.row).max().unwrap();
-        let left = positions.iter().map(|pos| pos.column).min().unwrap();
-        let right = positions.iter().map(|pos| pos.column).max().unwrap();
-
-        let top_left = Position::new(top, left);
-        let bottom_right = Position::new(bottom, right);
+/// Draws the elves after each round, until either `num_rounds` have passed or no elf moves.
+fn spreading_frames(initial_state: &State, num_rounds: Option<i32>) -> Frames {
+    let mut state = initial_state.clone();
+    let mut round = 0;
+    let mut finished = false;
 
-        Bounds2D {
-            top_left,
-            bottom_right,
+    let initial = Frame::new("Initial state", state.render());
+    let rounds = iter::from_fn(move || {
+        if finished || Some(round) == num_rounds {
+            return None;
         }
-    }
 
-    fn num_tiles(&self) -> i32 {
-        let num_rows = self.bottom_right.row + 1 - self.top_left.row;
-        let num_columns = self.bottom_right.column + 1 - self.top_left.column;
+        let next = state.advance(round);
+        finished = next == state;
+        state = next;
+        round += 1;
 
-        num_rows * num_columns
-    }
+        let caption = if finished {
+            format!("Round {}, no elf moved", round)
+        } else {
+            format!("Round {}", round)
+        };
 
-    /*fn get_positions_tl_to_br(&self) -> Vec<Position> {
-        let mut positions = vec![];
-        for row in self.top_left.row..=self.bottom_right.row {
-            for column in self.top_left.column..=self.bottom_right.column {
-                positions.push(Position::new(row, column));
-            }
-        }
+        Some(Frame::new(caption, state.render()))
+    });
 
-        positions
-    }*/
+    Box::new(iter::once(initial).chain(rounds))
 }
 
+pub type ElfId = i32;
+
 #[derive(Debug, Clone, Eq, PartialEq)]
-struct State {
-    elf_positions: BTreeMap<ElfId, Position>,
+pub struct State {
+    pub elf_positions: BTreeMap<ElfId, Position>,
 }
 
 impl State {
@@ -121,48 +173,40 @@ impl State {
         State { elf_positions }
     }
 
-    /*fn print(&self) {
-        let bounds = self.get_bounds();
-
-        let mut last_row = None;
-        for position in bounds.get_positions_tl_to_br() {
-            if let Some(prev_row) = last_row {
-                if prev_row < position.row {
-                    println!();
-                    print!("{:3} ", position.row);
-                }
-            } else {
-                print!("{:3} ", position.row);
-            }
-
-            if self.elf_positions.values().any(|p| *p == position) {
-                print!("#");
-            } else {
-                print!(".");
-            }
+    fn render(&self) -> String {
+        let elves: HashSet<Position> = self.elf_positions.values().copied().collect();
 
-            last_row = Some(position.row);
-        }
-
-        println!();
-    }*/
+        self.get_bounds()
+            .render(|position| if elves.contains(position) { '#' } else { '.' })
+    }
 
-    fn from_lines(lines: &[String]) -> State {
+    fn from_lines(lines: &[String]) -> Result<State, ParseError> {
         let mut elf_positions: BTreeMap<ElfId, Position> = BTreeMap::new();
         let mut current_id = 0;
 
         for (row, line) in lines.iter().enumerate() {
             for (column, c) in line.chars().enumerate() {
-                if c == '#' {
-                    let position = Position::new(row as i32, column as i32);
-                    elf_positions.insert(current_id, position);
+                match c {
+                    '#' => {
+                        let position = Position::new(row as i32, column as i32);
+                        elf_positions.insert(current_id, position);
 
-                    current_id += 1;
+                        current_id += 1;
+                    }
+                    '.' => (),
+                    _ => {
+                        return Err(ParseError::new(line, "a row of elves (#) and ground (.)")
+                            .on_line(row + 1))
+                    }
                 }
             }
         }
 
-        State::new(elf_positions)
+        if elf_positions.is_empty() {
+            return Err(ParseError::new("", "at least one elf (#)"));
+        }
+
+        Ok(State::new(elf_positions))
     }
 
     fn has_elf(&self, position: &Position) -> bool {
@@ -174,19 +218,18 @@ impl State {
         let mut proposed_elf_positions: HashMap<Position, Vec<(ElfId, Position)>> = HashMap::new();
 
         for (elf_id, position) in self.elf_positions.iter() {
-            let all_neighbors_empty = position.get_neighbors().iter().all(|n| !self.has_elf(n));
+            let all_neighbors_empty = position.neighbors8().all(|n| !self.has_elf(&n));
 
-            let mut direction = Position::new(0, 0);
+            let mut new_position = *position;
             if !all_neighbors_empty {
                 for rule in rules.iter() {
                     if rule.can_apply(self, position) {
-                        direction = rule.destination;
+                        new_position = position.step(rule.destination, 1);
                         break;
                     }
                 }
             }
 
-            let new_position = position.moved(&direction);
             proposed_elf_positions
                 .entry(new_position)
                 .or_default()
@@ -218,122 +261,61 @@ impl State {
         rules
     }
 
-    fn get_bounds(&self) -> Bounds2D {
-        Bounds2D::from_positions(
-            &self
-                .elf_positions
-                .values()
-                .copied()
-                .collect::<Vec<Position>>(),
-        )
+    fn get_bounds(&self) -> Bounds {
+        Bounds::from_positions(self.elf_positions.values()).unwrap()
     }
 }
 
 #[derive(Clone, Debug)]
 struct MoveRule {
-    check_all_empty: [Direction; 3],
-    destination: Direction,
+    check_all_empty: [Direction8; 3],
+    destination: Direction8,
 }
 
 impl MoveRule {
-    const fn new(check_all_empty: [Direction; 3], destination: &Position) -> MoveRule {
-        let destination = *destination;
-
+    const fn new(check_all_empty: [Direction8; 3], destination: Direction8) -> MoveRule {
         MoveRule {
             check_all_empty,
             destination,
         }
     }
 
-    fn can_apply(&self, state: &State, position: &Direction) -> bool {
-        for direction in self.check_all_empty.iter() {
-            let neighbor = position.moved(direction);
-            if state.has_elf(&neighbor) {
-                return false;
-            }
-        }
-
-        true
+    fn can_apply(&self, state: &State, position: &Position) -> bool {
+        self.check_all_empty
+            .iter()
+            .all(|direction| !state.has_elf(&position.step(*direction, 1)))
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
-struct Position {
-    row: i32,
-    column: i32,
-}
-
-impl Position {
-    const fn new(row: i32, column: i32) -> Position {
-        Position { row, column }
-    }
+/// Generates a `size` by `size` field that elves are scattered across, with at least one elf.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
 
-    fn moved(&self, other: &Position) -> Position {
-        Position::new(self.row + other.row, self.column + other.column)
-    }
+    let mut field: Vec<Vec<char>> = (0..size)
+        .map(|_| {
+            (0..size)
+                .map(|_| if rng.chance(0.4) { '#' } else { '.' })
+                .collect()
+        })
+        .collect();
+    field[rng.below(size)][rng.below(size)] = '#';
 
-    fn get_neighbors(&self) -> Vec<Position> {
-        vec![
-            NORTH, NORTH_EAST, EAST, SOUTH_EAST, SOUTH, SOUTH_WEST, WEST, NORTH_WEST,
-        ]
+    field
         .iter()
-        .map(|direction| self.moved(direction))
+        .map(|row| format!("{}\n", row.iter().collect::<String>()))
         .collect()
-    }
 }
 
-/*
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
     #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
-
-        assert_eq!(expected, actual)
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
+    fn test_positions_repeat_before_elves_stop() {
+        // Both elves step north, then back south, before spreading out west and east
+        let state = read_input(&mut BufReader::new("..\n##\n".as_bytes())).unwrap();
 
-        assert_eq!(expected, actual)
+        assert_eq!(Some(4), find_first_round_no_move(&state));
     }
 }
-*/
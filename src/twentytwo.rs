@@ -1,31 +1,101 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+use std::iter;
+use std::ops::{Add, Mul, Neg, Sub};
 
-pub fn part_one() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_final_position(&values.0, &values.1);
+use log::{log_enabled, trace, Level};
 
-    println!("{}", answer);
-}
+use crate::answer::Answer;
+use crate::grid::{Bounds, Direction, Position, SparseGrid};
+use crate::image::{Color, Image};
+use crate::params::Params;
+use crate::parse::{parse_number, read_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Part, Solution, SolveError};
+use crate::visualize::{Frame, Frames};
+
+pub struct DayTwentyTwo;
+
+impl Solution for DayTwentyTwo {
+    type Input = (Grid, Path);
+
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
+
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_final_position(&input.0, &input.1, &wrapping(&input.0, Part::One)?).into())
+    }
+
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_final_position(&input.0, &input.1, &wrapping(&input.0, Part::Two)?).into())
+    }
+
+    fn frames(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &Params,
+    ) -> Result<Option<Frames>, SolveError> {
+        Ok(Some(walk_frames(
+            &input.0,
+            &input.1,
+            wrapping(&input.0, part)?,
+        )))
+    }
 
-pub fn part_two() {
-    let values = read_input(&mut BufReader::new(io::stdin()));
-    //let answer = find_start_marker_2(&values[0]);
+    fn image(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &Params,
+    ) -> Result<Option<Image>, SolveError> {
+        Ok(Some(walk_image(
+            &input.0,
+            &input.1,
+            &wrapping(&input.0, part)?,
+        )))
+    }
 
-    //println!("{}", answer);
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
+    }
 }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> (Grid, Path) {
-    let lines: Vec<String> = reader.lines().map(|l| l.unwrap()).collect();
+fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Result<(Grid, Path), ParseError> {
+    let lines = read_lines(reader)?;
 
-    let grid = Grid::from_lines(&lines[0..(lines.len() - 2)]);
-    let path = Path::from_str(lines.last().unwrap());
+    let separator = lines
+        .iter()
+        .position(|l| l.is_empty())
+        .unwrap_or(lines.len());
+    if separator == 0 || separator + 2 != lines.len() {
+        return Err(ParseError::new(
+            "",
+            "a map, then an empty line, then a path on the last line",
+        ));
+    }
 
-    (grid, path)
+    let grid = Grid::from_lines(&lines[0..separator])?;
+    let path = Path::from_str(&lines[separator + 1]).map_err(|e| e.on_line(separator + 2))?;
+
+    Ok((grid, path))
 }
 
-fn find_final_position(grid: &Grid, path: &Path) -> i32 {
+/// Part one wraps around the flat map, part two walks over the cube that the map folds into, which
+/// only part two needs the map to be able to do.
+pub fn wrapping(grid: &Grid, part: Part) -> Result<Wrapping, SolveError> {
+    match part {
+        Part::One => Ok(Wrapping::Flat),
+        Part::Two => Cube::fold(grid)
+            .map(Wrapping::Cube)
+            .map_err(|e| SolveError::new(&format!("Could not fold the map into a cube: {}", e))),
+    }
+}
+
+/// The turtle starts on the leftmost open tile of the top row, facing east.
+fn starting_turtle(grid: &Grid) -> Turtle {
     let row = grid
         .tiles
         .iter()
@@ -41,19 +111,28 @@ fn find_final_position(grid: &Grid, path: &Path) -> i32 {
         .min()
         .unwrap();
 
-    let position = Position::new(row, column);
-    let orientation = Position::new(0, 1);
+    Turtle::new(&Position::new(row, column), Direction::East)
+}
 
-    let mut turtle = Turtle::new(&position, &orientation);
+/// The final password, from the row, column and facing at the end of the path.
+pub fn find_final_position(grid: &Grid, path: &Path, wrapping: &Wrapping) -> i32 {
+    let mut turtle = starting_turtle(grid);
 
-    //let mut turtle_positions = BTreeMap::new();
-    //turtle_positions.insert(turtle.position, 'T');
-    //grid.print(&turtle_positions);
+    // Only keep track of where the turtle has been if it is going to be drawn
+    let mut turtle_positions = BTreeMap::new();
+    let tracing = log_enabled!(Level::Trace);
+    if tracing {
+        turtle_positions.insert(turtle.position, turtle.marker());
+        trace!("\n{}", grid.render(&turtle_positions));
+    }
 
     for m in path.movements.iter() {
-        turtle = turtle.apply(grid, m);
-        //turtle_positions.insert(turtle.position, 'T');
-        //grid.print(&turtle_positions);
+        turtle = turtle.apply(grid, wrapping, m);
+
+        if tracing {
+            turtle_positions.insert(turtle.position, turtle.marker());
+            trace!("{:?}\n{}", m, grid.render(&turtle_positions));
+        }
     }
 
     (turtle.position.row + 1) * 1000
@@ -61,13 +140,79 @@ fn find_final_position(grid: &Grid, path: &Path) -> i32 {
         + turtle.get_orientation_value()
 }
 
+/// Walks the turtle along the path, drawing the trail it leaves after each movement.
+fn walk_frames(grid: &Grid, path: &Path, wrapping: Wrapping) -> Frames {
+    let grid = grid.clone();
+    let mut turtle = starting_turtle(&grid);
+    let mut trail = BTreeMap::new();
+    trail.insert(turtle.position, turtle.marker());
+
+    let initial = Frame::new("Start", grid.render(&trail));
+    let walk = path.movements.clone().into_iter().map(move |movement| {
+        match movement {
+            // Take one step at a time so that every tile along the way is part of the trail
+            Movement::Forward(num_steps) => {
+                for _ in 0..num_steps {
+                    turtle = turtle.apply(&grid, &wrapping, &Movement::Forward(1));
+                    trail.insert(turtle.position, turtle.marker());
+                }
+            }
+            _ => {
+                turtle = turtle.apply(&grid, &wrapping, &movement);
+                trail.insert(turtle.position, turtle.marker());
+            }
+        }
+
+        Frame::new(format!("{:?}", movement), grid.render(&trail))
+    });
+
+    Box::new(iter::once(initial).chain(walk))
+}
+
+/// Draws the map with the turtle's trail going from blue at the start to red at the end.
+fn walk_image(grid: &Grid, path: &Path, wrapping: &Wrapping) -> Image {
+    let mut turtle = starting_turtle(grid);
+    let mut trail = vec![turtle.position];
+    for movement in path.movements.iter() {
+        match movement {
+            Movement::Forward(num_steps) => {
+                for _ in 0..*num_steps {
+                    turtle = turtle.apply(grid, wrapping, &Movement::Forward(1));
+                    trail.push(turtle.position);
+                }
+            }
+            _ => turtle = turtle.apply(grid, wrapping, movement),
+        }
+    }
+
+    // Later visits are drawn over earlier ones
+    let last_visits: BTreeMap<Position, usize> =
+        trail.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+    let (start, end) = (Color::new(40, 90, 230), Color::new(240, 50, 50));
+    Image::from_bounds(&grid.bounds(), |p| match last_visits.get(p) {
+        Some(i) if *i == trail.len() - 1 => Color::WHITE,
+        Some(i) => start.mix(&end, *i as f64 / trail.len() as f64),
+        None => match grid.get_tile(p) {
+            None => Color::BLACK,
+            Some(TileType::Empty) => Color::new(200, 200, 190),
+            Some(TileType::Wall) => Color::new(70, 70, 80),
+        },
+    })
+}
+
 #[derive(Debug, Clone)]
-struct Path {
-    movements: Vec<Movement>,
+pub struct Path {
+    pub movements: Vec<Movement>,
 }
 
 impl Path {
-    fn from_str(s: &str) -> Path {
+    fn from_str(s: &str) -> Result<Path, ParseError> {
+        let forward = |buffer: &[char]| -> Result<Movement, ParseError> {
+            let distance = buffer.iter().collect::<String>();
+
+            Ok(Movement::Forward(parse_number(&distance, "a distance")?))
+        };
+
         let mut movements = vec![];
 
         let mut buffer = vec![];
@@ -75,152 +220,106 @@ impl Path {
             match c {
                 'L' => {
                     if !buffer.is_empty() {
-                        let movement =
-                            Movement::Forward(buffer.iter().collect::<String>().parse().unwrap());
-                        movements.push(movement);
+                        movements.push(forward(&buffer)?);
                         buffer.clear();
                     }
                     movements.push(Movement::TurnLeft);
                 }
                 'R' => {
                     if !buffer.is_empty() {
-                        let movement =
-                            Movement::Forward(buffer.iter().collect::<String>().parse().unwrap());
-                        movements.push(movement);
+                        movements.push(forward(&buffer)?);
                         buffer.clear();
                     }
                     movements.push(Movement::TurnRight);
                 }
-                c => buffer.push(c),
+                '0'..='9' => buffer.push(c),
+                _ => {
+                    return Err(ParseError::new(
+                        s,
+                        "a path of distances and turns (L or R), such as \"10R5L5\"",
+                    ))
+                }
             }
         }
 
         if !buffer.is_empty() {
-            let movement = Movement::Forward(buffer.iter().collect::<String>().parse().unwrap());
-            movements.push(movement);
+            movements.push(forward(&buffer)?);
             buffer.clear();
         }
 
-        Path { movements }
+        Ok(Path { movements })
     }
 }
 
 #[derive(Debug, Clone, Copy)]
-enum Movement {
+pub enum Movement {
     Forward(i32),
     TurnLeft,
     TurnRight,
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
-struct Position {
-    row: i32,
-    column: i32,
-}
-
-type Orientation = Position;
-
-impl Position {
-    fn new(row: i32, column: i32) -> Position {
-        Position { row, column }
-    }
-
-    fn moved(&self, row_diff: i32, column_diff: i32) -> Position {
-        Position::new(self.row + row_diff, self.column + column_diff)
-    }
-
-    fn manhatten_length(&self) -> i32 {
-        self.row.abs() + self.column.abs()
-    }
-
-    fn moved_in_direction(&self, direction: &Orientation, num_steps: i32) -> Position {
-        assert_eq!(direction.manhatten_length(), 1);
-
-        self.moved(direction.row * num_steps, direction.column * num_steps)
-    }
-}
-
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-enum TileType {
+pub enum TileType {
     Empty,
     Wall,
 }
 
-#[derive(Debug)]
-struct Grid {
-    tiles: BTreeMap<Position, TileType>,
-    width: i32,
-    height: i32,
+#[derive(Debug, Clone)]
+pub struct Grid {
+    pub tiles: SparseGrid<TileType>,
+    pub width: i32,
+    pub height: i32,
 }
 
 impl Grid {
-    fn new(tiles: BTreeMap<Position, TileType>) -> Grid {
-        let width = tiles.keys().map(|p| p.column).max().unwrap() + 1;
-        let height = tiles.keys().map(|p| p.row).max().unwrap() + 1;
+    fn new(tiles: SparseGrid<TileType>) -> Grid {
+        let bottom_right = tiles.bounds().unwrap().bottom_right;
 
         Grid {
             tiles,
-            width,
-            height,
+            width: bottom_right.column + 1,
+            height: bottom_right.row + 1,
         }
     }
 
-    fn print(&self, marked_positions: &BTreeMap<Position, char>) {
-        for row in 0..self.height {
-            for column in 0..self.width {
-                let position = Position::new(row, column);
-                let tile = self.get_tile(&position);
-
-                print!(
-                    "{}",
-                    match tile {
-                        None =>
-                            if let Some(c) = marked_positions.get(&position) {
-                                *c
-                            } else {
-                                ' '
-                            },
-                        Some(TileType::Empty) =>
-                            if let Some(c) = marked_positions.get(&position) {
-                                *c
-                            } else {
-                                '.'
-                            },
-                        Some(TileType::Wall) =>
-                            if let Some(c) = marked_positions.get(&position) {
-                                *c
-                            } else {
-                                '#'
-                            },
-                    }
-                );
-            }
-            println!();
-        }
+    fn bounds(&self) -> Bounds {
+        Bounds::from_positions(&[
+            Position::origin(),
+            Position::new(self.height - 1, self.width - 1),
+        ])
+        .unwrap()
     }
 
-    fn from_lines(lines: &[String]) -> Grid {
-        let mut tiles = BTreeMap::new();
-        for (row, line) in lines.iter().enumerate() {
-            for column in 0..line.len() {
-                let c = line.chars().nth(column);
-                let position = Position::new(row as i32, column as i32);
+    /// Draws the map, with any marked positions drawn as their character instead.
+    fn render(&self, marked_positions: &BTreeMap<Position, char>) -> String {
+        self.bounds()
+            .render(|position| match marked_positions.get(position) {
+                Some(c) => *c,
+                None => match self.get_tile(position) {
+                    None => ' ',
+                    Some(TileType::Empty) => '.',
+                    Some(TileType::Wall) => '#',
+                },
+            })
+    }
 
-                match c {
-                    None => (),
-                    Some(' ') => (),
-                    Some('.') => {
-                        tiles.insert(position, TileType::Empty);
-                    }
-                    Some('#') => {
-                        tiles.insert(position, TileType::Wall);
-                    }
-                    Some(_) => panic!(),
-                }
-            }
+    fn from_lines(lines: &[String]) -> Result<Grid, ParseError> {
+        let tiles = SparseGrid::parse(
+            lines,
+            &[' '],
+            "a row of the map (open tiles \".\", walls \"#\" and spaces)",
+            |c| match c {
+                '.' => Some(TileType::Empty),
+                '#' => Some(TileType::Wall),
+                _ => None,
+            },
+        )?;
+
+        if !tiles.iter().any(|(_, t)| *t == TileType::Empty) {
+            return Err(ParseError::new("", "a map with at least one open tile"));
         }
 
-        Grid::new(tiles)
+        Ok(Grid::new(tiles))
     }
 
     fn get_tile(&self, position: &Position) -> Option<TileType> {
@@ -230,10 +329,10 @@ impl Grid {
     fn move_position_with_wrapping(
         &self,
         position: &Position,
-        direction: &Orientation,
+        direction: Direction,
         num_steps: i32,
     ) -> Position {
-        let position = position.moved_in_direction(direction, num_steps);
+        let position = position.step(direction, num_steps);
 
         Position::new(
             position.row.rem_euclid(self.height),
@@ -241,148 +340,482 @@ impl Grid {
         )
     }
 
-    fn advance(
+    /// The next tile in the direction, coming back on the other side of the map after its edge.
+    fn next_tile_wrapping(&self, position: &Position, direction: Direction) -> Position {
+        let mut next = self.move_position_with_wrapping(position, direction, 1);
+        while self.get_tile(&next).is_none() {
+            next = self.move_position_with_wrapping(&next, direction, 1);
+        }
+
+        next
+    }
+}
+
+/// How the turtle gets back onto the map when it walks off the edge of it.
+#[derive(Debug, Clone)]
+pub enum Wrapping {
+    /// Comes back on the opposite side of the map, as in part one.
+    Flat,
+    /// Walks over the edge of the cube that the map folds into, as in part two.
+    Cube(Cube),
+}
+
+impl Wrapping {
+    /// The tile one step ahead, and the way the turtle faces once it is there.
+    fn next_tile(
         &self,
-        start_position: &Position,
-        direction: &Orientation,
-        num_steps: i32,
-    ) -> Position {
-        let mut position = *start_position;
-        for _ in 1..=num_steps {
-            let mut candidate_position = self.move_position_with_wrapping(&position, direction, 1);
-            let mut last_valid_position = position;
-
-            let mut done = false;
-            while !done {
-                let tile_type = self.get_tile(&candidate_position);
-                match tile_type {
-                    None => {
-                        candidate_position =
-                            self.move_position_with_wrapping(&candidate_position, direction, 1)
-                    }
-                    Some(tile_type) => match tile_type {
-                        TileType::Empty => {
-                            last_valid_position = candidate_position;
-                            done = true
-                        }
-                        TileType::Wall => {
-                            candidate_position = last_valid_position;
-                            done = true;
-                        }
-                    },
+        grid: &Grid,
+        position: &Position,
+        direction: Direction,
+    ) -> (Position, Direction) {
+        match self {
+            Wrapping::Flat => (grid.next_tile_wrapping(position, direction), direction),
+            Wrapping::Cube(cube) => cube.next_tile(position, direction),
+        }
+    }
+}
+
+/// A direction in the space that the cube sits in, with the cube centered on the origin.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Vector {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+impl Vector {
+    fn new(x: i32, y: i32, z: i32) -> Vector {
+        Vector { x, y, z }
+    }
+
+    fn dot(&self, other: &Vector) -> i32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        self + -other
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl Mul<i32> for Vector {
+    type Output = Vector;
+
+    fn mul(self, scale: i32) -> Vector {
+        Vector::new(self.x * scale, self.y * scale, self.z * scale)
+    }
+}
+
+/// Which way a face of the cube points once the map is folded: the directions that its columns
+/// (`right`) and rows (`down`) run in, and the way out of the cube (`normal`).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+struct Face {
+    right: Vector,
+    down: Vector,
+    normal: Vector,
+}
+
+impl Face {
+    /// The face reached by walking off this one in the direction, folded down over the edge
+    /// between them.
+    fn folded(&self, direction: Direction) -> Face {
+        match direction {
+            Direction::East => Face {
+                right: -self.normal,
+                down: self.down,
+                normal: self.right,
+            },
+            Direction::West => Face {
+                right: self.normal,
+                down: self.down,
+                normal: -self.right,
+            },
+            Direction::South => Face {
+                right: self.right,
+                down: -self.normal,
+                normal: self.down,
+            },
+            Direction::North => Face {
+                right: self.right,
+                down: self.normal,
+                normal: -self.down,
+            },
+        }
+    }
+
+    fn toward(&self, direction: Direction) -> Vector {
+        match direction {
+            Direction::East => self.right,
+            Direction::West => -self.right,
+            Direction::South => self.down,
+            Direction::North => -self.down,
+        }
+    }
+
+    /// The direction on the map that runs along `vector` on this face.
+    fn direction_of(&self, vector: Vector) -> Direction {
+        Direction::all()
+            .iter()
+            .copied()
+            .find(|d| self.toward(*d) == vector)
+            .expect("the vector lies along the face")
+    }
+}
+
+/// The map folded into a cube, which is found from the map itself so that any net of six square
+/// faces can be folded.
+#[derive(Debug, Clone)]
+pub struct Cube {
+    edge: i32,
+    /// The faces, keyed by where they are in the net, i.e. their top left tile divided by the
+    /// edge length.
+    faces: BTreeMap<(i32, i32), Face>,
+}
+
+impl Cube {
+    fn fold(grid: &Grid) -> Result<Cube, String> {
+        let num_tiles = grid.tiles.len() as i32;
+        let edge = (1..)
+            .take_while(|e| 6 * e * e <= num_tiles)
+            .last()
+            .filter(|e| 6 * e * e == num_tiles)
+            .ok_or_else(|| format!("{} tiles can't make six square faces", num_tiles))?;
+
+        let mut face_sizes: BTreeMap<(i32, i32), i32> = BTreeMap::new();
+        for (position, _) in grid.tiles.iter() {
+            *face_sizes
+                .entry((position.row / edge, position.column / edge))
+                .or_insert(0) += 1;
+        }
+        if face_sizes.len() != 6 || face_sizes.values().any(|size| *size != edge * edge) {
+            return Err(format!(
+                "the tiles don't split into six {} by {} faces",
+                edge, edge
+            ));
+        }
+
+        // Fold each face down from the one it was reached from in the net, starting with the
+        // first face lying flat
+        let first = *face_sizes.keys().next().unwrap();
+        let mut faces = BTreeMap::new();
+        faces.insert(
+            first,
+            Face {
+                right: Vector::new(1, 0, 0),
+                down: Vector::new(0, 1, 0),
+                normal: Vector::new(0, 0, 1),
+            },
+        );
+
+        let mut to_visit = VecDeque::from([first]);
+        while let Some(cell) = to_visit.pop_front() {
+            let face = faces[&cell];
+            for direction in Direction::all() {
+                let offset: Position = direction.offset();
+                let neighbor = (cell.0 + offset.row, cell.1 + offset.column);
+
+                if face_sizes.contains_key(&neighbor) && !faces.contains_key(&neighbor) {
+                    faces.insert(neighbor, face.folded(direction));
+                    to_visit.push_back(neighbor);
                 }
+            }
+        }
 
-                position = candidate_position;
+        if faces.len() != 6 {
+            return Err("the faces aren't all joined together".to_string());
+        }
+        for (i, a) in faces.values().enumerate() {
+            if faces.values().skip(i + 1).any(|b| a.normal == b.normal) {
+                return Err("two faces end up on the same side of the cube".to_string());
             }
         }
 
-        position
+        Ok(Cube { edge, faces })
+    }
+
+    fn cell_of(&self, position: &Position) -> (i32, i32) {
+        (
+            position.row.div_euclid(self.edge),
+            position.column.div_euclid(self.edge),
+        )
+    }
+
+    /// Where the center of a tile is in space, with coordinates doubled to keep them whole.
+    fn center(&self, position: &Position) -> Vector {
+        let cell = self.cell_of(position);
+        let face = self.faces[&cell];
+        let (row, column) = (
+            position.row - cell.0 * self.edge,
+            position.column - cell.1 * self.edge,
+        );
+
+        face.normal * self.edge
+            + face.right * (2 * column + 1 - self.edge)
+            + face.down * (2 * row + 1 - self.edge)
+    }
+
+    /// The tile one step ahead, and the way the turtle faces once it is there, which changes when
+    /// it walks over an edge of the cube onto a face that isn't next to this one in the net.
+    fn next_tile(&self, position: &Position, direction: Direction) -> (Position, Direction) {
+        let next = position.step(direction, 1);
+        if self.faces.contains_key(&self.cell_of(&next)) {
+            return (next, direction);
+        }
+
+        let face = self.faces[&self.cell_of(position)];
+        let exit = face.toward(direction);
+        let (target_cell, target) = self
+            .faces
+            .iter()
+            .find(|(_, f)| f.normal == exit)
+            .expect("every side of the cube has a face");
+
+        // Stepping over the edge goes half a tile out of this face, then half a tile down the next
+        let next_center = self.center(position) + exit - face.normal;
+
+        let next_row = (next_center.dot(&target.down) + self.edge - 1) / 2;
+        let next_column = (next_center.dot(&target.right) + self.edge - 1) / 2;
+
+        (
+            Position::new(
+                target_cell.0 * self.edge + next_row,
+                target_cell.1 * self.edge + next_column,
+            ),
+            target.direction_of(-face.normal),
+        )
     }
 }
 
 #[derive(Debug, Clone)]
 struct Turtle {
     position: Position,
-    orientation: Orientation,
+    orientation: Direction,
 }
 
 impl Turtle {
-    fn new(position: &Position, orientation: &Orientation) -> Turtle {
-        let position = *position;
-        let orientation = *orientation;
-
+    fn new(position: &Position, orientation: Direction) -> Turtle {
         Turtle {
-            position,
+            position: *position,
             orientation,
         }
     }
 
+    /// How the turtle is drawn, pointing the way it is facing.
+    fn marker(&self) -> char {
+        match self.orientation {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+
     fn get_orientation_value(&self) -> i32 {
         match self.orientation {
-            Position { row: 0, column: 1 } => 0,
-            Position { row: 1, column: 0 } => 1,
-            Position { row: 0, column: -1 } => 2,
-            Position { row: -1, column: 0 } => 3,
-            _ => panic!(),
+            Direction::East => 0,
+            Direction::South => 1,
+            Direction::West => 2,
+            Direction::North => 3,
         }
     }
 
-    fn apply(&self, grid: &Grid, movement: &Movement) -> Turtle {
+    /// Moves or turns the turtle. Moving forward stops early at a wall.
+    fn apply(&self, grid: &Grid, wrapping: &Wrapping, movement: &Movement) -> Turtle {
         match movement {
-            Movement::Forward(num_steps) => Turtle::new(
-                &grid.advance(&self.position, &self.orientation, *num_steps),
-                &self.orientation,
-            ),
-            Movement::TurnRight => self.rotated_right(),
-            Movement::TurnLeft => self.rotated_right().rotated_right().rotated_right(),
+            Movement::Forward(num_steps) => {
+                let mut turtle = self.clone();
+                for _ in 0..*num_steps {
+                    let (position, orientation) =
+                        wrapping.next_tile(grid, &turtle.position, turtle.orientation);
+                    if grid.get_tile(&position) == Some(TileType::Wall) {
+                        break;
+                    }
+
+                    turtle = Turtle::new(&position, orientation);
+                }
+
+                turtle
+            }
+            Movement::TurnRight => Turtle::new(&self.position, self.orientation.turned_right()),
+            Movement::TurnLeft => Turtle::new(&self.position, self.orientation.turned_left()),
         }
     }
+}
 
-    fn rotated_right(&self) -> Turtle {
-        let new_orientation = match self.orientation {
-            Position { row: 0, column: 1 } => Position::new(1, 0),
-            Position { row: 1, column: 0 } => Position::new(0, -1),
-            Position { row: 0, column: -1 } => Position::new(-1, 0),
-            Position { row: -1, column: 0 } => Position::new(0, 1),
-            _ => panic!(),
-        };
+/// Generates a map folded from a cube with `size` tiles along each edge, followed by a path of
+/// `size` moves.
+///
+/// The faces are laid out like either the example or the real input, so that the map can be folded
+/// into a cube in part two.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    let layouts: [&[&str]; 2] = [&["..#.", "###.", "..##"], &[".##", ".#.", "##.", "#.."]];
+    let layout = *rng.choose(&layouts);
+    let edge = size.max(1);
+
+    // The path starts on the first tile of the top row, so that has to be open
+    let start = (0, layout[0].find('#').unwrap() * edge);
+
+    let mut map = String::new();
+    for (face_row, faces) in layout.iter().enumerate() {
+        for row in face_row * edge..(face_row + 1) * edge {
+            for (face_column, face) in faces.trim_end_matches('.').chars().enumerate() {
+                for column in face_column * edge..(face_column + 1) * edge {
+                    map.push(match face {
+                        '.' => ' ',
+                        _ if (row, column) != start && rng.chance(0.1) => '#',
+                        _ => '.',
+                    });
+                }
+            }
+            map.push('\n');
+        }
+    }
 
-        Turtle::new(&self.position, &new_orientation)
+    let mut path = String::new();
+    for i in 0..edge {
+        if i > 0 {
+            path.push(*rng.choose(&['L', 'R']));
+        }
+        path.push_str(&rng.between(1, 2 * edge as i64).to_string());
     }
+
+    format!("{}\n{}\n", map, path)
 }
 
-/*
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
 
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 7;
-        let actual = find_start_marker(&values[0]);
-
-        assert_eq!(expected, actual)
+    /// The eleven ways of unfolding a cube, with its faces marked "#".
+    const NETS: [&[&str]; 11] = [
+        &["#...", "####", "#..."],
+        &[".#..", "####", "#..."],
+        &["..#.", "####", "#..."],
+        &["...#", "####", "#..."],
+        &[".#..", "####", ".#.."],
+        &["..#.", "####", ".#.."],
+        &["##..", ".###", ".#.."],
+        &["##..", ".###", "..#."],
+        &["##..", ".###", "...#"],
+        &["##..", ".##.", "..##"],
+        &["###..", "..###"],
+    ];
+
+    /// A map without walls, laid out as a net of faces with the given edge length.
+    fn open_map(net: &[&str], edge: usize) -> Grid {
+        let lines: Vec<String> = net
+            .iter()
+            .flat_map(|faces| {
+                let row: String = faces
+                    .chars()
+                    .flat_map(|f| iter::repeat_n(if f == '#' { '.' } else { ' ' }, edge))
+                    .collect();
+
+                iter::repeat_n(row, edge)
+            })
+            .collect();
+
+        Grid::from_lines(&lines).unwrap()
     }
 
-    #[ignore]
     #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    fn test_walking_around_cube() {
+        // Mirrored, upside down and transposed nets have the faces joined the other way around
+        let mut nets: Vec<Vec<String>> = vec![];
+        for net in NETS.iter() {
+            let net: Vec<String> = net.iter().map(|r| r.to_string()).collect();
+            let mirrored: Vec<String> = net.iter().map(|r| r.chars().rev().collect()).collect();
+            let flipped: Vec<String> = net.iter().rev().cloned().collect();
+            let transposed: Vec<String> = (0..net[0].len())
+                .map(|i| net.iter().map(|r| r.as_bytes()[i] as char).collect())
+                .collect();
+
+            nets.extend([net, mirrored, flipped, transposed]);
+        }
 
-        let expected = 1287;
-        let actual = find_start_marker(&values[0]);
+        let edge = 3;
+        for net in nets.iter() {
+            let net: Vec<&str> = net.iter().map(|r| r.as_str()).collect();
+            let grid = open_map(&net, edge);
+            let cube = Cube::fold(&grid).unwrap();
+
+            let mut centers: Vec<Vector> = grid.tiles.iter().map(|(p, _)| cube.center(p)).collect();
+            centers.sort_by_key(|c| (c.x, c.y, c.z));
+            centers.dedup();
+            assert_eq!(grid.tiles.len(), centers.len(), "{:?}", net);
+
+            // Walking straight ahead goes around the cube and back to where it started, a tile at
+            // a time, either along a face or over an edge
+            for (start, _) in grid.tiles.iter() {
+                for direction in Direction::all() {
+                    let (mut position, mut facing) = (*start, direction);
+                    for _ in 0..4 * edge {
+                        let (next, next_facing) = cube.next_tile(&position, facing);
+                        assert!(grid.get_tile(&next).is_some(), "{:?} {:?}", net, next);
+
+                        let step = cube.center(&next) - cube.center(&position);
+                        assert!([2, 4].contains(&step.dot(&step)), "{:?} {:?}", net, next);
+
+                        position = next;
+                        facing = next_facing;
+                    }
 
-        assert_eq!(expected, actual)
+                    assert_eq!((*start, direction), (position, facing), "{:?}", net);
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/six_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
+    fn test_fold_and_unfold_face() {
+        let face = Face {
+            right: Vector::new(1, 0, 0),
+            down: Vector::new(0, 1, 0),
+            normal: Vector::new(0, 0, 1),
+        };
 
-        let expected = 19;
-        let actual = find_start_marker_2(&values[0]);
+        for direction in Direction::all() {
+            let folded = face.folded(direction);
 
-        assert_eq!(expected, actual)
+            assert_eq!(face.toward(direction), folded.normal);
+            assert_eq!(face, folded.folded(direction.reversed()));
+        }
     }
 
-    #[ignore]
     #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/six.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 3716;
-        let actual = find_start_marker_2(&values[0]);
-
-        assert_eq!(expected, actual)
+    fn test_fold_invalid() {
+        assert!(Cube::fold(&open_map(&["######"], 2)).is_err());
+        assert!(Cube::fold(&open_map(&["##.##", "#...#"], 2)).is_err());
+        assert!(Cube::fold(&open_map(&["#####"], 2)).is_err());
+
+        let lines = vec!["...".to_string(), "....".to_string()];
+        assert!(Cube::fold(&Grid::from_lines(&lines).unwrap()).is_err());
+
+        // Part one doesn't need the map to fold
+        let input = "...#\n....\n\n3R1\n";
+        let input = read_input(&mut BufReader::new(input.as_bytes())).unwrap();
+        let params = Params::defaults(&[], false);
+        assert!(DayTwentyTwo.part_one(&input, &params).is_ok());
+        assert!(DayTwentyTwo.part_two(&input, &params).is_err());
     }
 }
-*/
@@ -1,40 +1,56 @@
 use std::io::prelude::*;
-use std::io::{self, BufReader};
+use std::io::BufReader;
+
+use crate::answer::Answer;
+use crate::params::Params;
+use crate::parse::{parse_lines, ParseError};
+use crate::random::Rng;
+use crate::solution::{Solution, SolveError};
 
 const ROCK: i32 = 1;
 const PAPER: i32 = 2;
 const SCISSORS: i32 = 3;
 
-pub fn part_one() {
-    let numbers = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_value_assume_true(&numbers);
+pub struct DayTwo;
 
-    println!("{}", answer);
-}
+impl Solution for DayTwo {
+    type Input = Vec<(char, char)>;
 
-pub fn part_two() {
-    let numbers = read_input(&mut BufReader::new(io::stdin()));
-    let answer = find_value_assume_result(&numbers);
+    fn read_input<R: Read>(&self, reader: &mut BufReader<R>) -> Result<Self::Input, ParseError> {
+        read_input(reader)
+    }
 
-    println!("{}", answer);
-}
+    fn part_one(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_value_assume_true(input).into())
+    }
 
-fn read_input<T: std::io::Read>(reader: &mut BufReader<T>) -> Vec<(char, char)> {
-    let mut entries: Vec<(char, char)> = Vec::new();
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let mut parts = line.split(' ');
+    fn part_two(&self, input: &Self::Input, _params: &Params) -> Result<Answer, SolveError> {
+        Ok(find_value_assume_result(input).into())
+    }
 
-        entries.push((
-            parts.next().unwrap().chars().into_iter().next().unwrap(),
-            parts.next().unwrap().chars().into_iter().next().unwrap(),
-        ));
+    fn generate(&self, rng: &mut Rng, size: usize) -> Option<String> {
+        Some(generate(rng, size))
     }
+}
 
-    entries
+fn read_input<T: std::io::Read>(
+    reader: &mut BufReader<T>,
+) -> Result<Vec<(char, char)>, ParseError> {
+    parse_lines(reader, |line| {
+        let chars: Vec<char> = line.chars().collect();
+
+        match chars[..] {
+            [a @ 'A'..='C', ' ', b @ 'X'..='Z'] => Ok((a, b)),
+            _ => Err(ParseError::new(
+                line,
+                "a move (A, B or C) and a response (X, Y or Z), such as \"A Y\"",
+            )),
+        }
+    })
 }
 
-fn find_value_assume_true(guide: &[(char, char)]) -> i32 {
+/// The total score when the second column is the shape to play.
+pub fn find_value_assume_true(guide: &[(char, char)]) -> i32 {
     let mut total = 0;
 
     for (a, b) in guide.iter() {
@@ -91,7 +107,8 @@ enum Res {
     Draw,
 }
 
-fn find_value_assume_result(guide: &[(char, char)]) -> i32 {
+/// The total score when the second column is how the round has to end.
+pub fn find_value_assume_result(guide: &[(char, char)]) -> i32 {
     let mut total = 0;
 
     for (a, b) in guide.iter() {
@@ -108,56 +125,15 @@ fn find_value_assume_result(guide: &[(char, char)]) -> i32 {
     total
 }
 
-#[cfg(test)]
-mod tests {
-    // Note this useful idiom: importing names from outer (for mod tests) scope.
-    use super::*;
-
-    use std::fs::File;
-
-    #[test]
-    fn test_part_1_example() {
-        let f = File::open("inputs/two_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 15;
-        let actual = find_value_assume_true(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_1_actual() {
-        let f = File::open("inputs/two.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 11475;
-        let actual = find_value_assume_true(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[test]
-    fn test_part_2_example() {
-        let f = File::open("inputs/two_example.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 12;
-        let actual = find_value_assume_result(&values);
-
-        assert_eq!(expected, actual);
-    }
-
-    #[ignore]
-    #[test]
-    fn test_part_2_actual() {
-        let f = File::open("inputs/two.txt").unwrap();
-        let values = read_input(&mut BufReader::new(f));
-
-        let expected = 16862;
-        let actual = find_value_assume_result(&values);
-
-        assert_eq!(expected, actual);
-    }
+/// Generates a strategy guide with `size` rounds.
+fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            format!(
+                "{} {}\n",
+                rng.choose(&['A', 'B', 'C']),
+                rng.choose(&['X', 'Y', 'Z'])
+            )
+        })
+        .collect()
 }
@@ -0,0 +1,214 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::image::{Color, Image, ImageFormat};
+
+/// A picture of a simulation at one point in time.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Frame {
+    /// What has just happened, e.g. "Round 3".
+    pub caption: String,
+    /// The grid drawn as text, with a line per row.
+    pub picture: String,
+}
+
+impl Frame {
+    pub fn new(caption: impl Into<String>, picture: String) -> Frame {
+        Frame {
+            caption: caption.into(),
+            picture,
+        }
+    }
+}
+
+/// The frames of a simulation, produced as it runs so that long simulations can be watched
+/// before they finish.
+pub type Frames = Box<dyn Iterator<Item = Frame>>;
+
+/// Shows frames one after another in the terminal.
+#[derive(Debug, Clone)]
+pub struct Player {
+    /// How long to show each frame for, or `None` to wait for enter to be pressed between frames.
+    pub delay: Option<Duration>,
+    /// Only shows every nth frame, plus the last one.
+    pub every: usize,
+}
+
+impl Player {
+    /// Shows the frames on `out`, reading key presses from `keys` when stepping through them.
+    /// Returns the number of frames shown, which is fewer than the number of frames if playback was
+    /// stopped with "q".
+    pub fn play(
+        &self,
+        frames: Frames,
+        out: &mut impl Write,
+        keys: &mut impl BufRead,
+    ) -> io::Result<usize> {
+        let mut frames = every_nth(frames, self.every).peekable();
+
+        let mut shown = 0;
+        while let Some((_, frame)) = frames.next() {
+            let is_last = frames.peek().is_none();
+
+            // Clear the screen and move to the top left, so that frames replace each other
+            write!(out, "\x1b[2J\x1b[H")?;
+            writeln!(out, "{}", frame.caption)?;
+            write!(out, "{}", frame.picture)?;
+            shown += 1;
+
+            match self.delay {
+                Some(delay) => {
+                    out.flush()?;
+                    if !is_last {
+                        thread::sleep(delay);
+                    }
+                }
+                None => {
+                    if is_last {
+                        break;
+                    }
+
+                    write!(out, "[enter] next frame, [q] quit: ")?;
+                    out.flush()?;
+
+                    let mut key = String::new();
+                    if keys.read_line(&mut key)? == 0 || key.trim() == "q" {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(shown)
+    }
+}
+
+/// Keeps every nth frame, starting with the first, and the last frame, along with the number of
+/// each frame.
+pub fn every_nth(frames: Frames, n: usize) -> impl Iterator<Item = (usize, Frame)> {
+    let n = n.max(1);
+    let mut frames = frames.enumerate().peekable();
+
+    iter::from_fn(move || loop {
+        let (i, frame) = frames.next()?;
+        if i % n == 0 || frames.peek().is_none() {
+            return Some((i, frame));
+        }
+    })
+}
+
+/// Saves each frame as an image in `dir`, named after its number, e.g. `frame_00012.png`.
+/// Returns the paths of the images.
+pub fn export_frames(
+    frames: impl Iterator<Item = (usize, Frame)>,
+    dir: &Path,
+    format: ImageFormat,
+    scale: usize,
+) -> Result<Vec<PathBuf>, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+
+    let mut paths = vec![];
+    for (i, frame) in frames {
+        let path = dir.join(format!("frame_{:05}.{}", i, format.extension()));
+        Image::from_text(&frame.picture, Color::for_char)
+            .scaled(scale)
+            .save(&path)?;
+
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    use std::fs::File;
+
+    use crate::get_days;
+    use crate::solution::Part;
+
+    fn numbered_frames(count: usize) -> Frames {
+        Box::new((1..=count).map(|i| Frame::new(format!("Frame {}", i), format!("{}\n", i))))
+    }
+
+    #[test]
+    fn test_play_stepping() {
+        let player = Player {
+            delay: None,
+            every: 1,
+        };
+        let mut out = vec![];
+
+        let shown = player
+            .play(numbered_frames(5), &mut out, &mut "\n\nq\n".as_bytes())
+            .unwrap();
+
+        assert_eq!(3, shown);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Frame 3\n3\n"));
+        assert!(!out.contains("Frame 4"));
+    }
+
+    #[test]
+    fn test_play_every() {
+        let player = Player {
+            delay: Some(Duration::from_millis(0)),
+            every: 3,
+        };
+        let mut out = vec![];
+
+        let shown = player
+            .play(numbered_frames(5), &mut out, &mut io::empty())
+            .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(3, shown);
+        assert!(
+            out.contains("Frame 1\n") && out.contains("Frame 4\n") && out.contains("Frame 5\n")
+        );
+    }
+
+    #[test]
+    fn test_export_frames() {
+        let dir = std::env::temp_dir().join(format!("aoc_frames_{}", std::process::id()));
+
+        let paths =
+            export_frames(every_nth(numbered_frames(4), 2), &dir, ImageFormat::Ppm, 2).unwrap();
+        let names: Vec<String> = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        let first = fs::read(&paths[0]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            vec!["frame_00000.ppm", "frame_00002.ppm", "frame_00003.ppm"],
+            names
+        );
+        assert!(first.starts_with(b"P6\n2 2\n255\n"));
+    }
+
+    #[test]
+    fn test_examples_have_frames() {
+        for day in get_days() {
+            for part in Part::all() {
+                let mut f = File::open(day.example_path()).unwrap();
+                let params = day.default_params(true);
+
+                if let Some(frames) = day.frames(part, &mut f, &params).unwrap() {
+                    let frames: Vec<Frame> = frames.take(3).collect();
+
+                    assert!(!frames.is_empty(), "day {} part {:?}", day.number, part);
+                    assert!(frames.iter().all(|f| !f.picture.is_empty()));
+                }
+            }
+        }
+    }
+}